@@ -3,7 +3,13 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use wager::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use wager::events::{
+    BlocklistEvent, ChoiceEvent, ClaimEvent, ConfigChangeEvent, ConfigEvent, JackpotCommitEvent,
+    JackpotDrawEvent, JackpotRevealEvent, LimitsEvent, PauseEvent, QueueEvent, RatingEvent,
+    ReferrerEvent, RelayKeyEvent, RelayersEvent, RoleEvent, RoundEvent, SelfExclusionEvent,
+    SessionEvent, SidePoolEvent, SweepEvent, VoteEvent, WagerEvent,
+};
+use wager::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, RelayDoc};
 use wager::state::{GenericBalance, State, Wager};
 
 fn main() {
@@ -15,8 +21,33 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(RelayDoc), &out_dir);
 
     export_schema(&schema_for!(State), &out_dir);
     export_schema(&schema_for!(GenericBalance), &out_dir);
     export_schema(&schema_for!(Wager), &out_dir);
+
+    export_schema(&schema_for!(WagerEvent), &out_dir);
+    export_schema(&schema_for!(QueueEvent), &out_dir);
+    export_schema(&schema_for!(RatingEvent), &out_dir);
+    export_schema(&schema_for!(SweepEvent), &out_dir);
+    export_schema(&schema_for!(ConfigChangeEvent), &out_dir);
+    export_schema(&schema_for!(ConfigEvent), &out_dir);
+    export_schema(&schema_for!(RelayKeyEvent), &out_dir);
+    export_schema(&schema_for!(RelayersEvent), &out_dir);
+    export_schema(&schema_for!(BlocklistEvent), &out_dir);
+    export_schema(&schema_for!(SessionEvent), &out_dir);
+    export_schema(&schema_for!(ReferrerEvent), &out_dir);
+    export_schema(&schema_for!(ClaimEvent), &out_dir);
+    export_schema(&schema_for!(LimitsEvent), &out_dir);
+    export_schema(&schema_for!(SelfExclusionEvent), &out_dir);
+    export_schema(&schema_for!(JackpotCommitEvent), &out_dir);
+    export_schema(&schema_for!(JackpotRevealEvent), &out_dir);
+    export_schema(&schema_for!(JackpotDrawEvent), &out_dir);
+    export_schema(&schema_for!(ChoiceEvent), &out_dir);
+    export_schema(&schema_for!(RoundEvent), &out_dir);
+    export_schema(&schema_for!(SidePoolEvent), &out_dir);
+    export_schema(&schema_for!(RoleEvent), &out_dir);
+    export_schema(&schema_for!(PauseEvent), &out_dir);
+    export_schema(&schema_for!(VoteEvent), &out_dir);
 }
@@ -0,0 +1,30 @@
+use bech32::{ToBase32, Variant};
+use ripemd160::Ripemd160;
+use sha2::{Digest, Sha256};
+
+/// Derives the bech32 account address of a secp256k1 public key, compressed or not, the way
+/// the cosmos SDK does: the data part is RIPEMD-160(SHA-256(compressed key)).
+pub fn pubkey_to_address(pubkey: &[u8], prefix: &str) -> Option<String> {
+    let compressed = match pubkey.len() {
+        33 if pubkey[0] == 0x02 || pubkey[0] == 0x03 => pubkey.to_vec(),
+        65 if pubkey[0] == 0x04 => {
+            let mut key = Vec::with_capacity(33);
+            key.push(0x02 | (pubkey[64] & 1));
+            key.extend_from_slice(&pubkey[1..33]);
+            key
+        }
+        _ => return None,
+    };
+    let hash = Ripemd160::digest(&Sha256::digest(&compressed));
+    bech32::encode(prefix, hash.to_base32(), Variant::Bech32).ok()
+}
+
+/// Returns true if the address is a valid bech32 address derived from the public key.
+pub fn pubkey_matches_address(pubkey: &[u8], address: &str) -> bool {
+    match bech32::decode(address) {
+        Ok((prefix, _, Variant::Bech32)) => {
+            pubkey_to_address(pubkey, &prefix).as_deref() == Some(address)
+        }
+        _ => false,
+    }
+}
@@ -1,19 +1,57 @@
 use cosmwasm_std::{
-    entry_point, from_binary, to_binary, Addr, BankMsg, Binary, Deps, DepsMut, Env, MessageInfo,
-    Response, StdResult, SubMsg, WasmMsg,
+    entry_point, from_binary, to_binary, to_vec, Addr, Api, BankMsg, Binary, Deps, DepsMut, Empty,
+    Env, Event, Fraction, MessageInfo, Order, OverflowError, OverflowOperation, Reply, Response,
+    StdError, StdResult, Storage, SubMsg, Timestamp, Uint128, Uint256, WasmMsg,
 };
 
 use cw2::set_contract_version;
-use cw20::{Balance, Cw20CoinVerified, Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw20::{
+    Balance, BalanceResponse as Cw20BalanceResponse, Cw20CoinVerified, Cw20ExecuteMsg,
+    Cw20QueryMsg, Cw20ReceiveMsg, Denom, Expiration,
+};
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
 
+use crate::address::pubkey_matches_address;
 use crate::error::ContractError;
-use crate::msg::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{config, config_read, GenericBalance, State, Wager, WAGERS};
+use crate::events::{
+    BlocklistEvent, ChoiceEvent, ClaimEvent, ConfigChangeEvent, ConfigChangeEventKind, ConfigEvent,
+    JackpotCommitEvent, JackpotDrawEvent, JackpotRevealEvent, LimitsEvent, PauseEvent, QueueEvent,
+    QueueEventKind, RatingEvent, ReferrerEvent, RelayKeyEvent, RelayersEvent, RoleEvent,
+    RoleEventKind, RoundEvent, SelfExclusionEvent, SessionEvent, SessionEventKind, SidePoolEvent,
+    SidePoolEventKind, SweepEvent, VoteEvent, WagerEvent, WagerEventKind,
+};
+use crate::msg::{
+    is_valid_name, AssetSolvency, BeaconQueryMsg, BlockedResponse, ClaimableResponse,
+    ConfigChangesResponse, ConfigUpdate, Cw20HookMsg, Cw721ExecuteMsg, Cw721HookMsg,
+    Cw721ReceiveMsg, ExecuteMsg, InstantiateMsg, JackpotResponse, NonceResponse, OracleQueryMsg,
+    PlayerLimitsResponse, PlayerStatsResponse, PriceResponse, QueryMsg, QueueResponse,
+    RandomnessResponse, RatingResponse, ReferralStatsResponse, RelayAction, RelayDoc, RelayIntent,
+    RolesResponse, RoundsResponse, SessionResponse, SolvencyResponse, TimelockedUpdate,
+    VotesResponse,
+};
+use crate::state::{
+    config, config_read, credit_claimable, escrow_deposit, escrow_withdraw, has_role,
+    is_arbiter_of, next_config_change_id, next_wager_id, rematch_pair, staked_since, BlockedPolicy,
+    ChanceGame, ChanceGameKind, ConfigChange, GenericBalance, Hill, JackpotWin, Nft, PendingAction,
+    PendingDraw, PendingLimits, PendingTransfer, PlayerStats, QueueEntry, RandomnessSource,
+    Rematch, Reveal, Role, Series, Session, Side, SideBet, SidePool, SidePoolStatus, StakeBucket,
+    StakeLimits, State, Vote, Wager, WagerStatus, BLOCKLIST, CLAIMABLE, CONFIG_CHANGES,
+    DEFAULT_PRICE_TOLERANCE_BPS, DEFAULT_RATING, GAME_ARBITERS, JACKPOT, JACKPOT_REVEAL_PERIOD,
+    JACKPOT_WINS, LIMIT_COOLDOWN, MAX_CONFIG_DELAY, MAX_QUEUE_SCAN, MAX_REVEAL_PERIOD,
+    MAX_SELF_EXCLUSION, NONCES, OWNER_COMMITMENTS, PENDING_DRAWS, PENDING_TRANSFER,
+    PLAYER_COMMITMENTS, PLAYER_LIMITS, PLAYER_STATS, QUEUE, QUEUE_ORDER, QUEUE_SEQ, RATINGS,
+    REFERRAL_STATS, REFERRED, REFERRERS, RELAYERS, RELAY_KEYS, REMATCHES, REMATCH_PAIRS,
+    REMATCH_PERIOD, ROLES, SECONDS_PER_DAY, SESSIONS, SIDE_BETS, SIDE_POOLS, STAKE_HISTORY,
+    STAKE_HISTORY_DAYS, TOTAL_ESCROWED, VOTES, WAGERS, WAGER_SEQ,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "duel-dojo:wager";
 const CONTRACT_VERSION: &str = "0.1";
 
+const TRANSFER_FROM_REPLY_ID: u64 = 1;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -25,8 +63,25 @@ pub fn instantiate(
     let state = State {
         creator: info.sender.clone(),
         owner: info.sender,
+        oracle: None,
+        price_tolerance_bps: DEFAULT_PRICE_TOLERANCE_BPS,
+        fee_bps: 0,
+        referral_share_bps: 0,
+        jackpot_share_bps: 0,
+        jackpot_odds: 0,
+        randomness: None,
+        paused: false,
+        approval_threshold: GenericBalance::new(),
+        required_approvals: 0,
+        config_delay: 0,
+        blocked_policy: BlockedPolicy::Refund,
     };
     config(deps.storage).save(&state)?;
+    ROLES.save(
+        deps.storage,
+        (Role::Owner.as_str(), &state.owner),
+        &Empty {},
+    )?;
     Ok(Response::default())
 }
 
@@ -37,20 +92,256 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // while paused no funds come in, wagers can still end and tokens be claimed
+    let adds_funds = matches!(
+        msg,
+        ExecuteMsg::Receive(_)
+            | ExecuteMsg::ReceiveNft(_)
+            | ExecuteMsg::CreateWagerNative { .. }
+            | ExecuteMsg::CreateWagerCw20 { .. }
+            | ExecuteMsg::JoinWagerCw20 { .. }
+            | ExecuteMsg::AddFundsNative { .. }
+            | ExecuteMsg::DepositNative { .. }
+            | ExecuteMsg::CreateChanceWager { .. }
+            | ExecuteMsg::CreateHillWager { .. }
+            | ExecuteMsg::CreateNftWager { .. }
+            | ExecuteMsg::JoinChanceWager { .. }
+            | ExecuteMsg::PlaceSideBet { .. }
+            | ExecuteMsg::JoinQueue { .. }
+            | ExecuteMsg::OfferRematch { .. }
+            | ExecuteMsg::Relay { .. }
+    );
+    if adds_funds && config(deps.storage).load()?.paused {
+        return Err(ContractError::Paused {});
+    }
+
     match msg {
         //DUEL DOJO FUNCTIONS
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
-        ExecuteMsg::CreateWagerNative { wager_id } => {
-            execute_create_wager(deps, env, info.sender, Balance::from(info.funds), wager_id)
-        }
+        ExecuteMsg::ReceiveNft(msg) => receive_nft(deps, env, info, msg),
+        ExecuteMsg::CreateWagerNative {
+            label,
+            stake,
+            stake_value,
+            series_length,
+        } => execute_create_wager(
+            deps,
+            env,
+            info.sender,
+            Balance::from(info.funds),
+            label,
+            None,
+            stake,
+            stake_value,
+            series_length,
+        ),
+        ExecuteMsg::CreateWagerCw20 {
+            token,
+            amount,
+            label,
+            stake,
+            stake_value,
+            series_length,
+        } => execute_pull_cw20(
+            deps,
+            env,
+            info.sender,
+            token,
+            amount,
+            PendingAction::CreateWager {
+                label,
+                stake,
+                stake_value,
+                series_length,
+            },
+        ),
+        ExecuteMsg::JoinWagerCw20 {
+            wager_id,
+            token,
+            amount,
+        } => execute_pull_cw20(
+            deps,
+            env,
+            info.sender,
+            token,
+            amount,
+            PendingAction::JoinWager { wager_id },
+        ),
         ExecuteMsg::AddFundsNative { wager_id } => {
             execute_add_funds(deps, env, info.sender, Balance::from(info.funds), wager_id)
         }
+        ExecuteMsg::DepositNative { wager_id } => {
+            let deposit = GenericBalance::from(Balance::from(info.funds));
+            execute_deposit(deps, env, info.sender, deposit, wager_id)
+        }
+        ExecuteMsg::CreateChanceWager {
+            game,
+            commitment,
+            reveal_period,
+        } => execute_create_chance_wager(
+            deps,
+            env,
+            info.sender,
+            Balance::from(info.funds),
+            game,
+            commitment,
+            reveal_period,
+        ),
+        ExecuteMsg::CreateHillWager { label } => {
+            execute_create_hill_wager(deps, env, info.sender, Balance::from(info.funds), label)
+        }
+        ExecuteMsg::CashOut { wager_id } => execute_cash_out(deps, info, wager_id),
+        ExecuteMsg::CreateNftWager {
+            label,
+            stake,
+            counter_stake,
+        } => execute_create_nft_wager(
+            deps,
+            env,
+            info.sender,
+            GenericBalance::from(Balance::from(info.funds)),
+            label,
+            stake,
+            counter_stake,
+        ),
+        ExecuteMsg::JoinChanceWager {
+            wager_id,
+            commitment,
+        } => execute_join_chance_wager(
+            deps,
+            env,
+            info.sender,
+            Balance::from(info.funds),
+            wager_id,
+            commitment,
+        ),
+        ExecuteMsg::RevealChoice {
+            wager_id,
+            choice,
+            salt,
+        } => execute_reveal_choice(deps, env, info, wager_id, choice, salt),
+        ExecuteMsg::ClaimRevealTimeout { wager_id } => {
+            execute_claim_reveal_timeout(deps, env, wager_id)
+        }
         ExecuteMsg::Cancel { wager_id } => execute_cancel(deps, env, info, wager_id),
+        ExecuteMsg::ProposeCancel { wager_id } => execute_propose_cancel(deps, env, info, wager_id),
+        ExecuteMsg::AcceptCancel { wager_id } => execute_accept_cancel(deps, env, info, wager_id),
+        ExecuteMsg::RetractCancel { wager_id } => execute_retract_cancel(deps, env, info, wager_id),
+        ExecuteMsg::Forfeit { wager_id } => execute_forfeit(deps, env, info, wager_id),
+        ExecuteMsg::VoidWager { wager_id } => execute_void_wager(deps, info, wager_id),
+        ExecuteMsg::RequestRematch { wager_id } => {
+            execute_request_rematch(deps, env, info, wager_id)
+        }
+        ExecuteMsg::OfferRematch {
+            settled_wager_id,
+            from_claimable,
+        } => execute_offer_rematch(
+            deps,
+            env,
+            info.sender,
+            Balance::from(info.funds),
+            settled_wager_id,
+            from_claimable,
+        ),
+        ExecuteMsg::PlaceSideBet { wager_id, side } => execute_place_side_bet(
+            deps,
+            env,
+            info.sender,
+            Balance::from(info.funds),
+            wager_id,
+            side,
+        ),
+        ExecuteMsg::LockSideBets { wager_id } => execute_lock_side_bets(deps, info, wager_id),
+        ExecuteMsg::ClaimSideBet { wager_id } => execute_claim_side_bet(deps, info, wager_id),
+        ExecuteMsg::VoteResult { wager_id, winner } => {
+            execute_vote_result(deps, env, info, wager_id, winner)
+        }
         ExecuteMsg::SendFunds {
             wager_id,
             winner_address,
         } => execute_send_funds(deps, env, info, wager_id, winner_address),
+        ExecuteMsg::ReportRound {
+            wager_id,
+            round,
+            winner,
+        } => execute_report_round(deps, env, info, wager_id, round, winner),
+        ExecuteMsg::JoinQueue {
+            game_id,
+            stake,
+            max_rating_diff,
+        } => {
+            if info.funds != [stake] {
+                return Err(ContractError::InvalidStake {});
+            }
+            execute_join_queue(
+                deps,
+                env,
+                info.sender,
+                Balance::from(info.funds),
+                game_id,
+                max_rating_diff,
+            )
+        }
+        ExecuteMsg::LeaveQueue { game_id } => execute_leave_queue(deps, env, info, game_id),
+        ExecuteMsg::SetRating { player, rating } => {
+            execute_set_rating(deps, env, info, player, rating)
+        }
+        ExecuteMsg::UpdateConfig(update) => execute_update_config(deps, env, info, update),
+        ExecuteMsg::ProposeConfigChange { update } => {
+            execute_propose_config_change(deps, env, info, update)
+        }
+        ExecuteMsg::ExecuteConfigChange { id } => {
+            execute_execute_config_change(deps, env, info, id)
+        }
+        ExecuteMsg::CancelConfigChange { id } => execute_cancel_config_change(deps, info, id),
+        ExecuteMsg::GrantRole {
+            role,
+            address,
+            game_id,
+        } => execute_grant_role(deps, info, role, address, game_id),
+        ExecuteMsg::RevokeRole {
+            role,
+            address,
+            game_id,
+        } => execute_revoke_role(deps, info, role, address, game_id),
+        ExecuteMsg::Pause {} => execute_set_paused(deps, info, true),
+        ExecuteMsg::Unpause {} => execute_set_paused(deps, info, false),
+        ExecuteMsg::SetStakeLimits { limits } => execute_set_stake_limits(deps, env, info, limits),
+        ExecuteMsg::SelfExclude { seconds } => execute_self_exclude(deps, env, info, seconds),
+        ExecuteMsg::SetReferrer { referrer } => execute_set_referrer(deps, env, info, referrer),
+        ExecuteMsg::CommitJackpotSeed {
+            wager_id,
+            commitment,
+        } => execute_commit_jackpot_seed(deps, env, info, wager_id, commitment),
+        ExecuteMsg::RevealJackpotSeed { wager_id, secret } => {
+            execute_reveal_jackpot_seed(deps, env, info, wager_id, secret)
+        }
+        ExecuteMsg::ClaimDrawTimeout { wager_id } => {
+            execute_claim_draw_timeout(deps, env, wager_id)
+        }
+        ExecuteMsg::Claim {} => execute_claim(deps, env, info),
+        ExecuteMsg::Relay { intent, signature } => {
+            execute_relay(deps, env, info, intent, signature)
+        }
+        ExecuteMsg::SetRelayKey { pubkey } => execute_set_relay_key(deps, env, info, pubkey),
+        ExecuteMsg::UpdateRelayers { add, remove } => {
+            execute_update_relayers(deps, env, info, add, remove)
+        }
+        ExecuteMsg::UpdateBlocklist { add, remove } => {
+            execute_update_blocklist(deps, info, add, remove)
+        }
+        ExecuteMsg::ForceCancel { wager_id } => execute_force_cancel(deps, env, info, wager_id),
+        ExecuteMsg::ForceLeaveQueue { game_id, player } => {
+            execute_force_leave_queue(deps, env, info, game_id, player)
+        }
+        ExecuteMsg::GrantSession {
+            key,
+            expires,
+            max_stake,
+        } => execute_grant_session(deps, env, info, key, expires, max_stake),
+        ExecuteMsg::AcceptSession { player } => execute_accept_session(deps, env, info, player),
+        ExecuteMsg::RevokeSession { key } => execute_revoke_session(deps, env, info, key),
+        ExecuteMsg::Sweep { asset, recipient } => execute_sweep(deps, env, info, asset, recipient),
     }
 }
 
@@ -63,18 +354,38 @@ pub fn receive_cw20(
     // Note: info.sender is the address of the token contract as the contract makes this call and
     //       cw20_msg.sender is the user who initiated the send of tokens call.
     //TODO: Add validation of allowed CW20s here by checking info.sender.
+    require_not_blocked(deps.storage, &deps.api.addr_validate(&cw20_msg.sender)?)?;
     let coin = Cw20CoinVerified {
         address: info.sender,
         amount: cw20_msg.amount,
     };
     match from_binary(&cw20_msg.msg) {
-        Ok(Cw20HookMsg::CreateWager { wager_id }) => {
+        Ok(Cw20HookMsg::CreateWager {
+            label,
+            stake,
+            stake_value,
+            series_length,
+        }) => {
             let api = deps.api;
             execute_create_wager(
                 deps,
                 env,
                 api.addr_validate(&cw20_msg.sender)?,
                 Balance::from(coin),
+                label,
+                None,
+                stake,
+                stake_value,
+                series_length,
+            )
+        }
+        Ok(Cw20HookMsg::Deposit { wager_id }) => {
+            let api = deps.api;
+            execute_deposit(
+                deps,
+                env,
+                api.addr_validate(&cw20_msg.sender)?,
+                GenericBalance::from(Balance::from(coin)),
                 wager_id,
             )
         }
@@ -88,185 +399,3515 @@ pub fn receive_cw20(
                 wager_id,
             )
         }
+        Ok(Cw20HookMsg::CreateChanceWager {
+            game,
+            commitment,
+            reveal_period,
+        }) => {
+            let api = deps.api;
+            execute_create_chance_wager(
+                deps,
+                env,
+                api.addr_validate(&cw20_msg.sender)?,
+                Balance::from(coin),
+                game,
+                commitment,
+                reveal_period,
+            )
+        }
+        Ok(Cw20HookMsg::CreateHillWager { label }) => {
+            let api = deps.api;
+            execute_create_hill_wager(
+                deps,
+                env,
+                api.addr_validate(&cw20_msg.sender)?,
+                Balance::from(coin),
+                label,
+            )
+        }
+        Ok(Cw20HookMsg::CreateNftWager {
+            label,
+            stake,
+            counter_stake,
+        }) => {
+            let api = deps.api;
+            execute_create_nft_wager(
+                deps,
+                env,
+                api.addr_validate(&cw20_msg.sender)?,
+                GenericBalance::from(Balance::from(coin)),
+                label,
+                stake,
+                counter_stake,
+            )
+        }
+        Ok(Cw20HookMsg::JoinChanceWager {
+            wager_id,
+            commitment,
+        }) => {
+            let api = deps.api;
+            execute_join_chance_wager(
+                deps,
+                env,
+                api.addr_validate(&cw20_msg.sender)?,
+                Balance::from(coin),
+                wager_id,
+                commitment,
+            )
+        }
+        Ok(Cw20HookMsg::PlaceSideBet { wager_id, side }) => {
+            let api = deps.api;
+            execute_place_side_bet(
+                deps,
+                env,
+                api.addr_validate(&cw20_msg.sender)?,
+                Balance::from(coin),
+                wager_id,
+                side,
+            )
+        }
+        Ok(Cw20HookMsg::JoinQueue {
+            game_id,
+            max_rating_diff,
+        }) => {
+            let api = deps.api;
+            execute_join_queue(
+                deps,
+                env,
+                api.addr_validate(&cw20_msg.sender)?,
+                Balance::from(coin),
+                game_id,
+                max_rating_diff,
+            )
+        }
+        Ok(Cw20HookMsg::OfferRematch { settled_wager_id }) => {
+            let api = deps.api;
+            execute_offer_rematch(
+                deps,
+                env,
+                api.addr_validate(&cw20_msg.sender)?,
+                Balance::from(coin),
+                settled_wager_id,
+                false,
+            )
+        }
+        Err(_) => Err(ContractError::DataShouldBeGiven {}),
+    }
+}
+
+pub fn receive_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    nft_msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // Note: info.sender is the address of the cw721 contract, nft_msg.sender the user who
+    //       sent the NFT.
+    let sender = deps.api.addr_validate(&nft_msg.sender)?;
+    require_not_blocked(deps.storage, &sender)?;
+    let nft = GenericBalance::from_nft(Nft {
+        contract: info.sender,
+        token_id: nft_msg.token_id,
+    });
+    match from_binary(&nft_msg.msg) {
+        Ok(Cw721HookMsg::CreateNftWager {
+            label,
+            stake,
+            counter_stake,
+        }) => execute_create_nft_wager(deps, env, sender, nft, label, stake, counter_stake),
+        Ok(Cw721HookMsg::Deposit { wager_id }) => execute_deposit(deps, env, sender, nft, wager_id),
         Err(_) => Err(ContractError::DataShouldBeGiven {}),
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_create_wager(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     sender: Addr,
     balance: Balance,
-    wager_id: String,
+    label: Option<String>,
+    game_id: Option<String>,
+    stake: Option<GenericBalance>,
+    stake_value: Option<Uint128>,
+    series_length: Option<u32>,
 ) -> Result<Response, ContractError> {
-    let user1_balance = match balance {
-        Balance::Native(balance) => GenericBalance {
-            native: balance.0,
-            cw20: vec![],
-        },
-        Balance::Cw20(token) => GenericBalance {
-            native: vec![],
-            cw20: vec![token],
-        },
+    if let Some(label) = &label {
+        if !is_valid_name(label) {
+            return Err(ContractError::InvalidLabel {});
+        }
+    }
+    // a series needs a majority, so an odd number of rounds
+    if series_length.is_some_and(|length| length % 2 == 0) {
+        return Err(ContractError::InvalidSeriesLength {});
+    }
+
+    let user1_balance = GenericBalance::from(balance);
+    if user1_balance.is_empty() {
+        return Err(ContractError::EmptyBalance {});
+    }
+    // a blocked session key cannot stake for its player either
+    require_not_blocked(deps.storage, &sender)?;
+    let sender = acting_player(deps.as_ref(), &env, sender, Some(&user1_balance))?;
+    require_not_blocked(deps.storage, &sender)?;
+    record_stake(deps.storage, &sender, env.block.time, &user1_balance)?;
+
+    let stake = match (stake, stake_value) {
+        (Some(_), Some(_)) => return Err(ContractError::ConflictingStake {}),
+        (Some(mut stake), None) => {
+            stake.normalize();
+            stake
+        }
+        (None, Some(_)) => GenericBalance::new(),
+        (None, None) => user1_balance.clone(),
     };
 
     let state = config(deps.storage).load()?;
 
-    let wager = Wager {
+    let mut wager = Wager {
         arbiter: state.owner,
         user1: sender,
         user2: Addr::unchecked("empty"),
         user1_balance,
         user2_balance: GenericBalance::new(),
+        game_id,
+        label,
+        stake,
+        stake_value,
+        status: WagerStatus::Open,
+        chance: None,
+        series: series_length.map(|length| Series {
+            length,
+            rounds: vec![],
+        }),
+        opponent: None,
+        hill: None,
+        cancel_proposed_by: None,
+        rematch_requested_by: vec![],
+        counter_stake: None,
+    };
+    let wager_id = store_new_wager(deps, &mut wager)?;
+
+    let res = Response::new()
+        .add_attributes(vec![("action", "create"), ("id", &wager_id.to_string())])
+        .add_event(WagerEvent::new(WagerEventKind::WagerCreated, wager_id, &wager).into_event()?)
+        .set_data(to_binary(&wager_id)?);
+    Ok(res)
+}
+
+/// Escrows the first deposit of a new wager and stores it under a new id. The wager is open
+/// once the creator deposited the whole stake.
+fn store_new_wager(deps: DepsMut, wager: &mut Wager) -> Result<u64, ContractError> {
+    // a stake can be deposited in several steps, the wager opens once it is complete
+    wager.status = match stake_progress(deps.as_ref(), wager, &wager.stake, &wager.user1_balance)? {
+        StakeProgress::Short => WagerStatus::Funding,
+        StakeProgress::Complete => WagerStatus::Open,
+        StakeProgress::Exceeded => return Err(ContractError::StakeExceeded {}),
     };
 
-    WAGERS.update(deps.storage, &wager_id, |existing| match existing {
-        None => Ok(wager),
+    escrow_deposit(deps.storage, &wager.user1_balance)?;
+
+    let wager_id = next_wager_id(deps.storage)?;
+    WAGERS.update(deps.storage, wager_id.into(), |existing| match existing {
+        None => Ok(wager.clone()),
         Some(_) => Err(ContractError::AlreadyInUse {}),
     })?;
-
-    let res = Response::new().add_attributes(vec![("action", "create"), ("id", wager_id.as_str())]);
-    Ok(res)
+    Ok(wager_id)
 }
 
 pub fn execute_add_funds(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     sender: Addr,
     balance: Balance,
-    wager_id: String,
+    wager_id: u64,
 ) -> Result<Response, ContractError> {
-    let mut wager = get_wager(&deps, &wager_id)?;
+    let deposit = GenericBalance::from(balance.clone());
+    require_not_blocked(deps.storage, &sender)?;
+    let sender = acting_player(deps.as_ref(), &env, sender, Some(&deposit))?;
+    require_not_blocked(deps.storage, &sender)?;
+    record_stake(deps.storage, &sender, env.block.time, &deposit)?;
+    let mut wager = get_wager(&deps, wager_id)?;
 
-    if wager.user2 != "empty" || wager.user1 == sender {
+    if wager.status != WagerStatus::Open || wager.user2 != "empty" || wager.user1 == sender {
         return Err(ContractError::AlreadyInUse {});
     }
+    if wager
+        .opponent
+        .as_ref()
+        .is_some_and(|opponent| *opponent != sender)
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+    if matches!(&wager.chance, Some(chance) if chance.user2_commitment.is_none()) {
+        return Err(ContractError::CommitmentRequired {});
+    }
 
     wager.user2_balance.add_tokens(balance);
     wager.user2 = sender;
 
-    if wager.user2_balance != wager.user1_balance {
-        return Err(ContractError::UnequalBalance {});
+    match stake_progress(
+        deps.as_ref(),
+        &wager,
+        wager.user2_stake(),
+        &wager.user2_balance,
+    )? {
+        StakeProgress::Complete => {}
+        _ => return Err(ContractError::UnequalBalance {}),
     }
+    wager.status = WagerStatus::Matched;
+    escrow_deposit(deps.storage, &wager.user2_balance)?;
 
-    WAGERS.update(deps.storage, &wager_id, |existing| match existing {
+    WAGERS.update(deps.storage, wager_id.into(), |existing| match existing {
         None => Err(ContractError::WagerDoesNotExist {}),
-        Some(_) => Ok(wager),
+        Some(_) => Ok(wager.clone()),
     })?;
 
-    let res =
-        Response::new().add_attributes(vec![("action", "add_funds"), ("id", wager_id.as_str())]);
+    let res = Response::new()
+        .add_attributes(vec![("action", "add_funds"), ("id", &wager_id.to_string())])
+        .add_event(WagerEvent::new(WagerEventKind::WagerJoined, wager_id, &wager).into_event()?);
 
     Ok(res)
 }
 
-pub fn execute_cancel(
+pub fn execute_request_rematch(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    wager_id: String,
+    wager_id: u64,
 ) -> Result<Response, ContractError> {
-    let wager = get_wager(&deps, &wager_id)?;
+    let sender = acting_player(deps.as_ref(), &env, info.sender, None)?;
+    let mut wager = get_wager(&deps, wager_id)?;
+    if sender != wager.user1 && sender != wager.user2 {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !wager.can_rematch() {
+        return Err(ContractError::NoRematch {});
+    }
+    if !wager.rematch_requested_by.contains(&sender) {
+        wager.rematch_requested_by.push(sender.clone());
+        WAGERS.save(deps.storage, wager_id.into(), &wager)?;
+    }
 
-    if (info.sender != wager.user1 && info.sender != wager.arbiter) || wager.user2 != "empty" {
-        Err(ContractError::Unauthorized {})
-    } else {
-        WAGERS.remove(deps.storage, &wager_id);
+    let event =
+        WagerEvent::new(WagerEventKind::WagerRematchRequested, wager_id, &wager).into_event()?;
+    Ok(Response::new()
+        .add_attribute("action", "request_rematch")
+        .add_attribute("id", wager_id.to_string())
+        .add_attribute("player", sender)
+        .add_event(event))
+}
 
-        let messages: Vec<SubMsg> = send_tokens(&wager.user1, &wager.user1_balance)?;
+pub fn execute_offer_rematch(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    balance: Balance,
+    settled_wager_id: u64,
+    from_claimable: bool,
+) -> Result<Response, ContractError> {
+    let rematch = REMATCHES
+        .may_load(deps.storage, settled_wager_id.into())?
+        .ok_or(ContractError::NoRematch {})?;
+    if env.block.height > rematch.expires {
+        return Err(ContractError::RematchExpired {});
+    }
+    let opponent = if sender == rematch.user1 {
+        rematch.user2.clone()
+    } else if sender == rematch.user2 {
+        rematch.user1.clone()
+    } else {
+        return Err(ContractError::Unauthorized {});
+    };
 
-        Ok(Response::new()
-            .add_attribute("action", "cancel")
-            .add_attribute("id", wager_id)
-            .add_attribute("to", wager.user1)
-            .add_submessages(messages))
+    let deposit = if from_claimable {
+        // the stake moves from the claimable tokens, which are already escrowed
+        if !balance.is_empty() || rematch.stake.is_empty() {
+            return Err(ContractError::InvalidStake {});
+        }
+        let mut claimable = CLAIMABLE
+            .may_load(deps.storage, &sender)?
+            .unwrap_or_default();
+        claimable
+            .sub_balance(&rematch.stake)
+            .map_err(|_| ContractError::InsufficientClaimable {})?;
+        if claimable.is_empty() {
+            CLAIMABLE.remove(deps.storage, &sender);
+        } else {
+            CLAIMABLE.save(deps.storage, &sender, &claimable)?;
+        }
+        escrow_withdraw(deps.storage, &rematch.stake)?;
+        rematch.stake.clone()
+    } else {
+        GenericBalance::from(balance)
+    };
+    if deposit.is_empty() {
+        return Err(ContractError::EmptyBalance {});
     }
+    require_not_blocked(deps.storage, &sender)?;
+    record_stake(deps.storage, &sender, env.block.time, &deposit)?;
+    REMATCHES.remove(deps.storage, settled_wager_id.into());
+    REMATCH_PAIRS.remove(deps.storage, rematch_pair(&rematch.user1, &rematch.user2));
+
+    let mut wager = Wager {
+        arbiter: rematch.arbiter,
+        user1: sender,
+        user2: Addr::unchecked("empty"),
+        user1_balance: deposit,
+        user2_balance: GenericBalance::new(),
+        game_id: rematch.game_id,
+        label: None,
+        stake: rematch.stake,
+        stake_value: rematch.stake_value,
+        status: WagerStatus::Open,
+        chance: None,
+        series: rematch.series_length.map(|length| Series {
+            length,
+            rounds: vec![],
+        }),
+        opponent: Some(opponent),
+        hill: None,
+        cancel_proposed_by: None,
+        rematch_requested_by: vec![],
+        counter_stake: None,
+    };
+    let wager_id = store_new_wager(deps, &mut wager)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "offer_rematch")
+        .add_attribute("id", wager_id.to_string())
+        .add_attribute("rematch_of", settled_wager_id.to_string())
+        .add_event(WagerEvent::new(WagerEventKind::WagerCreated, wager_id, &wager).into_event()?)
+        .set_data(to_binary(&wager_id)?))
 }
 
-pub fn execute_send_funds(
+pub fn execute_deposit(
     deps: DepsMut,
-    _env: Env,
-    info: MessageInfo,
-    wager_id: String,
-    winner_address: Addr,
+    env: Env,
+    sender: Addr,
+    deposit: GenericBalance,
+    wager_id: u64,
 ) -> Result<Response, ContractError> {
-    let wager = get_wager(&deps, &wager_id)?;
-    let state = config(deps.storage).load()?;
+    let mut wager = get_wager(&deps, wager_id)?;
+    if deposit.is_empty() {
+        return Err(ContractError::EmptyBalance {});
+    }
+    // the stake of a game of chance is complete at creation, it is only joined
+    if wager.chance.is_some() {
+        return Err(ContractError::CommitmentRequired {});
+    }
 
-    if info.sender != state.owner {
-        Err(ContractError::Unauthorized {})
-    } else if winner_address != wager.user1 && winner_address != wager.user2 {
-        Err(ContractError::UserDoesNotExist {})
-    } else {
-        // we delete the wager
-        WAGERS.remove(deps.storage, &wager_id);
+    require_not_blocked(deps.storage, &sender)?;
+    record_stake(deps.storage, &sender, env.block.time, &deposit)?;
+
+    let kind = match wager.status {
+        // the creator completes their stake
+        WagerStatus::Funding if sender == wager.user1 => {
+            wager.user1_balance.add_balance(&deposit);
+            match stake_progress(deps.as_ref(), &wager, &wager.stake, &wager.user1_balance)? {
+                StakeProgress::Short => {}
+                StakeProgress::Complete => wager.status = WagerStatus::Open,
+                StakeProgress::Exceeded => return Err(ContractError::StakeExceeded {}),
+            }
+            WagerEventKind::WagerDeposited
+        }
+        // the opponent takes the free seat and deposits the same stake, or the counter stake
+        WagerStatus::Open
+            if sender != wager.user1 && (wager.user2 == "empty" || wager.user2 == sender) =>
+        {
+            if wager
+                .opponent
+                .as_ref()
+                .is_some_and(|opponent| *opponent != sender)
+            {
+                return Err(ContractError::Unauthorized {});
+            }
+            wager.user2 = sender;
+            wager.user2_balance.add_balance(&deposit);
+            match stake_progress(
+                deps.as_ref(),
+                &wager,
+                wager.user2_stake(),
+                &wager.user2_balance,
+            )? {
+                StakeProgress::Short => WagerEventKind::WagerDeposited,
+                StakeProgress::Complete => {
+                    wager.status = WagerStatus::Matched;
+                    WagerEventKind::WagerJoined
+                }
+                StakeProgress::Exceeded => return Err(ContractError::StakeExceeded {}),
+            }
+        }
+        _ => return Err(ContractError::AlreadyInUse {}),
+    };
 
-        // send user1 tokens to winner
-        let user1_messages: Vec<SubMsg> = send_tokens(&winner_address, &wager.user1_balance)?;
-        // send user2 tokens to winner
-        let user2_messages: Vec<SubMsg> = send_tokens(&winner_address, &wager.user2_balance)?;
+    escrow_deposit(deps.storage, &deposit)?;
+    WAGERS.save(deps.storage, wager_id.into(), &wager)?;
 
-        Ok(Response::new()
-            .add_attribute("action", "send_tokens_to_winner")
-            .add_attribute("id", wager_id)
-            .add_attribute("to", winner_address)
-            .add_submessages(user1_messages)
-            .add_submessages(user2_messages))
+    Ok(Response::new()
+        .add_attributes(vec![("action", "deposit"), ("id", &wager_id.to_string())])
+        .add_event(WagerEvent::new(kind, wager_id, &wager).into_event()?))
+}
+
+pub fn execute_create_chance_wager(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    balance: Balance,
+    game: ChanceGameKind,
+    commitment: Binary,
+    reveal_period: u64,
+) -> Result<Response, ContractError> {
+    if reveal_period == 0 || reveal_period > MAX_REVEAL_PERIOD {
+        return Err(ContractError::InvalidRevealPeriod {});
     }
+
+    let res = execute_create_wager(
+        deps.branch(),
+        env,
+        sender,
+        balance,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )?;
+
+    let wager_id = WAGER_SEQ.load(deps.storage)?;
+    let mut wager = get_wager(&deps, wager_id)?;
+    wager.chance = Some(ChanceGame {
+        kind: game,
+        user1_commitment: commitment,
+        user2_commitment: None,
+        reveal_period,
+        reveal_deadline: None,
+        user1_reveal: None,
+        user2_reveal: None,
+    });
+    WAGERS.save(deps.storage, wager_id.into(), &wager)?;
+
+    // the event describes the wager as a game of chance
+    let event = WagerEvent::new(WagerEventKind::WagerCreated, wager_id, &wager).into_event()?;
+    Ok(Response::new()
+        .add_attributes(res.attributes)
+        .add_event(event)
+        .set_data(to_binary(&wager_id)?))
 }
 
-fn get_wager(deps: &DepsMut, wager_id: &str) -> Result<Wager, ContractError> {
-    match WAGERS.load(deps.storage, wager_id) {
-        Ok(wager) => Ok(wager),
-        Err(_) => Err(ContractError::WagerDoesNotExist {}),
+pub fn execute_create_hill_wager(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    balance: Balance,
+    label: Option<String>,
+) -> Result<Response, ContractError> {
+    let res = execute_create_wager(
+        deps.branch(),
+        env,
+        sender,
+        balance,
+        label,
+        None,
+        None,
+        None,
+        None,
+    )?;
+
+    let wager_id = WAGER_SEQ.load(deps.storage)?;
+    let mut wager = get_wager(&deps, wager_id)?;
+    wager.hill = Some(Hill { streak: 0 });
+    WAGERS.save(deps.storage, wager_id.into(), &wager)?;
+
+    // the event describes the wager as a hill
+    let event = WagerEvent::new(WagerEventKind::WagerCreated, wager_id, &wager).into_event()?;
+    Ok(Response::new()
+        .add_attributes(res.attributes)
+        .add_event(event)
+        .set_data(to_binary(&wager_id)?))
+}
+
+pub fn execute_cash_out(
+    deps: DepsMut,
+    info: MessageInfo,
+    wager_id: u64,
+) -> Result<Response, ContractError> {
+    let wager = get_wager(&deps, wager_id)?;
+    if wager.hill.is_none() {
+        return Err(ContractError::NotHill {});
     }
+    if info.sender != wager.user1 {
+        return Err(ContractError::Unauthorized {});
+    }
+    if wager.status != WagerStatus::Open || wager.user2 != "empty" {
+        return Err(ContractError::MatchInProgress {});
+    }
+
+    let messages = refund_wager(deps.storage, wager_id, &wager)?;
+    let event = WagerEvent::new(WagerEventKind::WagerCashedOut, wager_id, &wager)
+        .status(WagerStatus::Settled)
+        .into_event()?;
+    Ok(Response::new()
+        .add_attribute("action", "cash_out")
+        .add_attribute("id", wager_id.to_string())
+        .add_attribute("to", wager.user1)
+        .add_event(event)
+        .add_submessages(messages))
 }
 
-fn send_tokens(to: &Addr, balance: &GenericBalance) -> StdResult<Vec<SubMsg>> {
-    let native_balance = &balance.native;
-    let mut msgs: Vec<SubMsg> = if native_balance.is_empty() {
-        vec![]
-    } else {
-        vec![SubMsg::new(BankMsg::Send {
-            to_address: to.into(),
-            amount: native_balance.to_vec(),
-        })]
+pub fn execute_create_nft_wager(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    deposit: GenericBalance,
+    label: Option<String>,
+    stake: Option<GenericBalance>,
+    mut counter_stake: GenericBalance,
+) -> Result<Response, ContractError> {
+    if let Some(label) = &label {
+        if !is_valid_name(label) {
+            return Err(ContractError::InvalidLabel {});
+        }
+    }
+    if deposit.is_empty() {
+        return Err(ContractError::EmptyBalance {});
+    }
+    let mut stake = stake.unwrap_or_else(|| deposit.clone());
+    stake.normalize();
+    counter_stake.normalize();
+    // the terms are for wagering NFTs, other stakes are matched by CreateWagerNative
+    if stake.is_empty()
+        || counter_stake.is_empty()
+        || (stake.cw721.is_empty() && counter_stake.cw721.is_empty())
+    {
+        return Err(ContractError::InvalidStake {});
+    }
+
+    require_not_blocked(deps.storage, &sender)?;
+    let sender = acting_player(deps.as_ref(), &env, sender, Some(&deposit))?;
+    require_not_blocked(deps.storage, &sender)?;
+    record_stake(deps.storage, &sender, env.block.time, &deposit)?;
+
+    let state = config(deps.storage).load()?;
+    let mut wager = Wager {
+        arbiter: state.owner,
+        user1: sender,
+        user2: Addr::unchecked("empty"),
+        user1_balance: deposit,
+        user2_balance: GenericBalance::new(),
+        game_id: None,
+        label,
+        stake,
+        stake_value: None,
+        status: WagerStatus::Open,
+        chance: None,
+        series: None,
+        opponent: None,
+        hill: None,
+        cancel_proposed_by: None,
+        rematch_requested_by: vec![],
+        counter_stake: Some(counter_stake),
     };
+    let wager_id = store_new_wager(deps, &mut wager)?;
 
-    let cw20_balance = &balance.cw20;
-    let cw20_msgs: StdResult<Vec<_>> = cw20_balance
-        .iter()
-        .map(|c| {
-            let msg = Cw20ExecuteMsg::Transfer {
-                recipient: to.into(),
-                amount: c.amount,
-            };
-            let exec = SubMsg::new(WasmMsg::Execute {
-                contract_addr: c.address.to_string(),
-                msg: to_binary(&msg)?,
-                funds: vec![],
-            });
-            Ok(exec)
-        })
-        .collect();
-    msgs.append(&mut cw20_msgs?);
-    Ok(msgs)
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "create_nft_wager"),
+            ("id", &wager_id.to_string()),
+        ])
+        .add_event(WagerEvent::new(WagerEventKind::WagerCreated, wager_id, &wager).into_event()?)
+        .set_data(to_binary(&wager_id)?))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::Wager { id } => to_binary(&query_wager_for_id(id, deps)?),
+pub fn execute_join_chance_wager(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    balance: Balance,
+    wager_id: u64,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    let mut wager = get_wager(&deps, wager_id)?;
+    let chance = wager
+        .chance
+        .as_mut()
+        .ok_or(ContractError::NotChanceGame {})?;
+    if chance.user2_commitment.is_some() {
+        return Err(ContractError::AlreadyInUse {});
     }
-}
 
-fn query_config(deps: Deps) -> StdResult<State> {
-    let state = config_read(deps.storage).load()?;
-    Ok(state)
+    // the reveal period starts once both players are committed
+    chance.user2_commitment = Some(commitment);
+    let deadline = env
+        .block
+        .height
+        .checked_add(chance.reveal_period)
+        .ok_or_else(|| {
+            StdError::overflow(OverflowError::new(
+                OverflowOperation::Add,
+                env.block.height,
+                chance.reveal_period,
+            ))
+        })?;
+    chance.reveal_deadline = Some(deadline);
+    WAGERS.save(deps.storage, wager_id.into(), &wager)?;
+
+    execute_add_funds(deps.branch(), env, sender, balance, wager_id)
 }
 
-fn query_wager_for_id(id: String, deps: Deps) -> StdResult<Wager> {
-    let wager = WAGERS.load(deps.storage, &id)?;
-    Ok(wager)
+pub fn execute_reveal_choice(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wager_id: u64,
+    choice: u8,
+    salt: Binary,
+) -> Result<Response, ContractError> {
+    let sender = acting_player(deps.as_ref(), &env, info.sender, None)?;
+    let mut wager = get_wager(&deps, wager_id)?;
+    if wager.status != WagerStatus::Matched {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut chance = wager
+        .chance
+        .clone()
+        .ok_or(ContractError::NotChanceGame {})?;
+    if chance
+        .reveal_deadline
+        .is_some_and(|deadline| env.block.height > deadline)
+    {
+        return Err(ContractError::RevealPeriodOver {});
+    }
+
+    let (commitment, reveal) = if sender == wager.user1 {
+        (Some(&chance.user1_commitment), &mut chance.user1_reveal)
+    } else if sender == wager.user2 {
+        (chance.user2_commitment.as_ref(), &mut chance.user2_reveal)
+    } else {
+        return Err(ContractError::Unauthorized {});
+    };
+    if reveal.is_some() {
+        return Err(ContractError::AlreadyRevealed {});
+    }
+    if choice >= chance.kind.choices() {
+        return Err(ContractError::InvalidChoice {});
+    }
+    let hash = Sha256::new()
+        .chain([choice])
+        .chain(salt.as_slice())
+        .finalize();
+    if commitment.is_none_or(|commitment| commitment.as_slice() != &hash[..]) {
+        return Err(ContractError::InvalidReveal {});
+    }
+    *reveal = Some(Reveal { choice, salt });
+    // a revealed choice gives the outcome away
+    let locked = lock_side_pool(deps.storage, wager_id)?;
+
+    let event = ChoiceEvent {
+        wager_id,
+        player: sender,
+        choice,
+    }
+    .into_event();
+
+    let mut res = match (&chance.user1_reveal, &chance.user2_reveal) {
+        (Some(user1_reveal), Some(user2_reveal)) => {
+            match chance_winner(&wager, &chance.kind, user1_reveal, user2_reveal) {
+                Some(winner) => {
+                    settle_wager(deps, &env, wager_id, &wager, winner, None, "reveal_choice")?
+                }
+                None => {
+                    // a draw refunds both players
+                    let messages = refund_wager(deps.storage, wager_id, &wager)?;
+                    let event = WagerEvent::new(WagerEventKind::WagerSettled, wager_id, &wager)
+                        .status(WagerStatus::Settled)
+                        .into_event()?;
+                    let res = Response::new()
+                        .add_attribute("action", "reveal_choice")
+                        .add_attribute("id", wager_id.to_string())
+                        .add_event(event)
+                        .add_submessages(messages);
+                    match close_side_pool(deps.storage, wager_id, None)? {
+                        Some(event) => res.add_event(event),
+                        None => res,
+                    }
+                }
+            }
+        }
+        _ => {
+            wager.chance = Some(chance);
+            WAGERS.save(deps.storage, wager_id.into(), &wager)?;
+            Response::new()
+                .add_attribute("action", "reveal_choice")
+                .add_attribute("id", wager_id.to_string())
+        }
+    };
+    // the reveal comes before the settlement it completes
+    res.events.insert(0, event);
+    if let Some(locked) = locked {
+        res.events.insert(1, locked);
+    }
+    Ok(res)
+}
+
+pub fn execute_claim_reveal_timeout(
+    deps: DepsMut,
+    env: Env,
+    wager_id: u64,
+) -> Result<Response, ContractError> {
+    let wager = get_wager(&deps, wager_id)?;
+    let chance = wager
+        .chance
+        .clone()
+        .ok_or(ContractError::NotChanceGame {})?;
+    match chance.reveal_deadline {
+        Some(deadline) if env.block.height > deadline => {}
+        _ => return Err(ContractError::RevealPeriodOpen {}),
+    }
+
+    // a player who did not reveal in time forfeits
+    match (chance.user1_reveal, chance.user2_reveal) {
+        (Some(_), None) => settle_wager(
+            deps,
+            &env,
+            wager_id,
+            &wager,
+            wager.user1.clone(),
+            Some(wager.user2.clone()),
+            "claim_reveal_timeout",
+        ),
+        (None, Some(_)) => settle_wager(
+            deps,
+            &env,
+            wager_id,
+            &wager,
+            wager.user2.clone(),
+            Some(wager.user1.clone()),
+            "claim_reveal_timeout",
+        ),
+        _ => {
+            let messages = refund_wager(deps.storage, wager_id, &wager)?;
+            let event = WagerEvent::new(WagerEventKind::WagerExpired, wager_id, &wager)
+                .status(WagerStatus::Expired)
+                .into_event()?;
+            let res = Response::new()
+                .add_attribute("action", "claim_reveal_timeout")
+                .add_attribute("id", wager_id.to_string())
+                .add_event(event)
+                .add_submessages(messages);
+            Ok(match close_side_pool(deps.storage, wager_id, None)? {
+                Some(event) => res.add_event(event),
+                None => res,
+            })
+        }
+    }
+}
+
+/// Computes the winner of a game of chance both players revealed, None on a draw. The outcome
+/// is drawn from the hash of both salts, which neither player could choose alone.
+fn chance_winner(
+    wager: &Wager,
+    kind: &ChanceGameKind,
+    user1_reveal: &Reveal,
+    user2_reveal: &Reveal,
+) -> Option<Addr> {
+    let seed = Sha256::new()
+        .chain(user1_reveal.salt.as_slice())
+        .chain(user2_reveal.salt.as_slice())
+        .finalize();
+    let user1_wins = match kind {
+        ChanceGameKind::CoinFlip => Some(seed[0] % 2 == user1_reveal.choice),
+        ChanceGameKind::Dice => {
+            let (user1_roll, user2_roll) = (seed[0] % 6, seed[1] % 6);
+            (user1_roll != user2_roll).then_some(user1_roll > user2_roll)
+        }
+        ChanceGameKind::RockPaperScissors => {
+            match (3 + user1_reveal.choice - user2_reveal.choice) % 3 {
+                0 => None,
+                1 => Some(true),
+                _ => Some(false),
+            }
+        }
+    };
+    user1_wins.map(|user1_wins| {
+        if user1_wins {
+            wager.user1.clone()
+        } else {
+            wager.user2.clone()
+        }
+    })
+}
+
+enum StakeProgress {
+    Short,
+    Complete,
+    Exceeded,
+}
+
+/// Compares what a player deposited with the stake of the wager. Token stakes must be matched
+/// exactly, valued stakes within the configured tolerance of the oracle value.
+fn stake_progress(
+    deps: Deps,
+    wager: &Wager,
+    stake: &GenericBalance,
+    deposited: &GenericBalance,
+) -> Result<StakeProgress, ContractError> {
+    let progress = match wager.stake_value {
+        None => {
+            if deposited == stake {
+                StakeProgress::Complete
+            } else if stake.covers(deposited) {
+                StakeProgress::Short
+            } else {
+                StakeProgress::Exceeded
+            }
+        }
+        // the oracle cannot value NFTs
+        Some(_) if !deposited.cw721.is_empty() => return Err(ContractError::InvalidStake {}),
+        Some(stake_value) => {
+            let state = config_read(deps.storage).load()?;
+            let oracle = state.oracle.ok_or(ContractError::NoOracle {})?;
+            let value = query_value(deps, &oracle, deposited)?;
+            let tolerance = stake_value.multiply_ratio(state.price_tolerance_bps, 10_000u64);
+            if value > stake_value.saturating_add(tolerance) {
+                StakeProgress::Exceeded
+            } else if value >= stake_value.saturating_sub(tolerance) {
+                StakeProgress::Complete
+            } else {
+                StakeProgress::Short
+            }
+        }
+    };
+    Ok(progress)
+}
+
+pub fn execute_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wager_id: u64,
+) -> Result<Response, ContractError> {
+    let sender = acting_player(deps.as_ref(), &env, info.sender, None)?;
+    let wager = get_wager(&deps, wager_id)?;
+
+    if sender != wager.user1 {
+        require_arbiter(deps.storage, &wager, &sender)?;
+    }
+    if wager.status == WagerStatus::Matched || wager.status == WagerStatus::Disputed {
+        Err(ContractError::Unauthorized {})
+    } else {
+        // a player part way through depositing a basket stake is refunded too
+        let messages = refund_wager(deps.storage, wager_id, &wager)?;
+
+        let event = WagerEvent::new(WagerEventKind::WagerCancelled, wager_id, &wager)
+            .status(WagerStatus::Cancelled)
+            .into_event()?;
+
+        Ok(Response::new()
+            .add_attribute("action", "cancel")
+            .add_attribute("id", wager_id.to_string())
+            .add_attribute("to", wager.user1)
+            .add_event(event)
+            .add_submessages(messages))
+    }
+}
+
+pub fn execute_propose_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wager_id: u64,
+) -> Result<Response, ContractError> {
+    let sender = acting_player(deps.as_ref(), &env, info.sender, None)?;
+    let mut wager = get_wager(&deps, wager_id)?;
+    if sender != wager.user1 && sender != wager.user2 {
+        return Err(ContractError::Unauthorized {});
+    }
+    if wager.status != WagerStatus::Matched {
+        return Err(ContractError::NotMatched {});
+    }
+    wager.cancel_proposed_by = Some(sender.clone());
+    WAGERS.save(deps.storage, wager_id.into(), &wager)?;
+
+    let event =
+        WagerEvent::new(WagerEventKind::WagerCancelProposed, wager_id, &wager).into_event()?;
+    Ok(Response::new()
+        .add_attribute("action", "propose_cancel")
+        .add_attribute("id", wager_id.to_string())
+        .add_attribute("proposer", sender)
+        .add_event(event))
+}
+
+pub fn execute_accept_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wager_id: u64,
+) -> Result<Response, ContractError> {
+    let sender = acting_player(deps.as_ref(), &env, info.sender, None)?;
+    let wager = get_wager(&deps, wager_id)?;
+    if sender != wager.user1 && sender != wager.user2 {
+        return Err(ContractError::Unauthorized {});
+    }
+    if wager.status != WagerStatus::Matched {
+        return Err(ContractError::NotMatched {});
+    }
+    // the player who proposed cannot accept their own proposal
+    if wager
+        .cancel_proposed_by
+        .as_ref()
+        .is_none_or(|proposer| *proposer == sender)
+    {
+        return Err(ContractError::NoCancelProposal {});
+    }
+
+    refund_ended_wager(
+        deps.storage,
+        wager_id,
+        &wager,
+        WagerEventKind::WagerCancelled,
+        "accept_cancel",
+    )
+}
+
+pub fn execute_retract_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wager_id: u64,
+) -> Result<Response, ContractError> {
+    let sender = acting_player(deps.as_ref(), &env, info.sender, None)?;
+    let mut wager = get_wager(&deps, wager_id)?;
+    if wager.cancel_proposed_by.as_ref() != Some(&sender) {
+        return Err(ContractError::NoCancelProposal {});
+    }
+    wager.cancel_proposed_by = None;
+    WAGERS.save(deps.storage, wager_id.into(), &wager)?;
+
+    let event =
+        WagerEvent::new(WagerEventKind::WagerCancelRetracted, wager_id, &wager).into_event()?;
+    Ok(Response::new()
+        .add_attribute("action", "retract_cancel")
+        .add_attribute("id", wager_id.to_string())
+        .add_event(event))
+}
+
+pub fn execute_void_wager(
+    deps: DepsMut,
+    info: MessageInfo,
+    wager_id: u64,
+) -> Result<Response, ContractError> {
+    let wager = get_wager(&deps, wager_id)?;
+    require_arbiter(deps.storage, &wager, &info.sender)?;
+    if wager.status != WagerStatus::Matched {
+        return Err(ContractError::NotMatched {});
+    }
+
+    refund_ended_wager(
+        deps.storage,
+        wager_id,
+        &wager,
+        WagerEventKind::WagerVoided,
+        "void_wager",
+    )
+}
+
+/// Refunds both stakes of a wager that ends without a winner and closes its side pool, whose
+/// bets are refunded too.
+fn refund_ended_wager(
+    storage: &mut dyn Storage,
+    wager_id: u64,
+    wager: &Wager,
+    kind: WagerEventKind,
+    action: &str,
+) -> Result<Response, ContractError> {
+    let messages = refund_wager(storage, wager_id, wager)?;
+    let event = WagerEvent::new(kind, wager_id, wager)
+        .status(WagerStatus::Cancelled)
+        .into_event()?;
+    let mut res = Response::new()
+        .add_attribute("action", action)
+        .add_attribute("id", wager_id.to_string())
+        .add_event(event)
+        .add_submessages(messages);
+    if let Some(event) = close_side_pool(storage, wager_id, None)? {
+        res = res.add_event(event);
+    }
+    Ok(res)
+}
+
+pub fn execute_send_funds(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wager_id: u64,
+    winner_address: Addr,
+) -> Result<Response, ContractError> {
+    let wager = get_wager(&deps, wager_id)?;
+    let state = config(deps.storage).load()?;
+    if !matches!(wager.status, WagerStatus::Matched | WagerStatus::Disputed) {
+        return Err(ContractError::NotMatched {});
+    }
+    if wager.status == WagerStatus::Disputed {
+        // owners settle the disputes of arbiters
+        require_role(deps.storage, Role::Owner, &info.sender)?;
+    } else {
+        require_arbiter(deps.storage, &wager, &info.sender)?;
+        if needs_approvals(&state, &wager) {
+            return Err(ContractError::ApprovalsRequired {});
+        }
+    }
+
+    // games of chance are settled by the contract alone
+    if wager.chance.is_some() {
+        Err(ContractError::Unauthorized {})
+    } else if winner_address != wager.user1 && winner_address != wager.user2 {
+        Err(ContractError::UserDoesNotExist {})
+    } else {
+        settle_wager(
+            deps,
+            &env,
+            wager_id,
+            &wager,
+            winner_address,
+            None,
+            "send_tokens_to_winner",
+        )
+    }
+}
+
+pub fn execute_forfeit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wager_id: u64,
+) -> Result<Response, ContractError> {
+    // conceding hands the stake away, so session keys cannot do it for their player
+    let sender = info.sender;
+    let wager = get_wager(&deps, wager_id)?;
+    let winner = if sender == wager.user1 {
+        wager.user2.clone()
+    } else if sender == wager.user2 {
+        wager.user1.clone()
+    } else {
+        return Err(ContractError::Unauthorized {});
+    };
+    if !matches!(wager.status, WagerStatus::Matched | WagerStatus::Disputed) {
+        return Err(ContractError::NotMatched {});
+    }
+
+    // the opponent wins as if the arbiter had sent them the funds
+    settle_wager(
+        deps,
+        &env,
+        wager_id,
+        &wager,
+        winner,
+        Some(sender),
+        "forfeit",
+    )
+}
+
+pub fn execute_vote_result(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wager_id: u64,
+    winner: String,
+) -> Result<Response, ContractError> {
+    let mut wager = get_wager(&deps, wager_id)?;
+    require_arbiter(deps.storage, &wager, &info.sender)?;
+    match wager.status {
+        WagerStatus::Matched if wager.chance.is_none() => {}
+        WagerStatus::Disputed => return Err(ContractError::Disputed {}),
+        _ => return Err(ContractError::Unauthorized {}),
+    }
+    let winner = deps.api.addr_validate(&winner)?;
+    if winner != wager.user1 && winner != wager.user2 {
+        return Err(ContractError::UserDoesNotExist {});
+    }
+
+    let mut votes = VOTES
+        .may_load(deps.storage, wager_id.into())?
+        .unwrap_or_default();
+    if votes.iter().any(|vote| vote.arbiter == info.sender) {
+        return Err(ContractError::AlreadyVoted {});
+    }
+    let disputed = votes.iter().any(|vote| vote.winner != winner);
+    votes.push(Vote {
+        arbiter: info.sender.clone(),
+        winner: winner.clone(),
+    });
+    VOTES.save(deps.storage, wager_id.into(), &votes)?;
+    // the game went on, a cancellation proposed before no longer stands and bets are closed
+    wager.cancel_proposed_by = None;
+    let locked = lock_side_pool(deps.storage, wager_id)?;
+
+    let state = config(deps.storage).load()?;
+    let required = state.required_approvals.max(1);
+    let approvals = votes.iter().filter(|vote| vote.winner == winner).count() as u32;
+    let event = VoteEvent {
+        wager_id,
+        arbiter: info.sender,
+        winner: winner.clone(),
+        approvals,
+        required,
+    }
+    .into_event();
+
+    let mut res = if disputed {
+        wager.status = WagerStatus::Disputed;
+        WAGERS.save(deps.storage, wager_id.into(), &wager)?;
+        Response::new()
+            .add_attribute("action", "vote_result")
+            .add_attribute("id", wager_id.to_string())
+            .add_event(
+                WagerEvent::new(WagerEventKind::WagerDisputed, wager_id, &wager).into_event()?,
+            )
+    } else if approvals >= required {
+        settle_wager(deps, &env, wager_id, &wager, winner, None, "vote_result")?
+    } else {
+        WAGERS.save(deps.storage, wager_id.into(), &wager)?;
+        Response::new()
+            .add_attribute("action", "vote_result")
+            .add_attribute("id", wager_id.to_string())
+    };
+    res.events.insert(0, event);
+    if let Some(locked) = locked {
+        res.events.insert(1, locked);
+    }
+    Ok(res)
+}
+
+/// Returns true if the wager can only be settled by arbiter votes
+fn needs_approvals(state: &State, wager: &Wager) -> bool {
+    wager.user1_balance.reaches_any(&state.approval_threshold)
+        || wager.user2_balance.reaches_any(&state.approval_threshold)
+}
+
+pub fn execute_report_round(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wager_id: u64,
+    round: u32,
+    winner: String,
+) -> Result<Response, ContractError> {
+    let mut wager = get_wager(&deps, wager_id)?;
+    require_arbiter(deps.storage, &wager, &info.sender)?;
+    if wager.status != WagerStatus::Matched {
+        return Err(ContractError::Unauthorized {});
+    }
+    let winner = deps.api.addr_validate(&winner)?;
+    if winner != wager.user1 && winner != wager.user2 {
+        return Err(ContractError::UserDoesNotExist {});
+    }
+    let series = wager.series.as_mut().ok_or(ContractError::NotSeries {})?;
+    // rounds are reported once each and in order, until a player won the majority
+    let (user1_wins, user2_wins) = series.wins(&wager.user1);
+    if round as usize != series.rounds.len() + 1 || user1_wins.max(user2_wins) > series.length / 2 {
+        return Err(ContractError::InvalidRound {});
+    }
+    series.rounds.push(winner.clone());
+
+    let (user1_wins, user2_wins) = series.wins(&wager.user1);
+    let decided = user1_wins.max(user2_wins) > series.length / 2;
+    // the game went on, a cancellation proposed before no longer stands and bets are closed
+    wager.cancel_proposed_by = None;
+    let locked = lock_side_pool(deps.storage, wager_id)?;
+    let event = RoundEvent {
+        wager_id,
+        round,
+        winner: winner.clone(),
+        user1_wins,
+        user2_wins,
+    }
+    .into_event();
+
+    // the pot is released once a player won the majority of the rounds, high stakes still
+    // wait for the arbiters to vote
+    let state = config(deps.storage).load()?;
+    let mut res = if decided && !needs_approvals(&state, &wager) {
+        settle_wager(deps, &env, wager_id, &wager, winner, None, "report_round")?
+    } else {
+        WAGERS.save(deps.storage, wager_id.into(), &wager)?;
+        Response::new()
+            .add_attribute("action", "report_round")
+            .add_attribute("id", wager_id.to_string())
+    };
+    res.events.insert(0, event);
+    if let Some(locked) = locked {
+        res.events.insert(1, locked);
+    }
+    Ok(res)
+}
+
+pub fn execute_grant_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: String,
+    game_id: Option<String>,
+) -> Result<Response, ContractError> {
+    require_role(deps.storage, Role::Owner, &info.sender)?;
+    require_no_delay(deps.storage)?;
+    let event = grant_role(deps, role, address, game_id)?;
+    Ok(Response::new()
+        .add_attribute("action", "grant_role")
+        .add_event(event))
+}
+
+fn grant_role(
+    deps: DepsMut,
+    role: Role,
+    address: String,
+    game_id: Option<String>,
+) -> Result<Event, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+    match &game_id {
+        Some(game_id) if role == Role::Arbiter => {
+            GAME_ARBITERS.save(deps.storage, (game_id, &address), &Empty {})?
+        }
+        Some(_) => return Err(ContractError::GameScopedRole {}),
+        None => ROLES.save(deps.storage, (role.as_str(), &address), &Empty {})?,
+    }
+
+    Ok(RoleEvent {
+        kind: RoleEventKind::RoleGranted,
+        role,
+        address,
+        game_id,
+    }
+    .into_event())
+}
+
+pub fn execute_revoke_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: String,
+    game_id: Option<String>,
+) -> Result<Response, ContractError> {
+    require_role(deps.storage, Role::Owner, &info.sender)?;
+    require_no_delay(deps.storage)?;
+    let event = revoke_role(deps, info.sender, role, address, game_id)?;
+    Ok(Response::new()
+        .add_attribute("action", "revoke_role")
+        .add_event(event))
+}
+
+/// Revokes the role for the sender, an owner
+fn revoke_role(
+    deps: DepsMut,
+    sender: Addr,
+    role: Role,
+    address: String,
+    game_id: Option<String>,
+) -> Result<Event, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+    // the contract always keeps an owner
+    if role == Role::Owner && address == sender {
+        return Err(ContractError::OwnOwnerRole {});
+    }
+    match &game_id {
+        Some(game_id) if role == Role::Arbiter => {
+            GAME_ARBITERS.remove(deps.storage, (game_id, &address))
+        }
+        Some(_) => return Err(ContractError::GameScopedRole {}),
+        None => ROLES.remove(deps.storage, (role.as_str(), &address)),
+    }
+    // fees and new wagers go to an owner that still holds the role
+    let mut state = config(deps.storage).load()?;
+    if role == Role::Owner && address == state.owner {
+        state.owner = sender;
+        config(deps.storage).save(&state)?;
+    }
+
+    Ok(RoleEvent {
+        kind: RoleEventKind::RoleRevoked,
+        role,
+        address,
+        game_id,
+    }
+    .into_event())
+}
+
+pub fn execute_set_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    // guardians pause, only owners can lift a pause
+    let role = if paused { Role::Guardian } else { Role::Owner };
+    require_role(deps.storage, role, &info.sender)?;
+    config(deps.storage).update(|mut state| -> StdResult<_> {
+        state.paused = paused;
+        Ok(state)
+    })?;
+
+    let event = PauseEvent {
+        paused,
+        sender: info.sender,
+    }
+    .into_event();
+    Ok(Response::new()
+        .add_attribute("action", if paused { "pause" } else { "unpause" })
+        .add_event(event))
+}
+
+/// Returns an error naming the role unless the address holds it
+fn require_role(storage: &dyn Storage, role: Role, address: &Addr) -> Result<(), ContractError> {
+    if has_role(storage, &role, address)? {
+        Ok(())
+    } else {
+        Err(ContractError::MissingRole { role })
+    }
+}
+
+/// Refuses the stakes and bets of an address on the blocklist
+fn require_not_blocked(storage: &dyn Storage, address: &Addr) -> Result<(), ContractError> {
+    if BLOCKLIST.has(storage, address) {
+        return Err(ContractError::Blocked {});
+    }
+    Ok(())
+}
+
+/// Checks that the address can settle the wager, as an arbiter of the contract or of the game
+/// of the wager. The arbiter recorded on the wager only settles it while holding the role.
+fn require_arbiter(
+    storage: &dyn Storage,
+    wager: &Wager,
+    address: &Addr,
+) -> Result<(), ContractError> {
+    if is_arbiter_of(storage, wager.game_id.as_deref(), address)? {
+        Ok(())
+    } else {
+        Err(ContractError::MissingRole {
+            role: Role::Arbiter,
+        })
+    }
+}
+
+/// Deletes a wager and returns the messages refunding both players
+fn refund_wager(storage: &mut dyn Storage, wager_id: u64, wager: &Wager) -> StdResult<Vec<SubMsg>> {
+    WAGERS.remove(storage, wager_id.into());
+    VOTES.remove(storage, wager_id.into());
+    remove_jackpot_commitments(storage, wager_id, wager);
+    escrow_withdraw(storage, &wager.user1_balance)?;
+    escrow_withdraw(storage, &wager.user2_balance)?;
+
+    let mut messages: Vec<SubMsg> = send_tokens(&wager.user1, &wager.user1_balance)?;
+    messages.append(&mut send_tokens(&wager.user2, &wager.user2_balance)?);
+    Ok(messages)
+}
+
+/// Counts a settled wager in the stats of both players.
+fn record_result(
+    storage: &mut dyn Storage,
+    winner: &Addr,
+    loser: &Addr,
+    forfeited: bool,
+) -> StdResult<()> {
+    PLAYER_STATS.update(storage, winner, |stats| -> StdResult<_> {
+        let mut stats: PlayerStats = stats.unwrap_or_default();
+        stats.wins += 1;
+        Ok(stats)
+    })?;
+    PLAYER_STATS.update(storage, loser, |stats| -> StdResult<_> {
+        let mut stats: PlayerStats = stats.unwrap_or_default();
+        stats.losses += 1;
+        if forfeited {
+            stats.forfeits += 1;
+        }
+        Ok(stats)
+    })?;
+    Ok(())
+}
+
+/// Deletes a wager and sends the pot, less the platform fee, to the winner. The pot of a
+/// king-of-the-hill wager stays escrowed as the stake of the winner in a new wager instead.
+fn settle_wager(
+    deps: DepsMut,
+    env: &Env,
+    wager_id: u64,
+    wager: &Wager,
+    winner_address: Addr,
+    forfeited_by: Option<Addr>,
+    action: &str,
+) -> Result<Response, ContractError> {
+    let state = config(deps.storage).load()?;
+    VOTES.remove(deps.storage, wager_id.into());
+    let owner_commitment = OWNER_COMMITMENTS.may_load(deps.storage, wager_id.into())?;
+    let player_commitment =
+        PLAYER_COMMITMENTS.may_load(deps.storage, (wager_id.into(), &winner_address))?;
+    remove_jackpot_commitments(deps.storage, wager_id, wager);
+
+    // the fee of each side stays escrowed as claimable tokens
+    let user1_payout = take_fee(deps.storage, &state, &wager.user1, &wager.user1_balance)?;
+    let user2_payout = take_fee(deps.storage, &state, &wager.user2, &wager.user2_balance)?;
+
+    let mut fee = wager.user1_balance.clone();
+    fee.add_balance(&wager.user2_balance);
+    fee.sub_balance(&user1_payout)?;
+    fee.sub_balance(&user2_payout)?;
+
+    let mut event = WagerEvent::new(WagerEventKind::WagerSettled, wager_id, wager)
+        .status(WagerStatus::Settled)
+        .winner(winner_address.clone())
+        .fee(fee);
+    // only a wager with an opponent was played
+    let played = wager.user2 != "empty";
+    if played {
+        let loser = if winner_address == wager.user1 {
+            &wager.user2
+        } else {
+            &wager.user1
+        };
+        record_result(deps.storage, &winner_address, loser, forfeited_by.is_some())?;
+    }
+    if let Some(player) = forfeited_by {
+        event = event.forfeited_by(player);
+    }
+    let mut messages: Vec<SubMsg> = vec![];
+    let mut next_hill = None;
+    // the match is over either way
+    WAGERS.remove(deps.storage, wager_id.into());
+    match &wager.hill {
+        Some(hill) => {
+            // the winner stays on with the pot as their stake for the next challenger, in a new
+            // wager so that every match has its own side pool and jackpot draw
+            let streak = if !played {
+                hill.streak
+            } else if winner_address == wager.user1 {
+                hill.streak + 1
+            } else {
+                1
+            };
+            let mut pot = user1_payout;
+            pot.add_balance(&user2_payout);
+            let champion = Wager {
+                user1: winner_address.clone(),
+                user2: Addr::unchecked("empty"),
+                user1_balance: pot.clone(),
+                user2_balance: GenericBalance::new(),
+                stake: pot,
+                stake_value: None,
+                status: WagerStatus::Open,
+                series: wager.series.as_ref().map(|series| Series {
+                    length: series.length,
+                    rounds: vec![],
+                }),
+                hill: Some(Hill { streak }),
+                cancel_proposed_by: None,
+                rematch_requested_by: vec![],
+                ..wager.clone()
+            };
+            let next_id = next_wager_id(deps.storage)?;
+            WAGERS.save(deps.storage, next_id.into(), &champion)?;
+            event = event.streak(streak);
+            next_hill = Some((next_id, champion));
+        }
+        None => {
+            // the players can play again
+            let mut held = false;
+            if played && wager.can_rematch() {
+                let rematch = Rematch {
+                    arbiter: wager.arbiter.clone(),
+                    user1: wager.user1.clone(),
+                    user2: wager.user2.clone(),
+                    game_id: wager.game_id.clone(),
+                    stake: wager.stake.clone(),
+                    stake_value: wager.stake_value,
+                    series_length: wager.series.as_ref().map(|series| series.length),
+                    expires: env.block.height + REMATCH_PERIOD,
+                };
+                // the players can only rematch their latest wager
+                let pair = rematch_pair(&wager.user1, &wager.user2);
+                if let Some(previous) = REMATCH_PAIRS.may_load(deps.storage, pair)? {
+                    REMATCHES.remove(deps.storage, previous.into());
+                }
+                REMATCH_PAIRS.save(deps.storage, pair, &wager_id)?;
+                REMATCHES.save(deps.storage, wager_id.into(), &rematch)?;
+                held = wager.rematch_requested_by.contains(&winner_address);
+            }
+
+            if held {
+                // the winnings stay escrowed as claimable tokens to fund the rematch
+                credit_claimable(deps.storage, &winner_address, &user1_payout)?;
+                credit_claimable(deps.storage, &winner_address, &user2_payout)?;
+            } else {
+                escrow_withdraw(deps.storage, &user1_payout)?;
+                escrow_withdraw(deps.storage, &user2_payout)?;
+                // send user1 tokens to winner
+                messages.append(&mut send_tokens(&winner_address, &user1_payout)?);
+                // send user2 tokens to winner
+                messages.append(&mut send_tokens(&winner_address, &user2_payout)?);
+            }
+        }
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", action)
+        .add_attribute("id", wager_id.to_string())
+        .add_attribute("to", winner_address.clone())
+        .add_event(event.into_event()?)
+        .add_submessages(messages);
+    if let Some((next_id, champion)) = next_hill {
+        res = res.add_attribute("next_id", next_id.to_string()).add_event(
+            WagerEvent::new(WagerEventKind::WagerCreated, next_id, &champion).into_event()?,
+        );
+    }
+    let side = if winner_address == wager.user1 {
+        Side::User1
+    } else {
+        Side::User2
+    };
+    if let Some(event) = close_side_pool(deps.storage, wager_id, Some(side))? {
+        res = res.add_event(event);
+    }
+    let commitments = owner_commitment.map(|commitment| (commitment, player_commitment));
+    if let Some((messages, event)) =
+        start_jackpot_draw(deps, env, &state, wager_id, &winner_address, commitments)?
+    {
+        res = res.add_event(event).add_submessages(messages);
+    }
+    Ok(res)
+}
+
+pub fn execute_place_side_bet(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    balance: Balance,
+    wager_id: u64,
+    side: Side,
+) -> Result<Response, ContractError> {
+    require_not_blocked(deps.storage, &sender)?;
+    let wager = get_wager(&deps, wager_id)?;
+    // bets close once a round is reported, an arbiter voted or a choice is revealed
+    let started = wager
+        .series
+        .as_ref()
+        .is_some_and(|series| !series.rounds.is_empty())
+        || wager
+            .chance
+            .as_ref()
+            .is_some_and(|chance| chance.user1_reveal.is_some() || chance.user2_reveal.is_some())
+        || VOTES.has(deps.storage, wager_id.into());
+    if wager.status != WagerStatus::Matched || started {
+        return Err(ContractError::SideBetsClosed {});
+    }
+    // nobody who can decide the outcome bets on it
+    if sender == wager.user1
+        || sender == wager.user2
+        || sender == wager.arbiter
+        || is_arbiter_of(deps.storage, wager.game_id.as_deref(), &sender)?
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let deposit = GenericBalance::from(balance);
+    let (asset, amount) = deposit
+        .single_asset()
+        .ok_or(ContractError::InvalidSideBet {})?;
+    if amount.is_zero() {
+        return Err(ContractError::EmptyBalance {});
+    }
+
+    let mut pool = SIDE_POOLS
+        .may_load(deps.storage, wager_id.into())?
+        .unwrap_or_default();
+    if pool.status != SidePoolStatus::Open {
+        return Err(ContractError::SideBetsClosed {});
+    }
+    // the first bet sets the asset of the pool
+    if pool.asset.get_or_insert_with(|| asset.clone()) != &asset {
+        return Err(ContractError::InvalidSideBet {});
+    }
+
+    let mut bet = SIDE_BETS
+        .may_load(deps.storage, (wager_id.into(), &sender))?
+        .unwrap_or_default();
+    match side {
+        Side::User1 => {
+            pool.user1_total += amount;
+            bet.user1 += amount;
+        }
+        Side::User2 => {
+            pool.user2_total += amount;
+            bet.user2 += amount;
+        }
+    }
+    pool.unclaimed += amount;
+
+    record_stake(deps.storage, &sender, env.block.time, &deposit)?;
+    escrow_deposit(deps.storage, &deposit)?;
+    SIDE_POOLS.save(deps.storage, wager_id.into(), &pool)?;
+    SIDE_BETS.save(deps.storage, (wager_id.into(), &sender), &bet)?;
+
+    let event = SidePoolEvent::new(SidePoolEventKind::SideBetPlaced, wager_id, &pool)
+        .bet(sender, Some(side), amount)
+        .into_event()?;
+    Ok(Response::new()
+        .add_attribute("action", "place_side_bet")
+        .add_attribute("id", wager_id.to_string())
+        .add_event(event))
+}
+
+pub fn execute_lock_side_bets(
+    deps: DepsMut,
+    info: MessageInfo,
+    wager_id: u64,
+) -> Result<Response, ContractError> {
+    let wager = get_wager(&deps, wager_id)?;
+    require_arbiter(deps.storage, &wager, &info.sender)?;
+
+    let mut pool = SIDE_POOLS
+        .may_load(deps.storage, wager_id.into())?
+        .unwrap_or_default();
+    if pool.status != SidePoolStatus::Open {
+        return Err(ContractError::SideBetsClosed {});
+    }
+    pool.status = SidePoolStatus::Locked;
+    SIDE_POOLS.save(deps.storage, wager_id.into(), &pool)?;
+
+    let event =
+        SidePoolEvent::new(SidePoolEventKind::SideBetsLocked, wager_id, &pool).into_event()?;
+    Ok(Response::new()
+        .add_attribute("action", "lock_side_bets")
+        .add_attribute("id", wager_id.to_string())
+        .add_event(event))
+}
+
+pub fn execute_claim_side_bet(
+    deps: DepsMut,
+    info: MessageInfo,
+    wager_id: u64,
+) -> Result<Response, ContractError> {
+    let mut pool = SIDE_POOLS
+        .may_load(deps.storage, wager_id.into())?
+        .ok_or(ContractError::NothingToClaim {})?;
+    let bet = SIDE_BETS
+        .may_load(deps.storage, (wager_id.into(), &info.sender))?
+        .ok_or(ContractError::NothingToClaim {})?;
+
+    let amount = match &pool.status {
+        SidePoolStatus::Won { side: Side::User1 } => {
+            pool.payout.multiply_ratio(bet.user1, pool.user1_total)
+        }
+        SidePoolStatus::Won { side: Side::User2 } => {
+            pool.payout.multiply_ratio(bet.user2, pool.user2_total)
+        }
+        SidePoolStatus::Refunded => bet.user1 + bet.user2,
+        _ => return Err(ContractError::SideBetsPending {}),
+    };
+    let asset = match (&pool.asset, amount.is_zero()) {
+        (Some(asset), false) => asset.clone(),
+        _ => return Err(ContractError::NothingToClaim {}),
+    };
+
+    SIDE_BETS.remove(deps.storage, (wager_id.into(), &info.sender));
+    pool.unclaimed = pool.unclaimed.checked_sub(amount).map_err(StdError::from)?;
+    SIDE_POOLS.save(deps.storage, wager_id.into(), &pool)?;
+
+    let claimed = GenericBalance::from_asset(&asset, amount);
+    escrow_withdraw(deps.storage, &claimed)?;
+    let messages = send_tokens(&info.sender, &claimed)?;
+
+    let event = SidePoolEvent::new(SidePoolEventKind::SideBetClaimed, wager_id, &pool)
+        .bet(info.sender, None, amount)
+        .into_event()?;
+    Ok(Response::new()
+        .add_attribute("action", "claim_side_bet")
+        .add_attribute("id", wager_id.to_string())
+        .add_event(event)
+        .add_submessages(messages))
+}
+
+/// Closes the side pool of a wager to new bets once play started, returns None if it has no
+/// open pool.
+fn lock_side_pool(storage: &mut dyn Storage, wager_id: u64) -> StdResult<Option<Event>> {
+    let mut pool = match SIDE_POOLS.may_load(storage, wager_id.into())? {
+        Some(pool) if pool.status == SidePoolStatus::Open => pool,
+        _ => return Ok(None),
+    };
+    pool.status = SidePoolStatus::Locked;
+    SIDE_POOLS.save(storage, wager_id.into(), &pool)?;
+
+    let event =
+        SidePoolEvent::new(SidePoolEventKind::SideBetsLocked, wager_id, &pool).into_event()?;
+    Ok(Some(event))
+}
+
+/// Closes the side pool of an ended wager, if it has one. The bets on the winner share the
+/// pool less the platform fee, every bet is refunded without a winner or without bets on
+/// both sides.
+fn close_side_pool(
+    storage: &mut dyn Storage,
+    wager_id: u64,
+    winner: Option<Side>,
+) -> StdResult<Option<Event>> {
+    let mut pool = match SIDE_POOLS.may_load(storage, wager_id.into())? {
+        Some(pool) => pool,
+        None => return Ok(None),
+    };
+    let state = config(storage).load()?;
+
+    let mut fee = Uint128::zero();
+    pool.status = match (winner, &pool.asset) {
+        (Some(side), Some(asset)) if !pool.user1_total.is_zero() && !pool.user2_total.is_zero() => {
+            let total = pool.user1_total + pool.user2_total;
+            fee = total.multiply_ratio(state.fee_bps, 10_000u64);
+            credit_claimable(
+                storage,
+                &state.owner,
+                &GenericBalance::from_asset(asset, fee),
+            )?;
+            pool.payout = total - fee;
+            pool.unclaimed = pool.payout;
+            SidePoolStatus::Won { side }
+        }
+        _ => SidePoolStatus::Refunded,
+    };
+    SIDE_POOLS.save(storage, wager_id.into(), &pool)?;
+
+    let event = SidePoolEvent::new(SidePoolEventKind::SidePoolClosed, wager_id, &pool)
+        .fee(fee)
+        .into_event()?;
+    Ok(Some(event))
+}
+
+/// Draws the jackpot for a settled wager. Beacon draws are decided at once, commit-reveal
+/// draws wait for the committed secrets of the owner and of the winner, if any.
+fn start_jackpot_draw(
+    deps: DepsMut,
+    env: &Env,
+    state: &State,
+    wager_id: u64,
+    winner: &Addr,
+    commitments: Option<(Binary, Option<Binary>)>,
+) -> Result<Option<(Vec<SubMsg>, Event)>, ContractError> {
+    if state.jackpot_odds == 0 {
+        return Ok(None);
+    }
+    match &state.randomness {
+        None => Ok(None),
+        Some(RandomnessSource::Beacon { contract }) => {
+            let beacon: RandomnessResponse = deps
+                .querier
+                .query_wasm_smart(contract, &BeaconQueryMsg::Randomness {})?;
+            let randomness = Sha256::new()
+                .chain(beacon.randomness.as_slice())
+                .chain(wager_id.to_be_bytes())
+                .finalize();
+            let won = draw_wins(state, &randomness);
+            let draw = finish_jackpot_draw(deps.storage, env, wager_id, winner, won)?;
+            Ok(Some(draw))
+        }
+        Some(RandomnessSource::CommitReveal {}) => {
+            // without the owner's commitment there is nothing to draw against
+            if let Some((commitment, player_commitment)) = commitments {
+                let entropy = Sha256::new()
+                    .chain(env.block.height.to_be_bytes())
+                    .chain(env.block.time.nanos().to_be_bytes())
+                    .chain(wager_id.to_be_bytes())
+                    .finalize();
+                PENDING_DRAWS.save(
+                    deps.storage,
+                    wager_id.into(),
+                    &PendingDraw {
+                        winner: winner.clone(),
+                        commitment,
+                        player_commitment,
+                        secret: None,
+                        player_secret: None,
+                        entropy: Binary::from(&entropy[..]),
+                        deadline: env.block.height + JACKPOT_REVEAL_PERIOD,
+                    },
+                )?;
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// Whether the randomness wins the jackpot at the configured odds
+fn draw_wins(state: &State, randomness: &[u8]) -> bool {
+    let mut roll = [0u8; 8];
+    roll.copy_from_slice(&randomness[..8]);
+    state.jackpot_odds != 0 && u64::from_be_bytes(roll) % state.jackpot_odds == 0
+}
+
+/// Pays the jackpot to the winner if the draw won and there is a jackpot
+fn finish_jackpot_draw(
+    storage: &mut dyn Storage,
+    env: &Env,
+    wager_id: u64,
+    winner: &Addr,
+    won: bool,
+) -> Result<(Vec<SubMsg>, Event), ContractError> {
+    let jackpot = JACKPOT.may_load(storage)?.unwrap_or_default();
+    let won = won && !jackpot.is_empty();
+
+    let mut messages = vec![];
+    let mut amount = GenericBalance::new();
+    if won {
+        JACKPOT.save(storage, &GenericBalance::new())?;
+        escrow_withdraw(storage, &jackpot)?;
+        JACKPOT_WINS.save(
+            storage,
+            wager_id.into(),
+            &JackpotWin {
+                wager_id,
+                winner: winner.clone(),
+                amount: jackpot.clone(),
+                height: env.block.height,
+            },
+        )?;
+        messages = send_tokens(winner, &jackpot)?;
+        amount = jackpot;
+    }
+
+    let event = JackpotDrawEvent {
+        wager_id,
+        winner: winner.clone(),
+        won,
+        amount,
+    }
+    .into_event()?;
+    Ok((messages, event))
+}
+
+/// Takes the platform fee from one side of a settled pot and returns what is left for the
+/// winner. The referrer of the player is credited their share of the fee and the jackpot
+/// receives its share of what is left, the owner the rest.
+fn take_fee(
+    storage: &mut dyn Storage,
+    state: &State,
+    player: &Addr,
+    side: &GenericBalance,
+) -> StdResult<GenericBalance> {
+    let mut fee = side.scale(state.fee_bps, 10_000u64);
+    let mut payout = side.clone();
+    payout.sub_balance(&fee)?;
+
+    if let Some(referrer) = REFERRERS.may_load(storage, player)? {
+        let reward = fee.scale(state.referral_share_bps, 10_000u64);
+        fee.sub_balance(&reward)?;
+        credit_claimable(storage, &referrer, &reward)?;
+
+        let mut stats = REFERRAL_STATS
+            .may_load(storage, &referrer)?
+            .unwrap_or_default();
+        stats.volume.add_balance(side);
+        stats.earnings.add_balance(&reward);
+        REFERRAL_STATS.save(storage, &referrer, &stats)?;
+    }
+
+    let contribution = fee.scale(state.jackpot_share_bps, 10_000u64);
+    if !contribution.is_empty() {
+        fee.sub_balance(&contribution)?;
+        let mut jackpot = JACKPOT.may_load(storage)?.unwrap_or_default();
+        jackpot.add_balance(&contribution);
+        JACKPOT.save(storage, &jackpot)?;
+    }
+    credit_claimable(storage, &state.owner, &fee)?;
+    Ok(payout)
+}
+
+pub fn execute_join_queue(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    stake: Balance,
+    game_id: String,
+    max_rating_diff: Option<u32>,
+) -> Result<Response, ContractError> {
+    if stake.is_empty() {
+        return Err(ContractError::EmptyBalance {});
+    }
+    require_not_blocked(deps.storage, &sender)?;
+    if QUEUE.has(deps.storage, (&game_id, &sender)) {
+        return Err(ContractError::AlreadyQueued {});
+    }
+    // the stake counts towards the limits once it is in a wager
+    check_stake(
+        deps.storage,
+        &sender,
+        env.block.time,
+        &GenericBalance::from(stake.clone()),
+    )?;
+
+    let entry = QueueEntry {
+        rating: RATINGS
+            .may_load(deps.storage, &sender)?
+            .unwrap_or(DEFAULT_RATING),
+        player: sender,
+        stake,
+        max_rating_diff,
+        joined_at: env.block.height,
+        seq: QUEUE_SEQ.may_load(deps.storage)?.unwrap_or_default() + 1,
+    };
+    let stake_key = entry.stake_key()?;
+
+    // the longest waiting player with the same stake that both players accept is the opponent,
+    // looking only at the oldest entries so that a long queue costs no more gas
+    let candidates = QUEUE_ORDER
+        .prefix((&game_id, &stake_key))
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(MAX_QUEUE_SCAN)
+        .map(|item| item.map(|(_, player)| player))
+        .collect::<StdResult<Vec<Addr>>>()?
+        .into_iter()
+        .map(|player| QUEUE.load(deps.storage, (&game_id, &player)))
+        .collect::<StdResult<Vec<QueueEntry>>>()?
+        .into_iter()
+        .filter(|waiting| waiting.accepts(&entry));
+
+    // waiting players who became blocked, self-excluded or reached their limits are refunded
+    // and leave the queue instead of holding it up
+    let mut evicted = Response::new();
+    let mut opponent = None;
+    for waiting in candidates {
+        let eligible = require_not_blocked(deps.storage, &waiting.player).is_ok()
+            && check_stake(
+                deps.storage,
+                &waiting.player,
+                env.block.time,
+                &GenericBalance::from(waiting.stake.clone()),
+            )
+            .is_ok();
+        if eligible {
+            opponent = Some(waiting);
+            break;
+        }
+        let (messages, event) = remove_queue_entry(deps.storage, &game_id, &waiting)?;
+        evicted = evicted
+            .add_attribute("evicted", waiting.player)
+            .add_event(event)
+            .add_submessages(messages);
+    }
+
+    let opponent = match opponent {
+        Some(opponent) => opponent,
+        None => {
+            QUEUE_SEQ.save(deps.storage, &entry.seq)?;
+            QUEUE.save(deps.storage, (&game_id, &entry.player), &entry)?;
+            QUEUE_ORDER.save(
+                deps.storage,
+                (&game_id, &stake_key, entry.seq.into()),
+                &entry.player,
+            )?;
+            escrow_deposit(deps.storage, &GenericBalance::from(entry.stake.clone()))?;
+            let event = QueueEvent::new(
+                QueueEventKind::QueueJoined,
+                &game_id,
+                &entry.player,
+                &entry.stake,
+            )
+            .into_event()?;
+            return Ok(evicted
+                .add_attribute("action", "join_queue")
+                .add_attribute("game_id", game_id)
+                .add_attribute("player", entry.player)
+                .add_event(event));
+        }
+    };
+
+    // the queued stake moves into the wager, which records it again
+    unqueue(deps.storage, &game_id, &opponent)?;
+    escrow_withdraw(deps.storage, &GenericBalance::from(opponent.stake.clone()))?;
+
+    // the waiting player creates the wager and the caller joins it
+    execute_create_wager(
+        deps.branch(),
+        env.clone(),
+        opponent.player.clone(),
+        opponent.stake,
+        None,
+        Some(game_id.clone()),
+        None,
+        None,
+        None,
+    )?;
+    let wager_id = WAGER_SEQ.load(deps.storage)?;
+    let created = WagerEvent::new(
+        WagerEventKind::WagerCreated,
+        wager_id,
+        &get_wager(&deps, wager_id)?,
+    )
+    .into_event()?;
+    execute_add_funds(
+        deps.branch(),
+        env,
+        entry.player.clone(),
+        entry.stake,
+        wager_id,
+    )?;
+
+    // the wager is created and joined as if both players had sent the messages
+    let wager = get_wager(&deps, wager_id)?;
+    let joined = WagerEvent::new(WagerEventKind::WagerJoined, wager_id, &wager).into_event()?;
+
+    Ok(evicted
+        .add_attribute("action", "join_queue")
+        .add_attribute("game_id", game_id)
+        .add_attribute("id", wager_id.to_string())
+        .add_attribute("user1", opponent.player)
+        .add_attribute("user2", entry.player)
+        .add_event(created)
+        .add_event(joined)
+        .set_data(to_binary(&wager_id)?))
+}
+
+pub fn execute_leave_queue(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    game_id: String,
+) -> Result<Response, ContractError> {
+    let entry = QUEUE
+        .may_load(deps.storage, (&game_id, &info.sender))?
+        .ok_or(ContractError::NotInQueue {})?;
+    let (messages, event) = remove_queue_entry(deps.storage, &game_id, &entry)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "leave_queue")
+        .add_attribute("game_id", game_id)
+        .add_attribute("to", entry.player)
+        .add_event(event)
+        .add_submessages(messages))
+}
+
+pub fn execute_force_leave_queue(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    game_id: String,
+    player: String,
+) -> Result<Response, ContractError> {
+    require_role(deps.storage, Role::Operator, &info.sender)?;
+    let player = deps.api.addr_validate(&player)?;
+    if !BLOCKLIST.has(deps.storage, &player) {
+        return Err(ContractError::NotBlocked {});
+    }
+    let entry = QUEUE
+        .may_load(deps.storage, (&game_id, &player))?
+        .ok_or(ContractError::NotInQueue {})?;
+    let (messages, event) = remove_queue_entry(deps.storage, &game_id, &entry)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "force_leave_queue")
+        .add_attribute("game_id", game_id)
+        .add_attribute("to", entry.player)
+        .add_event(event)
+        .add_submessages(messages))
+}
+
+/// Removes the entry from the queue of the game and from the order of its stake
+fn unqueue(storage: &mut dyn Storage, game_id: &str, entry: &QueueEntry) -> StdResult<()> {
+    QUEUE.remove(storage, (game_id, &entry.player));
+    QUEUE_ORDER.remove(storage, (game_id, &entry.stake_key()?, entry.seq.into()));
+    Ok(())
+}
+
+/// Removes the entry from the queue of the game, returns the refund of its stake and the
+/// queue_left event
+fn remove_queue_entry(
+    storage: &mut dyn Storage,
+    game_id: &str,
+    entry: &QueueEntry,
+) -> Result<(Vec<SubMsg>, Event), ContractError> {
+    unqueue(storage, game_id, entry)?;
+    escrow_withdraw(storage, &GenericBalance::from(entry.stake.clone()))?;
+
+    let messages = send_tokens(&entry.player, &GenericBalance::from(entry.stake.clone()))?;
+    let event = QueueEvent::new(
+        QueueEventKind::QueueLeft,
+        game_id,
+        &entry.player,
+        &entry.stake,
+    )
+    .into_event()?;
+    Ok((messages, event))
+}
+
+pub fn execute_set_rating(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    player: String,
+    rating: u32,
+) -> Result<Response, ContractError> {
+    require_role(deps.storage, Role::Operator, &info.sender)?;
+
+    let player = deps.api.addr_validate(&player)?;
+    RATINGS.save(deps.storage, &player, &rating)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_rating")
+        .add_attribute("player", player.clone())
+        .add_attribute("rating", rating.to_string())
+        .add_event(RatingEvent { player, rating }.into_event()))
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    update: ConfigUpdate,
+) -> Result<Response, ContractError> {
+    let mut state = config(deps.storage).load()?;
+    require_role(deps.storage, Role::Owner, &info.sender)?;
+    require_no_delay(deps.storage)?;
+    apply_config_update(deps.api, &mut state, update)?;
+    config(deps.storage).save(&state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_config")
+        .add_event(ConfigEvent::from(&state).into_event()?))
+}
+
+pub fn execute_propose_config_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    update: TimelockedUpdate,
+) -> Result<Response, ContractError> {
+    let state = config(deps.storage).load()?;
+    require_role(deps.storage, update.role(), &info.sender)?;
+    // refuse invalid updates now rather than once the delay has passed
+    validate_update(deps.api, &state, &update)?;
+
+    let change = ConfigChange {
+        id: next_config_change_id(deps.storage)?,
+        update,
+        eta: checked_plus_seconds(env.block.time, state.config_delay)?,
+    };
+    CONFIG_CHANGES.save(deps.storage, change.id.into(), &change)?;
+
+    let event = ConfigChangeEvent::new(ConfigChangeEventKind::ConfigChangeProposed, &change)?;
+    Ok(Response::new()
+        .add_attribute("action", "propose_config_change")
+        .add_attribute("id", change.id.to_string())
+        .add_event(event.into_event()))
+}
+
+pub fn execute_execute_config_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let change = CONFIG_CHANGES.load(deps.storage, id.into())?;
+    require_role(deps.storage, change.update.role(), &info.sender)?;
+    if env.block.time < change.eta {
+        return Err(ContractError::ChangeNotReady {});
+    }
+    CONFIG_CHANGES.remove(deps.storage, id.into());
+
+    let event = match change.update {
+        TimelockedUpdate::Config(update) => {
+            let mut state = config(deps.storage).load()?;
+            apply_config_update(deps.api, &mut state, update)?;
+            config(deps.storage).save(&state)?;
+            ConfigEvent {
+                change_id: Some(id),
+                ..ConfigEvent::from(&state)
+            }
+            .into_event()?
+        }
+        TimelockedUpdate::GrantRole {
+            role,
+            address,
+            game_id,
+        } => grant_role(deps, role, address, game_id)?,
+        TimelockedUpdate::RevokeRole {
+            role,
+            address,
+            game_id,
+        } => revoke_role(deps, info.sender, role, address, game_id)?,
+        TimelockedUpdate::UpdateRelayers { add, remove } => update_relayers(deps, add, remove)?,
+        TimelockedUpdate::UpdateBlocklist { add, remove } => update_blocklist(deps, add, remove)?,
+    };
+    Ok(Response::new()
+        .add_attribute("action", "execute_config_change")
+        .add_attribute("id", id.to_string())
+        .add_event(event))
+}
+
+pub fn execute_cancel_config_change(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let change = CONFIG_CHANGES.load(deps.storage, id.into())?;
+    require_role(deps.storage, change.update.role(), &info.sender)?;
+    CONFIG_CHANGES.remove(deps.storage, id.into());
+
+    let event = ConfigChangeEvent::new(ConfigChangeEventKind::ConfigChangeCancelled, &change)?;
+    Ok(Response::new()
+        .add_attribute("action", "cancel_config_change")
+        .add_attribute("id", id.to_string())
+        .add_event(event.into_event()))
+}
+
+/// Refuses updates made at once while a config delay is set, they must be proposed instead
+fn require_no_delay(storage: &dyn Storage) -> Result<(), ContractError> {
+    if config_read(storage).load()?.config_delay > 0 {
+        return Err(ContractError::Timelocked {});
+    }
+    Ok(())
+}
+
+/// Validates a timelocked update without applying it
+fn validate_update(
+    api: &dyn Api,
+    state: &State,
+    update: &TimelockedUpdate,
+) -> Result<(), ContractError> {
+    match update {
+        TimelockedUpdate::Config(update) => {
+            apply_config_update(api, &mut state.clone(), update.clone())?;
+        }
+        TimelockedUpdate::GrantRole {
+            role,
+            address,
+            game_id,
+        }
+        | TimelockedUpdate::RevokeRole {
+            role,
+            address,
+            game_id,
+        } => {
+            api.addr_validate(address)?;
+            if game_id.is_some() && *role != Role::Arbiter {
+                return Err(ContractError::GameScopedRole {});
+            }
+        }
+        TimelockedUpdate::UpdateRelayers { add, remove }
+        | TimelockedUpdate::UpdateBlocklist { add, remove } => {
+            for address in add.iter().chain(remove) {
+                api.addr_validate(address)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validates the fields of a configuration update that are set and applies them to the state.
+/// Adds seconds to a block time, failing instead of panicking on overflow
+fn checked_plus_seconds(time: Timestamp, seconds: u64) -> StdResult<Timestamp> {
+    seconds
+        .checked_mul(1_000_000_000)
+        .and_then(|nanos| nanos.checked_add(time.nanos()))
+        .map(Timestamp::from_nanos)
+        .ok_or_else(|| {
+            StdError::overflow(OverflowError::new(
+                OverflowOperation::Add,
+                time.nanos(),
+                seconds,
+            ))
+        })
+}
+
+fn apply_config_update(
+    api: &dyn Api,
+    state: &mut State,
+    update: ConfigUpdate,
+) -> Result<(), ContractError> {
+    let ConfigUpdate {
+        oracle,
+        price_tolerance_bps,
+        fee_bps,
+        referral_share_bps,
+        jackpot_share_bps,
+        jackpot_odds,
+        randomness,
+        approval_threshold,
+        required_approvals,
+        config_delay,
+        blocked_policy,
+    } = update;
+
+    if let Some(oracle) = oracle {
+        state.oracle = Some(api.addr_validate(&oracle)?);
+    }
+    if let Some(price_tolerance_bps) = price_tolerance_bps {
+        if price_tolerance_bps > 10_000 {
+            return Err(ContractError::InvalidTolerance {});
+        }
+        state.price_tolerance_bps = price_tolerance_bps;
+    }
+    if let Some(fee_bps) = fee_bps {
+        if fee_bps > 10_000 {
+            return Err(ContractError::InvalidFee {});
+        }
+        state.fee_bps = fee_bps;
+    }
+    if let Some(referral_share_bps) = referral_share_bps {
+        if referral_share_bps > 10_000 {
+            return Err(ContractError::InvalidFee {});
+        }
+        state.referral_share_bps = referral_share_bps;
+    }
+    if let Some(jackpot_share_bps) = jackpot_share_bps {
+        if jackpot_share_bps > 10_000 {
+            return Err(ContractError::InvalidFee {});
+        }
+        state.jackpot_share_bps = jackpot_share_bps;
+    }
+    if let Some(jackpot_odds) = jackpot_odds {
+        state.jackpot_odds = jackpot_odds;
+    }
+    if let Some(randomness) = randomness {
+        if let RandomnessSource::Beacon { contract } = &randomness {
+            api.addr_validate(contract.as_str())?;
+        }
+        state.randomness = Some(randomness);
+    }
+    if let Some(mut approval_threshold) = approval_threshold {
+        approval_threshold.normalize();
+        state.approval_threshold = approval_threshold;
+    }
+    if let Some(required_approvals) = required_approvals {
+        state.required_approvals = required_approvals;
+    }
+    if !state.approval_threshold.is_empty() && state.required_approvals == 0 {
+        return Err(ContractError::InvalidApprovals {});
+    }
+    if let Some(config_delay) = config_delay {
+        if config_delay > MAX_CONFIG_DELAY {
+            return Err(ContractError::InvalidConfigDelay {});
+        }
+        state.config_delay = config_delay;
+    }
+    if let Some(blocked_policy) = blocked_policy {
+        state.blocked_policy = blocked_policy;
+    }
+    Ok(())
+}
+
+pub fn execute_set_stake_limits(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    mut limits: StakeLimits,
+) -> Result<Response, ContractError> {
+    limits.normalize();
+    let mut player_limits = PLAYER_LIMITS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    player_limits.refresh(env.block.time);
+
+    // tighter caps apply at once, looser ones replace them after the cool-down
+    let tightest = player_limits.limits.tightest(&limits);
+    player_limits.pending = if tightest != limits {
+        Some(PendingLimits {
+            limits,
+            effective: env.block.time.plus_seconds(LIMIT_COOLDOWN),
+        })
+    } else {
+        None
+    };
+    player_limits.limits = tightest;
+    PLAYER_LIMITS.save(deps.storage, &info.sender, &player_limits)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_stake_limits")
+        .add_event(LimitsEvent::new(info.sender, &player_limits)?.into_event()))
+}
+
+pub fn execute_self_exclude(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    seconds: u64,
+) -> Result<Response, ContractError> {
+    let mut player_limits = PLAYER_LIMITS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if seconds > MAX_SELF_EXCLUSION {
+        return Err(ContractError::InvalidExclusion {});
+    }
+    let until = checked_plus_seconds(env.block.time, seconds)?;
+    // an exclusion is only ever extended
+    let until = player_limits
+        .excluded_until
+        .map_or(until, |excluded_until| excluded_until.max(until));
+    player_limits.excluded_until = Some(until);
+    PLAYER_LIMITS.save(deps.storage, &info.sender, &player_limits)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "self_exclude")
+        .add_event(
+            SelfExclusionEvent {
+                player: info.sender,
+                until: until.seconds(),
+            }
+            .into_event(),
+        ))
+}
+
+/// Refuses the stake of a self-excluded player or one that takes them over their limits, and
+/// counts it in the stake history of the player otherwise.
+fn record_stake(
+    storage: &mut dyn Storage,
+    player: &Addr,
+    now: Timestamp,
+    stake: &GenericBalance,
+) -> Result<(), ContractError> {
+    let history = check_stake(storage, player, now, stake)?;
+    STAKE_HISTORY.save(storage, player, &history)?;
+    Ok(())
+}
+
+/// Refuses the stake like record_stake without counting it, returns the stake history of the
+/// player with the stake counted
+fn check_stake(
+    storage: &dyn Storage,
+    player: &Addr,
+    now: Timestamp,
+    stake: &GenericBalance,
+) -> Result<Vec<StakeBucket>, ContractError> {
+    let today = now.seconds() / SECONDS_PER_DAY;
+    let mut history = STAKE_HISTORY.may_load(storage, player)?.unwrap_or_default();
+    history.retain(|bucket| bucket.day + STAKE_HISTORY_DAYS > today);
+    match history.last_mut() {
+        Some(bucket) if bucket.day == today => bucket.staked.add_balance(stake),
+        _ => history.push(StakeBucket {
+            day: today,
+            staked: stake.clone(),
+        }),
+    }
+
+    if let Some(mut player_limits) = PLAYER_LIMITS.may_load(storage, player)? {
+        player_limits.refresh(now);
+        if player_limits.is_excluded(now) {
+            return Err(ContractError::SelfExcluded {});
+        }
+        let exceeded = player_limits
+            .limits
+            .windows()
+            .iter()
+            .any(|(days, caps)| staked_since(&history, today, *days).exceeds_any(caps));
+        if exceeded {
+            return Err(ContractError::StakeLimitExceeded {});
+        }
+    }
+    Ok(history)
+}
+
+pub fn execute_set_referrer(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    referrer: String,
+) -> Result<Response, ContractError> {
+    let referrer = deps.api.addr_validate(&referrer)?;
+    if referrer == info.sender {
+        return Err(ContractError::InvalidReferrer {});
+    }
+    if REFERRERS.may_load(deps.storage, &info.sender)?.is_some() {
+        return Err(ContractError::ReferrerAlreadySet {});
+    }
+    REFERRERS.save(deps.storage, &info.sender, &referrer)?;
+    REFERRED.save(deps.storage, (&referrer, &info.sender), &Empty {})?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_referrer")
+        .add_event(
+            ReferrerEvent {
+                player: info.sender,
+                referrer,
+            }
+            .into_event(),
+        ))
+}
+
+pub fn execute_commit_jackpot_seed(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    wager_id: u64,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    let wager = get_wager(&deps, wager_id)?;
+    // players commit their own secret, so that the owner cannot choose the outcome alone
+    if info.sender == wager.user1 || info.sender == wager.user2 {
+        PLAYER_COMMITMENTS.save(deps.storage, (wager_id.into(), &info.sender), &commitment)?;
+    } else {
+        require_role(deps.storage, Role::Owner, &info.sender)?;
+        OWNER_COMMITMENTS.save(deps.storage, wager_id.into(), &commitment)?;
+    }
+
+    let event = JackpotCommitEvent {
+        wager_id,
+        committer: info.sender,
+        commitment,
+    };
+    Ok(Response::new()
+        .add_attribute("action", "commit_jackpot_seed")
+        .add_attribute("id", wager_id.to_string())
+        .add_event(event.into_event()))
+}
+
+pub fn execute_reveal_jackpot_seed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wager_id: u64,
+    secret: Binary,
+) -> Result<Response, ContractError> {
+    let mut pending = PENDING_DRAWS
+        .may_load(deps.storage, wager_id.into())?
+        .ok_or(ContractError::NoPendingDraw {})?;
+    if env.block.height > pending.deadline {
+        return Err(ContractError::RevealPeriodOver {});
+    }
+    let hash = Sha256::digest(secret.as_slice());
+    // the winner reveals their own secret, owners the owner's
+    let (commitment, revealed) = match &pending.player_commitment {
+        Some(commitment) if info.sender == pending.winner && pending.player_secret.is_none() => {
+            (commitment, &mut pending.player_secret)
+        }
+        _ => {
+            require_role(deps.storage, Role::Owner, &info.sender)?;
+            (&pending.commitment, &mut pending.secret)
+        }
+    };
+    if revealed.is_some() {
+        return Err(ContractError::AlreadyRevealed {});
+    }
+    if hash[..] != commitment[..] {
+        return Err(ContractError::InvalidReveal {});
+    }
+    *revealed = Some(secret);
+
+    let res = Response::new()
+        .add_attribute("action", "reveal_jackpot_seed")
+        .add_attribute("id", wager_id.to_string());
+    if !pending.is_revealed() {
+        PENDING_DRAWS.save(deps.storage, wager_id.into(), &pending)?;
+        let event = JackpotRevealEvent {
+            wager_id,
+            revealer: info.sender,
+        };
+        return Ok(res.add_event(event.into_event()));
+    }
+    PENDING_DRAWS.remove(deps.storage, wager_id.into());
+
+    let state = config(deps.storage).load()?;
+    let mut hasher = Sha256::new().chain(pending.secret.unwrap_or_default().as_slice());
+    if let Some(player_secret) = pending.player_secret {
+        hasher = hasher.chain(player_secret.as_slice());
+    }
+    let randomness = hasher.chain(pending.entropy.as_slice()).finalize();
+    let won = draw_wins(&state, &randomness);
+    let (messages, event) =
+        finish_jackpot_draw(deps.storage, &env, wager_id, &pending.winner, won)?;
+
+    Ok(res.add_event(event).add_submessages(messages))
+}
+
+pub fn execute_claim_draw_timeout(
+    deps: DepsMut,
+    env: Env,
+    wager_id: u64,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_DRAWS
+        .may_load(deps.storage, wager_id.into())?
+        .ok_or(ContractError::NoPendingDraw {})?;
+    if env.block.height <= pending.deadline {
+        return Err(ContractError::RevealPeriodOpen {});
+    }
+    PENDING_DRAWS.remove(deps.storage, wager_id.into());
+
+    // withholding a secret never pays: a winner who did not reveal loses the draw, an owner
+    // who did not reveal loses it to the winner
+    let won = pending.player_commitment.is_none() || pending.player_secret.is_some();
+    let (messages, event) =
+        finish_jackpot_draw(deps.storage, &env, wager_id, &pending.winner, won)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "claim_draw_timeout")
+        .add_attribute("id", wager_id.to_string())
+        .add_event(event)
+        .add_submessages(messages))
+}
+
+/// Drops the commitments of the jackpot draw of an ended wager
+fn remove_jackpot_commitments(storage: &mut dyn Storage, wager_id: u64, wager: &Wager) {
+    OWNER_COMMITMENTS.remove(storage, wager_id.into());
+    PLAYER_COMMITMENTS.remove(storage, (wager_id.into(), &wager.user1));
+    PLAYER_COMMITMENTS.remove(storage, (wager_id.into(), &wager.user2));
+}
+
+pub fn execute_claim(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let claimable = CLAIMABLE
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if claimable.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    CLAIMABLE.remove(deps.storage, &info.sender);
+    escrow_withdraw(deps.storage, &claimable)?;
+
+    let messages = send_tokens(&info.sender, &claimable)?;
+    Ok(Response::new()
+        .add_attribute("action", "claim")
+        .add_event(
+            ClaimEvent {
+                recipient: info.sender,
+                amount: claimable,
+            }
+            .into_event()?,
+        )
+        .add_submessages(messages))
+}
+
+pub fn execute_sweep(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Denom,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    require_role(deps.storage, Role::Owner, &info.sender)?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    let held = query_held(deps.as_ref(), &env.contract.address, &asset)?;
+    let escrowed = TOTAL_ESCROWED.may_load(deps.storage)?.unwrap_or_default();
+    let escrowed = match &asset {
+        Denom::Native(denom) => escrowed
+            .native
+            .iter()
+            .find(|token| &token.denom == denom)
+            .map(|token| token.amount),
+        Denom::Cw20(address) => escrowed
+            .cw20
+            .iter()
+            .find(|token| &token.address == address)
+            .map(|token| token.amount),
+    }
+    .unwrap_or_default();
+
+    // player funds stay in the contract, only the surplus can leave
+    let amount = held.saturating_sub(escrowed);
+    if amount.is_zero() {
+        return Err(ContractError::NothingToSweep {});
+    }
+
+    let swept = GenericBalance::from_asset(&asset, amount);
+    let messages: Vec<SubMsg> = send_tokens(&recipient, &swept)?;
+    let event = SweepEvent {
+        asset,
+        amount,
+        recipient: recipient.clone(),
+    }
+    .into_event()?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sweep")
+        .add_attribute("amount", amount)
+        .add_attribute("to", recipient)
+        .add_event(event)
+        .add_submessages(messages))
+}
+
+fn get_wager(deps: &DepsMut, wager_id: u64) -> Result<Wager, ContractError> {
+    match WAGERS.load(deps.storage, wager_id.into()) {
+        Ok(wager) => Ok(wager),
+        Err(_) => Err(ContractError::WagerDoesNotExist {}),
+    }
+}
+
+fn send_tokens(to: &Addr, balance: &GenericBalance) -> StdResult<Vec<SubMsg>> {
+    let native_balance = &balance.native;
+    let mut msgs: Vec<SubMsg> = if native_balance.is_empty() {
+        vec![]
+    } else {
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: to.into(),
+            amount: native_balance.to_vec(),
+        })]
+    };
+
+    let cw20_balance = &balance.cw20;
+    let cw20_msgs: StdResult<Vec<_>> = cw20_balance
+        .iter()
+        .map(|c| {
+            let msg = Cw20ExecuteMsg::Transfer {
+                recipient: to.into(),
+                amount: c.amount,
+            };
+            let exec = SubMsg::new(WasmMsg::Execute {
+                contract_addr: c.address.to_string(),
+                msg: to_binary(&msg)?,
+                funds: vec![],
+            });
+            Ok(exec)
+        })
+        .collect();
+    msgs.append(&mut cw20_msgs?);
+
+    let cw721_msgs: StdResult<Vec<_>> = balance
+        .cw721
+        .iter()
+        .map(|nft| {
+            let msg = Cw721ExecuteMsg::TransferNft {
+                recipient: to.into(),
+                token_id: nft.token_id.clone(),
+            };
+            let exec = SubMsg::new(WasmMsg::Execute {
+                contract_addr: nft.contract.to_string(),
+                msg: to_binary(&msg)?,
+                funds: vec![],
+            });
+            Ok(exec)
+        })
+        .collect();
+    msgs.append(&mut cw721_msgs?);
+    Ok(msgs)
+}
+
+/// Pulls CW20 tokens from the sender with TransferFrom. The wager is only created or joined
+/// in the reply, once the contract balance shows the whole amount arrived.
+pub fn execute_pull_cw20(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    token: String,
+    amount: Uint128,
+    action: PendingAction,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::EmptyBalance {});
+    }
+    let token = deps.api.addr_validate(&token)?;
+    // tokens of a session key are pulled from the player that granted it
+    let pulled = GenericBalance::from(Balance::Cw20(Cw20CoinVerified {
+        address: token.clone(),
+        amount,
+    }));
+    let sender = acting_player(deps.as_ref(), &env, sender, Some(&pulled))?;
+    let held_before = query_held(
+        deps.as_ref(),
+        &env.contract.address,
+        &Denom::Cw20(token.clone()),
+    )?;
+
+    PENDING_TRANSFER.save(
+        deps.storage,
+        &PendingTransfer {
+            sender: sender.clone(),
+            token: token.clone(),
+            amount,
+            held_before,
+            action,
+        },
+    )?;
+
+    let transfer = WasmMsg::Execute {
+        contract_addr: token.into(),
+        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+            owner: sender.into(),
+            recipient: env.contract.address.into(),
+            amount,
+        })?,
+        funds: vec![],
+    };
+    Ok(Response::new()
+        .add_attribute("action", "transfer_from")
+        .add_submessage(SubMsg::reply_on_success(transfer, TRANSFER_FROM_REPLY_ID)))
+}
+
+/// Checks a signed intent and pulls the stake from the player like CreateWagerCw20 and
+/// JoinWagerCw20 do for the sender.
+pub fn execute_relay(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    intent: RelayIntent,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    if RELAYERS.may_load(deps.storage, &info.sender)?.is_none() {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let player = deps.api.addr_validate(&intent.player)?;
+    if intent.expires.is_expired(&env.block) {
+        return Err(ContractError::IntentExpired {});
+    }
+    let nonce = NONCES.may_load(deps.storage, &player)?.unwrap_or_default();
+    if intent.nonce != nonce {
+        return Err(ContractError::InvalidNonce {});
+    }
+    let pubkey = match &intent.pubkey {
+        Some(pubkey) => {
+            if !pubkey_matches_address(pubkey, player.as_str()) {
+                return Err(ContractError::PubkeyMismatch {});
+            }
+            pubkey.clone()
+        }
+        None => RELAY_KEYS
+            .may_load(deps.storage, &player)?
+            .ok_or(ContractError::NoRelayKey {})?,
+    };
+
+    let doc = RelayDoc {
+        chain_id: env.block.chain_id.clone(),
+        contract: env.contract.address.to_string(),
+        intent: intent.clone(),
+    };
+    let hash = Sha256::digest(&to_vec(&doc)?);
+    if !matches!(
+        deps.api.secp256k1_verify(&hash, &signature, &pubkey),
+        Ok(true)
+    ) {
+        return Err(ContractError::InvalidSignature {});
+    }
+    NONCES.save(deps.storage, &player, &(nonce + 1))?;
+
+    let (token, amount, action) = match intent.action {
+        RelayAction::CreateWager {
+            token,
+            amount,
+            label,
+            stake,
+            stake_value,
+            series_length,
+        } => (
+            token,
+            amount,
+            PendingAction::CreateWager {
+                label,
+                stake,
+                stake_value,
+                series_length,
+            },
+        ),
+        RelayAction::JoinWager {
+            wager_id,
+            token,
+            amount,
+        } => (token, amount, PendingAction::JoinWager { wager_id }),
+    };
+    let res = execute_pull_cw20(deps, env, player, token, amount, action)?;
+    Ok(res.add_attribute("relayer", info.sender))
+}
+
+pub fn execute_set_relay_key(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    pubkey: Option<Binary>,
+) -> Result<Response, ContractError> {
+    match &pubkey {
+        Some(pubkey) => RELAY_KEYS.save(deps.storage, &info.sender, pubkey)?,
+        None => RELAY_KEYS.remove(deps.storage, &info.sender),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_relay_key")
+        .add_event(
+            RelayKeyEvent {
+                player: info.sender,
+                pubkey,
+            }
+            .into_event(),
+        ))
+}
+
+pub fn execute_update_relayers(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    require_role(deps.storage, Role::Operator, &info.sender)?;
+    require_no_delay(deps.storage)?;
+    let event = update_relayers(deps, add, remove)?;
+    Ok(Response::new()
+        .add_attribute("action", "update_relayers")
+        .add_event(event))
+}
+
+fn update_relayers(
+    deps: DepsMut,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Event, ContractError> {
+    let added = add
+        .iter()
+        .map(|relayer| deps.api.addr_validate(relayer))
+        .collect::<StdResult<Vec<_>>>()?;
+    let removed = remove
+        .iter()
+        .map(|relayer| deps.api.addr_validate(relayer))
+        .collect::<StdResult<Vec<_>>>()?;
+    for relayer in &added {
+        RELAYERS.save(deps.storage, relayer, &Empty {})?;
+    }
+    for relayer in &removed {
+        RELAYERS.remove(deps.storage, relayer);
+    }
+
+    Ok(RelayersEvent { added, removed }.into_event()?)
+}
+
+pub fn execute_update_blocklist(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    require_role(deps.storage, Role::Operator, &info.sender)?;
+    require_no_delay(deps.storage)?;
+    let event = update_blocklist(deps, add, remove)?;
+    Ok(Response::new()
+        .add_attribute("action", "update_blocklist")
+        .add_event(event))
+}
+
+fn update_blocklist(
+    deps: DepsMut,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Event, ContractError> {
+    let added = add
+        .iter()
+        .map(|address| deps.api.addr_validate(address))
+        .collect::<StdResult<Vec<_>>>()?;
+    let removed = remove
+        .iter()
+        .map(|address| deps.api.addr_validate(address))
+        .collect::<StdResult<Vec<_>>>()?;
+    for address in &added {
+        BLOCKLIST.save(deps.storage, address, &Empty {})?;
+    }
+    for address in &removed {
+        BLOCKLIST.remove(deps.storage, address);
+    }
+
+    Ok(BlocklistEvent { added, removed }.into_event()?)
+}
+
+pub fn execute_force_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wager_id: u64,
+) -> Result<Response, ContractError> {
+    require_role(deps.storage, Role::Operator, &info.sender)?;
+    let wager = get_wager(&deps, wager_id)?;
+    let user1_blocked = BLOCKLIST.has(deps.storage, &wager.user1);
+    let user2_blocked = BLOCKLIST.has(deps.storage, &wager.user2);
+    if !user1_blocked && !user2_blocked {
+        return Err(ContractError::NotBlocked {});
+    }
+
+    // under the forfeit policy the opponent of a blocked player wins a matched wager
+    let state = config(deps.storage).load()?;
+    let matched = matches!(wager.status, WagerStatus::Matched | WagerStatus::Disputed);
+    if matched && state.blocked_policy == BlockedPolicy::Forfeit && user1_blocked != user2_blocked {
+        let (winner, blocked) = if user1_blocked {
+            (wager.user2.clone(), wager.user1.clone())
+        } else {
+            (wager.user1.clone(), wager.user2.clone())
+        };
+        return settle_wager(
+            deps,
+            &env,
+            wager_id,
+            &wager,
+            winner,
+            Some(blocked),
+            "force_cancel",
+        );
+    }
+
+    refund_ended_wager(
+        deps.storage,
+        wager_id,
+        &wager,
+        WagerEventKind::WagerCancelled,
+        "force_cancel",
+    )
+}
+
+pub fn execute_grant_session(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    key: String,
+    expires: Expiration,
+    max_stake: Option<GenericBalance>,
+) -> Result<Response, ContractError> {
+    let key = deps.api.addr_validate(&key)?;
+    if key == info.sender || expires.is_expired(&env.block) {
+        return Err(ContractError::InvalidSession {});
+    }
+    // a key plays for one player only, grants it did not accept can be replaced
+    let accepted = match SESSIONS.may_load(deps.storage, &key)? {
+        Some(existing) if existing.accepted && existing.player != info.sender => {
+            return Err(ContractError::InvalidSession {});
+        }
+        Some(existing) => existing.accepted && existing.player == info.sender,
+        None => false,
+    };
+
+    let session = Session {
+        player: info.sender,
+        expires,
+        max_stake: max_stake.map(|mut max_stake| {
+            max_stake.normalize();
+            max_stake
+        }),
+        accepted,
+    };
+    SESSIONS.save(deps.storage, &key, &session)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "grant_session")
+        .add_event(
+            SessionEvent::new(SessionEventKind::SessionGranted, &key, &session).into_event()?,
+        ))
+}
+
+pub fn execute_accept_session(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    player: String,
+) -> Result<Response, ContractError> {
+    let player = deps.api.addr_validate(&player)?;
+    let mut session = match SESSIONS.may_load(deps.storage, &info.sender)? {
+        Some(session) if session.player == player => session,
+        _ => return Err(ContractError::Unauthorized {}),
+    };
+    if session.expires.is_expired(&env.block) {
+        return Err(ContractError::SessionExpired {});
+    }
+    session.accepted = true;
+    SESSIONS.save(deps.storage, &info.sender, &session)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_session")
+        .add_event(
+            SessionEvent::new(SessionEventKind::SessionAccepted, &info.sender, &session)
+                .into_event()?,
+        ))
+}
+
+pub fn execute_revoke_session(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    key: String,
+) -> Result<Response, ContractError> {
+    let key = deps.api.addr_validate(&key)?;
+    let session = match SESSIONS.may_load(deps.storage, &key)? {
+        Some(session) if session.player == info.sender || key == info.sender => session,
+        _ => return Err(ContractError::Unauthorized {}),
+    };
+    SESSIONS.remove(deps.storage, &key);
+
+    Ok(Response::new()
+        .add_attribute("action", "revoke_session")
+        .add_event(
+            SessionEvent::new(SessionEventKind::SessionRevoked, &key, &session).into_event()?,
+        ))
+}
+
+/// Returns the player a sender acts for: the player whose session key the sender accepted, or
+/// the sender itself. A stake put up through a session key must be within its limit.
+fn acting_player(
+    deps: Deps,
+    env: &Env,
+    sender: Addr,
+    stake: Option<&GenericBalance>,
+) -> Result<Addr, ContractError> {
+    let session = match SESSIONS.may_load(deps.storage, &sender)? {
+        Some(session) if session.accepted => session,
+        _ => return Ok(sender),
+    };
+    if session.expires.is_expired(&env.block) {
+        return Err(ContractError::SessionExpired {});
+    }
+    if let (Some(max_stake), Some(stake)) = (&session.max_stake, stake) {
+        if !max_stake.covers(stake) {
+            return Err(ContractError::SessionLimitExceeded {});
+        }
+    }
+    Ok(session.player)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        TRANSFER_FROM_REPLY_ID => reply_transfer_from(deps, env),
+        id => Err(ContractError::Std(StdError::generic_err(format!(
+            "Unknown reply id: {}",
+            id
+        )))),
+    }
+}
+
+fn reply_transfer_from(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let pending = PENDING_TRANSFER.load(deps.storage)?;
+    PENDING_TRANSFER.remove(deps.storage);
+
+    let held = query_held(
+        deps.as_ref(),
+        &env.contract.address,
+        &Denom::Cw20(pending.token.clone()),
+    )?;
+    // tokens taking a fee on transfer, or not moving at all, are refused
+    if held.checked_sub(pending.held_before).ok() != Some(pending.amount) {
+        return Err(ContractError::TransferNotReceived {});
+    }
+
+    let balance = Balance::Cw20(Cw20CoinVerified {
+        address: pending.token,
+        amount: pending.amount,
+    });
+    match pending.action {
+        PendingAction::CreateWager {
+            label,
+            stake,
+            stake_value,
+            series_length,
+        } => execute_create_wager(
+            deps,
+            env,
+            pending.sender,
+            balance,
+            label,
+            None,
+            stake,
+            stake_value,
+            series_length,
+        ),
+        PendingAction::JoinWager { wager_id } => {
+            execute_add_funds(deps, env, pending.sender, balance, wager_id)
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Wager { id } => to_binary(&query_wager_for_id(id, deps)?),
+        QueryMsg::Rounds { wager_id } => to_binary(&query_rounds(wager_id, deps)?),
+        QueryMsg::Roles { role, game_id } => to_binary(&query_roles(role, game_id, deps)?),
+        QueryMsg::Votes { wager_id } => to_binary(&query_votes(wager_id, deps)?),
+        QueryMsg::Rematch { settled_wager_id } => {
+            to_binary(&query_rematch(settled_wager_id, deps)?)
+        }
+        QueryMsg::ConfigChanges {} => to_binary(&query_config_changes(deps)?),
+        QueryMsg::PlayerLimits { player } => to_binary(&query_player_limits(player, deps, env)?),
+        QueryMsg::SidePool { wager_id } => to_binary(&query_side_pool(wager_id, deps)?),
+        QueryMsg::SideBet { wager_id, bettor } => {
+            to_binary(&query_side_bet(wager_id, bettor, deps)?)
+        }
+        QueryMsg::Queue { game_id } => to_binary(&query_queue(game_id, deps)?),
+        QueryMsg::Blocked { address } => to_binary(&query_blocked(address, deps)?),
+        QueryMsg::Rating { player } => to_binary(&query_rating(player, deps)?),
+        QueryMsg::Solvency {} => to_binary(&query_solvency(deps, env)?),
+        QueryMsg::Nonce { player } => to_binary(&query_nonce(player, deps)?),
+        QueryMsg::Session { key } => to_binary(&query_session(key, deps)?),
+        QueryMsg::ReferralStats { referrer } => to_binary(&query_referral_stats(referrer, deps)?),
+        QueryMsg::PlayerStats { player } => to_binary(&query_player_stats(player, deps)?),
+        QueryMsg::Claimable { address } => to_binary(&query_claimable(address, deps)?),
+        QueryMsg::Jackpot { limit } => to_binary(&query_jackpot(limit, deps)?),
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<State> {
+    let state = config_read(deps.storage).load()?;
+    Ok(state)
+}
+
+fn query_wager_for_id(id: u64, deps: Deps) -> StdResult<Wager> {
+    let wager = WAGERS.load(deps.storage, id.into())?;
+    Ok(wager)
+}
+
+fn query_rounds(wager_id: u64, deps: Deps) -> StdResult<RoundsResponse> {
+    let wager = WAGERS.load(deps.storage, wager_id.into())?;
+    let series = wager
+        .series
+        .ok_or_else(|| StdError::generic_err("Wager is not a series"))?;
+    let (user1_wins, user2_wins) = series.wins(&wager.user1);
+    Ok(RoundsResponse {
+        series_length: series.length,
+        rounds: series.rounds,
+        user1_wins,
+        user2_wins,
+    })
+}
+
+fn query_rematch(settled_wager_id: u64, deps: Deps) -> StdResult<Rematch> {
+    REMATCHES.load(deps.storage, settled_wager_id.into())
+}
+
+fn query_votes(wager_id: u64, deps: Deps) -> StdResult<VotesResponse> {
+    let votes = VOTES
+        .may_load(deps.storage, wager_id.into())?
+        .unwrap_or_default();
+    let state = config_read(deps.storage).load()?;
+    Ok(VotesResponse {
+        votes,
+        required: state.required_approvals.max(1),
+    })
+}
+
+fn query_config_changes(deps: Deps) -> StdResult<ConfigChangesResponse> {
+    let changes = CONFIG_CHANGES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, change)| change))
+        .collect::<StdResult<_>>()?;
+    Ok(ConfigChangesResponse { changes })
+}
+
+fn query_player_limits(player: String, deps: Deps, env: Env) -> StdResult<PlayerLimitsResponse> {
+    let player = deps.api.addr_validate(&player)?;
+    let mut limits = PLAYER_LIMITS
+        .may_load(deps.storage, &player)?
+        .unwrap_or_default();
+    limits.refresh(env.block.time);
+    let history = STAKE_HISTORY
+        .may_load(deps.storage, &player)?
+        .unwrap_or_default();
+    let today = env.block.time.seconds() / SECONDS_PER_DAY;
+    let [daily, weekly, monthly] = limits
+        .limits
+        .windows()
+        .map(|(days, _)| staked_since(&history, today, days));
+    Ok(PlayerLimitsResponse {
+        limits,
+        daily_staked: daily,
+        weekly_staked: weekly,
+        monthly_staked: monthly,
+    })
+}
+
+fn query_roles(role: Role, game_id: Option<String>, deps: Deps) -> StdResult<RolesResponse> {
+    let holders = match &game_id {
+        Some(game_id) if role == Role::Arbiter => GAME_ARBITERS.prefix(game_id),
+        Some(_) => return Err(StdError::generic_err("only arbiters are scoped to games")),
+        None => ROLES.prefix(role.as_str()),
+    };
+    let addresses = holders
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|address| String::from_utf8(address).map(Addr::unchecked))
+        .collect::<Result<Vec<Addr>, _>>()?;
+    Ok(RolesResponse {
+        role,
+        game_id,
+        addresses,
+    })
+}
+
+fn query_side_pool(wager_id: u64, deps: Deps) -> StdResult<SidePool> {
+    SIDE_POOLS.load(deps.storage, wager_id.into())
+}
+
+fn query_side_bet(wager_id: u64, bettor: String, deps: Deps) -> StdResult<SideBet> {
+    let bettor = deps.api.addr_validate(&bettor)?;
+    Ok(SIDE_BETS
+        .may_load(deps.storage, (wager_id.into(), &bettor))?
+        .unwrap_or_default())
+}
+
+fn query_queue(game_id: String, deps: Deps) -> StdResult<QueueResponse> {
+    let mut entries = QUEUE
+        .prefix(&game_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, entry)| entry))
+        .collect::<StdResult<Vec<QueueEntry>>>()?;
+    entries.sort_by_key(|entry| entry.seq);
+    Ok(QueueResponse { entries })
+}
+
+fn query_blocked(address: String, deps: Deps) -> StdResult<BlockedResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(BlockedResponse {
+        blocked: BLOCKLIST.has(deps.storage, &address),
+        address,
+    })
+}
+
+fn query_rating(player: String, deps: Deps) -> StdResult<RatingResponse> {
+    let player = deps.api.addr_validate(&player)?;
+    let rating = RATINGS
+        .may_load(deps.storage, &player)?
+        .unwrap_or(DEFAULT_RATING);
+    Ok(RatingResponse { player, rating })
+}
+
+fn query_player_stats(player: String, deps: Deps) -> StdResult<PlayerStatsResponse> {
+    let player = deps.api.addr_validate(&player)?;
+    let stats = PLAYER_STATS
+        .may_load(deps.storage, &player)?
+        .unwrap_or_default();
+    Ok(PlayerStatsResponse {
+        player,
+        wins: stats.wins,
+        losses: stats.losses,
+        forfeits: stats.forfeits,
+    })
+}
+
+fn query_nonce(player: String, deps: Deps) -> StdResult<NonceResponse> {
+    let player = deps.api.addr_validate(&player)?;
+    let nonce = NONCES.may_load(deps.storage, &player)?.unwrap_or_default();
+    Ok(NonceResponse { player, nonce })
+}
+
+fn query_session(key: String, deps: Deps) -> StdResult<SessionResponse> {
+    let key = deps.api.addr_validate(&key)?;
+    let session = SESSIONS.may_load(deps.storage, &key)?;
+    Ok(SessionResponse { key, session })
+}
+
+fn query_referral_stats(referrer: String, deps: Deps) -> StdResult<ReferralStatsResponse> {
+    let referrer = deps.api.addr_validate(&referrer)?;
+    let players = REFERRED
+        .prefix(&referrer)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|player| String::from_utf8(player).map(Addr::unchecked))
+        .collect::<Result<Vec<Addr>, _>>()?;
+    let stats = REFERRAL_STATS
+        .may_load(deps.storage, &referrer)?
+        .unwrap_or_default();
+    Ok(ReferralStatsResponse {
+        referrer,
+        players,
+        volume: stats.volume,
+        earnings: stats.earnings,
+    })
+}
+
+fn query_claimable(address: String, deps: Deps) -> StdResult<ClaimableResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let claimable = CLAIMABLE
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    Ok(ClaimableResponse { address, claimable })
+}
+
+const DEFAULT_JACKPOT_LIMIT: u32 = 10;
+const MAX_JACKPOT_LIMIT: u32 = 30;
+
+fn query_jackpot(limit: Option<u32>, deps: Deps) -> StdResult<JackpotResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_JACKPOT_LIMIT)
+        .min(MAX_JACKPOT_LIMIT) as usize;
+    let jackpot = JACKPOT.may_load(deps.storage)?.unwrap_or_default();
+    let wins = JACKPOT_WINS
+        .range(deps.storage, None, None, Order::Descending)
+        .take(limit)
+        .map(|item| item.map(|(_, win)| win))
+        .collect::<StdResult<Vec<JackpotWin>>>()?;
+    Ok(JackpotResponse { jackpot, wins })
+}
+
+/// Values a balance in the oracle's reference unit
+fn query_value(deps: Deps, oracle: &Addr, balance: &GenericBalance) -> StdResult<Uint128> {
+    let assets = balance
+        .native
+        .iter()
+        .map(|token| (Denom::Native(token.denom.clone()), token.amount))
+        .chain(
+            balance
+                .cw20
+                .iter()
+                .map(|token| (Denom::Cw20(token.address.clone()), token.amount)),
+        );
+
+    let mut value = Uint128::zero();
+    for (asset, amount) in assets {
+        let price: PriceResponse = deps
+            .querier
+            .query_wasm_smart(oracle, &OracleQueryMsg::Price { asset })?;
+        // an amount at a high price may not fit, which fails the valuation instead of panicking
+        let asset_value =
+            amount.full_mul(price.price.numerator()) / Uint256::from(price.price.denominator());
+        let asset_value = Uint128::try_from(asset_value).map_err(|_| {
+            StdError::overflow(OverflowError::new(
+                OverflowOperation::Mul,
+                amount,
+                price.price,
+            ))
+        })?;
+        value = value.checked_add(asset_value)?;
+    }
+    Ok(value)
+}
+
+fn query_held(deps: Deps, contract: &Addr, asset: &Denom) -> StdResult<Uint128> {
+    match asset {
+        Denom::Native(denom) => Ok(deps.querier.query_balance(contract, denom)?.amount),
+        Denom::Cw20(address) => {
+            let held: Cw20BalanceResponse = deps.querier.query_wasm_smart(
+                address,
+                &Cw20QueryMsg::Balance {
+                    address: contract.to_string(),
+                },
+            )?;
+            Ok(held.balance)
+        }
+    }
+}
+
+fn query_solvency(deps: Deps, env: Env) -> StdResult<SolvencyResponse> {
+    let escrowed = TOTAL_ESCROWED.may_load(deps.storage)?.unwrap_or_default();
+    let contract = env.contract.address;
+
+    let mut assets: Vec<AssetSolvency> = deps
+        .querier
+        .query_all_balances(&contract)?
+        .into_iter()
+        .map(|held| AssetSolvency {
+            asset: Denom::Native(held.denom),
+            escrowed: Uint128::zero(),
+            held: held.amount,
+        })
+        .collect();
+
+    for token in escrowed.native {
+        match assets
+            .iter_mut()
+            .find(|asset| asset.asset == Denom::Native(token.denom.clone()))
+        {
+            Some(asset) => asset.escrowed = token.amount,
+            None => assets.push(AssetSolvency {
+                asset: Denom::Native(token.denom),
+                escrowed: token.amount,
+                held: Uint128::zero(),
+            }),
+        }
+    }
+
+    for token in escrowed.cw20 {
+        let asset = Denom::Cw20(token.address);
+        assets.push(AssetSolvency {
+            held: query_held(deps, &contract, &asset)?,
+            asset,
+            escrowed: token.amount,
+        });
+    }
+
+    Ok(SolvencyResponse {
+        solvent: assets.iter().all(|asset| asset.held >= asset.escrowed),
+        assets,
+    })
 }
@@ -1,6 +1,8 @@
 use cosmwasm_std::StdError;
 use thiserror::Error;
 
+use crate::state::Role;
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
@@ -9,6 +11,69 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Unauthorized: requires the {role} role")]
+    MissingRole { role: Role },
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Stake is above the approval threshold, arbiters must vote for the result")]
+    ApprovalsRequired {},
+
+    #[error("Approval threshold needs at least one approval")]
+    InvalidApprovals {},
+
+    #[error("Arbiter already voted on this wager")]
+    AlreadyVoted {},
+
+    #[error("Wager is disputed, only an owner can settle it")]
+    Disputed {},
+
+    #[error("Owners cannot revoke their own owner role")]
+    OwnOwnerRole {},
+
+    #[error("Only the arbiter role can be scoped to a game")]
+    GameScopedRole {},
+
+    #[error("Configuration changes must be proposed while a config delay is set")]
+    Timelocked {},
+
+    #[error("Configuration change cannot be executed before its eta")]
+    ChangeNotReady {},
+
+    #[error("Address is blocked")]
+    Blocked {},
+
+    #[error("Neither player of the wager is blocked")]
+    NotBlocked {},
+
+    #[error("Wager is not matched")]
+    NotMatched {},
+
+    #[error("The other player has not proposed to cancel the wager")]
+    NoCancelProposal {},
+
+    #[error("Wager is not a king-of-the-hill wager")]
+    NotHill {},
+
+    #[error("Champion can only cash out between matches")]
+    MatchInProgress {},
+
+    #[error("Wager cannot be rematched")]
+    NoRematch {},
+
+    #[error("Rematch expired")]
+    RematchExpired {},
+
+    #[error("Claimable tokens do not cover the stake")]
+    InsufficientClaimable {},
+
+    #[error("Player is self-excluded")]
+    SelfExcluded {},
+
+    #[error("Stake is above the player's stake limits")]
+    StakeLimitExceeded {},
+
     #[error("Only accepts tokens in the cw20_whitelist")]
     NotInWhitelist {},
 
@@ -35,4 +100,121 @@ pub enum ContractError {
 
     #[error("Data should be given")]
     DataShouldBeGiven {},
+
+    #[error("Label must be between 3 and 20 bytes")]
+    InvalidLabel {},
+
+    #[error("Funds sent do not match the stake")]
+    InvalidStake {},
+
+    #[error("Player is already in the queue for this game")]
+    AlreadyQueued {},
+
+    #[error("Player is not in the queue for this game")]
+    NotInQueue {},
+
+    #[error("Deposit exceeds the stake of the wager")]
+    StakeExceeded {},
+
+    #[error("A stake can be given in tokens or as a value, not both")]
+    ConflictingStake {},
+
+    #[error("Valued stakes need a price oracle")]
+    NoOracle {},
+
+    #[error("Tolerance must be at most 10000 basis points")]
+    InvalidTolerance {},
+
+    #[error("Self-exclusion must last at most 5 years")]
+    InvalidExclusion {},
+
+    #[error("Config delay must be at most 30 days")]
+    InvalidConfigDelay {},
+
+    #[error("Contract did not receive the amount pulled with TransferFrom")]
+    TransferNotReceived {},
+
+    #[error("Intent has expired")]
+    IntentExpired {},
+
+    #[error("Intent nonce does not match the next nonce of the player")]
+    InvalidNonce {},
+
+    #[error("Player has not registered a relay key")]
+    NoRelayKey {},
+
+    #[error("Signature does not match the intent")]
+    InvalidSignature {},
+
+    #[error("Public key does not derive to the player address")]
+    PubkeyMismatch {},
+
+    #[error("Session key must be another address, unexpired and not granted by another player")]
+    InvalidSession {},
+
+    #[error("Session key has expired")]
+    SessionExpired {},
+
+    #[error("Stake exceeds the limit of the session key")]
+    SessionLimitExceeded {},
+
+    #[error("Fee and referral share must be at most 10000 basis points")]
+    InvalidFee {},
+
+    #[error("A player cannot refer themselves")]
+    InvalidReferrer {},
+
+    #[error("Referrer is already set")]
+    ReferrerAlreadySet {},
+
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+
+    #[error("No jackpot draw is pending for this wager")]
+    NoPendingDraw {},
+
+    #[error("Secret does not match the commitment")]
+    InvalidReveal {},
+
+    #[error("Contract holds nothing above the escrowed amount of this asset")]
+    NothingToSweep {},
+
+    #[error("Reveal period must be between one block and about a week of blocks")]
+    InvalidRevealPeriod {},
+
+    #[error("Wager is not a game of chance")]
+    NotChanceGame {},
+
+    #[error("Games of chance are joined with a commitment")]
+    CommitmentRequired {},
+
+    #[error("Choice is not valid for this game")]
+    InvalidChoice {},
+
+    #[error("Choice already revealed")]
+    AlreadyRevealed {},
+
+    #[error("Reveal deadline has passed")]
+    RevealPeriodOver {},
+
+    #[error("Reveal deadline has not passed")]
+    RevealPeriodOpen {},
+
+    #[error("Series must have an odd number of rounds")]
+    InvalidSeriesLength {},
+
+    #[error("Wager is not a series")]
+    NotSeries {},
+
+    #[error("Round must be the next round of the series")]
+    InvalidRound {},
+
+    #[error("Side bets are closed for this wager")]
+    SideBetsClosed {},
+
+    #[error("Side bets must be made in the single asset of the pool")]
+    InvalidSideBet {},
+
+    #[error("Side bets are paid once the wager ends")]
+    SideBetsPending {},
 }
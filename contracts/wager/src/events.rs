@@ -0,0 +1,941 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_vec, Addr, Binary, Event, StdError, StdResult, Uint128};
+use cw20::{Balance, Denom, Expiration};
+
+use crate::state::{
+    BlockedPolicy, ChanceGameKind, ConfigChange, GenericBalance, PlayerLimits, RandomnessSource,
+    Role, Session, Side, SidePool, SidePoolStatus, State, Wager, WagerStatus,
+};
+
+/// Version of the event schema, bumped whenever an event attribute is added, renamed or removed.
+/// Every event carries it in the `schema_version` attribute.
+pub const EVENT_SCHEMA_VERSION: &str = "13";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WagerEventKind {
+    /// A wager was created, by a player or by the matchmaking queue
+    WagerCreated,
+    /// Part of a basket stake was deposited without matching the wager
+    WagerDeposited,
+    /// A second player funded the wager, which is now matched
+    WagerJoined,
+    /// The wager was cancelled and the stakes refunded, by agreement once matched
+    WagerCancelled,
+    /// The pot was sent to the winner, or refunded after a drawn game of chance
+    WagerSettled,
+    /// The wager expired and the stakes were refunded, such as a game of chance nobody revealed
+    WagerExpired,
+    /// Arbiters voted for different winners
+    WagerDisputed,
+    /// The champion of a king-of-the-hill wager took their stake between matches
+    WagerCashedOut,
+    /// A player of a matched wager proposed to cancel it
+    WagerCancelProposed,
+    /// The player who proposed to cancel a wager withdrew the proposal
+    WagerCancelRetracted,
+    /// The arbiter voided a matched wager whose game never started and refunded the stakes
+    WagerVoided,
+    /// A player asked to keep their winnings as claimable tokens for a rematch
+    WagerRematchRequested,
+}
+
+impl WagerEventKind {
+    /// Event type, wasmd prefixes it with `wasm-`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WagerEventKind::WagerCreated => "wager_created",
+            WagerEventKind::WagerDeposited => "wager_deposited",
+            WagerEventKind::WagerJoined => "wager_joined",
+            WagerEventKind::WagerCancelled => "wager_cancelled",
+            WagerEventKind::WagerSettled => "wager_settled",
+            WagerEventKind::WagerExpired => "wager_expired",
+            WagerEventKind::WagerDisputed => "wager_disputed",
+            WagerEventKind::WagerCashedOut => "wager_cashed_out",
+            WagerEventKind::WagerCancelProposed => "wager_cancel_proposed",
+            WagerEventKind::WagerCancelRetracted => "wager_cancel_retracted",
+            WagerEventKind::WagerVoided => "wager_voided",
+            WagerEventKind::WagerRematchRequested => "wager_rematch_requested",
+        }
+    }
+}
+
+/// Canonical event emitted by every handler that changes a wager. Each field is emitted as an
+/// attribute of the same name, balances are JSON encoded and unset fields are left out.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WagerEvent {
+    /// event type
+    pub kind: WagerEventKind,
+    /// id of the wager
+    pub wager_id: u64,
+    /// game the wager is played in, if known
+    pub game_id: Option<String>,
+    /// arbiter allowed to settle the wager
+    pub arbiter: Addr,
+    /// creator of the wager
+    pub user1: Addr,
+    /// player that joined the wager, if any
+    pub user2: Option<Addr>,
+    /// only player allowed to join the wager, if any
+    pub opponent: Option<Addr>,
+    /// stake of the creator in native and cw20 tokens
+    pub user1_balance: GenericBalance,
+    /// stake of the player that joined in native and cw20 tokens
+    pub user2_balance: GenericBalance,
+    /// status of the wager once the handler is done
+    pub status: WagerStatus,
+    /// winner of a settled wager
+    pub winner: Option<Addr>,
+    /// platform fee taken from a settled pot, referral rewards included
+    pub fee: Option<GenericBalance>,
+    /// matches in a row won by the champion of a king-of-the-hill wager
+    pub streak: Option<u32>,
+    /// kind of a game of chance
+    pub chance_game: Option<ChanceGameKind>,
+    /// player who conceded a settled wager to their opponent, if any
+    pub forfeited_by: Option<Addr>,
+}
+
+impl WagerEvent {
+    pub fn new(kind: WagerEventKind, wager_id: u64, wager: &Wager) -> Self {
+        WagerEvent {
+            kind,
+            wager_id,
+            game_id: wager.game_id.clone(),
+            arbiter: wager.arbiter.clone(),
+            user1: wager.user1.clone(),
+            user2: if wager.user2 == "empty" {
+                None
+            } else {
+                Some(wager.user2.clone())
+            },
+            opponent: wager.opponent.clone(),
+            user1_balance: wager.user1_balance.clone(),
+            user2_balance: wager.user2_balance.clone(),
+            status: wager.status.clone(),
+            winner: None,
+            fee: None,
+            streak: wager.hill.as_ref().map(|hill| hill.streak),
+            chance_game: wager.chance.as_ref().map(|chance| chance.kind.clone()),
+            forfeited_by: None,
+        }
+    }
+
+    pub fn status(mut self, status: WagerStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn winner(mut self, winner: Addr) -> Self {
+        self.winner = Some(winner);
+        self
+    }
+
+    pub fn fee(mut self, fee: GenericBalance) -> Self {
+        self.fee = Some(fee);
+        self
+    }
+
+    pub fn streak(mut self, streak: u32) -> Self {
+        self.streak = Some(streak);
+        self
+    }
+
+    pub fn forfeited_by(mut self, player: Addr) -> Self {
+        self.forfeited_by = Some(player);
+        self
+    }
+
+    pub fn into_event(self) -> StdResult<Event> {
+        let mut event = Event::new(self.kind.as_str())
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("wager_id", self.wager_id.to_string());
+        if let Some(game_id) = self.game_id {
+            event = event.add_attribute("game_id", game_id);
+        }
+        event = event
+            .add_attribute("arbiter", self.arbiter)
+            .add_attribute("user1", self.user1);
+        if let Some(user2) = self.user2 {
+            event = event.add_attribute("user2", user2);
+        }
+        if let Some(opponent) = self.opponent {
+            event = event.add_attribute("opponent", opponent);
+        }
+        event = event
+            .add_attribute("user1_balance", to_json_string(&self.user1_balance)?)
+            .add_attribute("user2_balance", to_json_string(&self.user2_balance)?)
+            .add_attribute("status", self.status.to_string());
+        if let Some(winner) = self.winner {
+            event = event.add_attribute("winner", winner);
+        }
+        if let Some(fee) = self.fee {
+            event = event.add_attribute("fee", to_json_string(&fee)?);
+        }
+        if let Some(streak) = self.streak {
+            event = event.add_attribute("streak", streak.to_string());
+        }
+        if let Some(chance_game) = self.chance_game {
+            event = event.add_attribute("chance_game", chance_game.as_str());
+        }
+        if let Some(forfeited_by) = self.forfeited_by {
+            event = event.add_attribute("forfeited_by", forfeited_by);
+        }
+        Ok(event)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueEventKind {
+    /// A player started waiting in the matchmaking queue
+    QueueJoined,
+    /// A player left the matchmaking queue and was refunded
+    QueueLeft,
+}
+
+impl QueueEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueueEventKind::QueueJoined => "queue_joined",
+            QueueEventKind::QueueLeft => "queue_left",
+        }
+    }
+}
+
+/// Canonical event emitted when the matchmaking queue changes without creating a wager.
+/// A queue join that finds an opponent emits `wager_created` followed by `wager_joined`
+/// instead.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QueueEvent {
+    /// event type
+    pub kind: QueueEventKind,
+    /// game of the queue
+    pub game_id: String,
+    /// player joining or leaving
+    pub player: Addr,
+    /// stake escrowed while waiting
+    pub stake: GenericBalance,
+}
+
+impl QueueEvent {
+    pub fn new(kind: QueueEventKind, game_id: &str, player: &Addr, stake: &Balance) -> Self {
+        QueueEvent {
+            kind,
+            game_id: game_id.to_string(),
+            player: player.clone(),
+            stake: GenericBalance::from(stake.clone()),
+        }
+    }
+
+    pub fn into_event(self) -> StdResult<Event> {
+        Ok(Event::new(self.kind.as_str())
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("game_id", self.game_id)
+            .add_attribute("player", self.player)
+            .add_attribute("stake", to_json_string(&self.stake)?))
+    }
+}
+
+/// Canonical event emitted when an operator rates a player for matchmaking.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RatingEvent {
+    /// player that was rated
+    pub player: Addr,
+    /// new rating of the player
+    pub rating: u32,
+}
+
+impl RatingEvent {
+    pub fn into_event(self) -> Event {
+        Event::new("rating_set")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("player", self.player)
+            .add_attribute("rating", self.rating.to_string())
+    }
+}
+
+/// Canonical event emitted when the owner sweeps tokens that are not escrowed for players.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SweepEvent {
+    /// asset that was swept
+    pub asset: Denom,
+    /// amount sent to the recipient
+    pub amount: Uint128,
+    /// receiver of the swept tokens
+    pub recipient: Addr,
+}
+
+impl SweepEvent {
+    pub fn into_event(self) -> StdResult<Event> {
+        Ok(Event::new("swept")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("asset", to_json_string(&self.asset)?)
+            .add_attribute("amount", self.amount)
+            .add_attribute("recipient", self.recipient))
+    }
+}
+
+/// Canonical event emitted when the owner updates the configuration.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigEvent {
+    /// price oracle valuing stakes, if any
+    pub oracle: Option<Addr>,
+    /// tolerance of valued stakes in basis points
+    pub price_tolerance_bps: u64,
+    /// platform fee in basis points
+    pub fee_bps: u64,
+    /// referrer share of the fee in basis points
+    pub referral_share_bps: u64,
+    /// jackpot share of the owner's fee in basis points
+    pub jackpot_share_bps: u64,
+    /// one in this many settled wagers win the jackpot
+    pub jackpot_odds: u64,
+    /// source of the randomness of jackpot draws
+    pub randomness: Option<RandomnessSource>,
+    /// stake from which wagers are settled by arbiter votes, empty if disabled
+    pub approval_threshold: GenericBalance,
+    /// arbiter votes needed to settle such a wager
+    pub required_approvals: u32,
+    /// seconds proposed changes wait before they can be executed
+    pub config_delay: u64,
+    /// outcome of force-cancelled matched wagers of blocked players
+    pub blocked_policy: BlockedPolicy,
+    /// id of the executed configuration change, none for UpdateConfig
+    pub change_id: Option<u64>,
+}
+
+impl From<&State> for ConfigEvent {
+    fn from(state: &State) -> Self {
+        ConfigEvent {
+            oracle: state.oracle.clone(),
+            price_tolerance_bps: state.price_tolerance_bps,
+            fee_bps: state.fee_bps,
+            referral_share_bps: state.referral_share_bps,
+            jackpot_share_bps: state.jackpot_share_bps,
+            jackpot_odds: state.jackpot_odds,
+            randomness: state.randomness.clone(),
+            approval_threshold: state.approval_threshold.clone(),
+            required_approvals: state.required_approvals,
+            config_delay: state.config_delay,
+            blocked_policy: state.blocked_policy.clone(),
+            change_id: None,
+        }
+    }
+}
+
+impl ConfigEvent {
+    pub fn into_event(self) -> StdResult<Event> {
+        let mut event =
+            Event::new("config_updated").add_attribute("schema_version", EVENT_SCHEMA_VERSION);
+        if let Some(oracle) = self.oracle {
+            event = event.add_attribute("oracle", oracle);
+        }
+        event = event
+            .add_attribute("price_tolerance_bps", self.price_tolerance_bps.to_string())
+            .add_attribute("fee_bps", self.fee_bps.to_string())
+            .add_attribute("referral_share_bps", self.referral_share_bps.to_string())
+            .add_attribute("jackpot_share_bps", self.jackpot_share_bps.to_string())
+            .add_attribute("jackpot_odds", self.jackpot_odds.to_string());
+        if let Some(randomness) = self.randomness {
+            event = event.add_attribute("randomness", to_json_string(&randomness)?);
+        }
+        event = event
+            .add_attribute(
+                "approval_threshold",
+                to_json_string(&self.approval_threshold)?,
+            )
+            .add_attribute("required_approvals", self.required_approvals.to_string())
+            .add_attribute("config_delay", self.config_delay.to_string())
+            .add_attribute("blocked_policy", self.blocked_policy.as_str());
+        if let Some(change_id) = self.change_id {
+            event = event.add_attribute("change_id", change_id.to_string());
+        }
+        Ok(event)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigChangeEventKind {
+    /// An owner or operator queued a timelocked change
+    ConfigChangeProposed,
+    /// An owner or operator dropped a queued change
+    ConfigChangeCancelled,
+}
+
+impl ConfigChangeEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfigChangeEventKind::ConfigChangeProposed => "config_change_proposed",
+            ConfigChangeEventKind::ConfigChangeCancelled => "config_change_cancelled",
+        }
+    }
+}
+
+/// Canonical event emitted when a timelocked change is proposed or cancelled. Executed changes
+/// emit the event of the update they make, such as `config_updated`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigChangeEvent {
+    /// event type
+    pub kind: ConfigChangeEventKind,
+    /// id of the change
+    pub change_id: u64,
+    /// block time in seconds from which the change can be executed
+    pub eta: u64,
+    /// update the change makes, as JSON
+    pub update: String,
+}
+
+impl ConfigChangeEvent {
+    pub fn new(kind: ConfigChangeEventKind, change: &ConfigChange) -> StdResult<Self> {
+        Ok(ConfigChangeEvent {
+            kind,
+            change_id: change.id,
+            eta: change.eta.seconds(),
+            update: to_json_string(&change.update)?,
+        })
+    }
+
+    pub fn into_event(self) -> Event {
+        Event::new(self.kind.as_str())
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("change_id", self.change_id.to_string())
+            .add_attribute("eta", self.eta.to_string())
+            .add_attribute("update", self.update)
+    }
+}
+
+/// Canonical event emitted when a player registers or removes the key of relayed intents.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RelayKeyEvent {
+    /// player owning the key
+    pub player: Addr,
+    /// new public key, none if relaying was disabled
+    pub pubkey: Option<Binary>,
+}
+
+impl RelayKeyEvent {
+    pub fn into_event(self) -> Event {
+        let mut event = Event::new("relay_key_set")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("player", self.player);
+        if let Some(pubkey) = self.pubkey {
+            event = event.add_attribute("pubkey", pubkey.to_base64());
+        }
+        event
+    }
+}
+
+/// Canonical event emitted when the owner changes the relayer whitelist.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RelayersEvent {
+    /// relayers added to the whitelist
+    pub added: Vec<Addr>,
+    /// relayers removed from the whitelist
+    pub removed: Vec<Addr>,
+}
+
+impl RelayersEvent {
+    pub fn into_event(self) -> StdResult<Event> {
+        Ok(Event::new("relayers_updated")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("added", to_json_string(&self.added)?)
+            .add_attribute("removed", to_json_string(&self.removed)?))
+    }
+}
+
+/// Canonical event emitted when operators update the blocklist.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlocklistEvent {
+    /// addresses blocked
+    pub added: Vec<Addr>,
+    /// addresses unblocked
+    pub removed: Vec<Addr>,
+}
+
+impl BlocklistEvent {
+    pub fn into_event(self) -> StdResult<Event> {
+        Ok(Event::new("blocklist_updated")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("added", to_json_string(&self.added)?)
+            .add_attribute("removed", to_json_string(&self.removed)?))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEventKind {
+    /// A player granted or renewed a session key
+    SessionGranted,
+    /// A session key accepted the grant of a player
+    SessionAccepted,
+    /// A player or the session key revoked a session
+    SessionRevoked,
+}
+
+impl SessionEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionEventKind::SessionGranted => "session_granted",
+            SessionEventKind::SessionAccepted => "session_accepted",
+            SessionEventKind::SessionRevoked => "session_revoked",
+        }
+    }
+}
+
+/// Canonical event emitted when a player grants or revokes a session key, or the key accepts
+/// it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SessionEvent {
+    /// event type
+    pub kind: SessionEventKind,
+    /// session key
+    pub key: Addr,
+    /// player the key acts for
+    pub player: Addr,
+    /// expiration of the key
+    pub expires: Expiration,
+    /// largest stake the key can put up, if limited
+    pub max_stake: Option<GenericBalance>,
+}
+
+impl SessionEvent {
+    pub fn new(kind: SessionEventKind, key: &Addr, session: &Session) -> Self {
+        SessionEvent {
+            kind,
+            key: key.clone(),
+            player: session.player.clone(),
+            expires: session.expires,
+            max_stake: session.max_stake.clone(),
+        }
+    }
+
+    pub fn into_event(self) -> StdResult<Event> {
+        let mut event = Event::new(self.kind.as_str())
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("key", self.key)
+            .add_attribute("player", self.player)
+            .add_attribute("expires", self.expires.to_string());
+        if let Some(max_stake) = self.max_stake {
+            event = event.add_attribute("max_stake", to_json_string(&max_stake)?);
+        }
+        Ok(event)
+    }
+}
+
+/// Canonical event emitted when a player registers their referrer.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReferrerEvent {
+    /// player that was referred
+    pub player: Addr,
+    /// referrer credited a share of the player's fees
+    pub referrer: Addr,
+}
+
+impl ReferrerEvent {
+    pub fn into_event(self) -> Event {
+        Event::new("referrer_set")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("player", self.player)
+            .add_attribute("referrer", self.referrer)
+    }
+}
+
+/// Canonical event emitted when a player sets their stake limits.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LimitsEvent {
+    /// player the limits apply to
+    pub player: Addr,
+    /// limits in force, as JSON
+    pub limits: String,
+    /// looser limits waiting for the cool-down, as JSON, if any
+    pub pending: Option<String>,
+    /// block time in seconds from which the pending limits apply
+    pub effective: Option<u64>,
+}
+
+impl LimitsEvent {
+    pub fn new(player: Addr, limits: &PlayerLimits) -> StdResult<Self> {
+        Ok(LimitsEvent {
+            player,
+            limits: to_json_string(&limits.limits)?,
+            pending: limits
+                .pending
+                .as_ref()
+                .map(|pending| to_json_string(&pending.limits))
+                .transpose()?,
+            effective: limits
+                .pending
+                .as_ref()
+                .map(|pending| pending.effective.seconds()),
+        })
+    }
+
+    pub fn into_event(self) -> Event {
+        let mut event = Event::new("stake_limits_set")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("player", self.player)
+            .add_attribute("limits", self.limits);
+        if let Some(pending) = self.pending {
+            event = event.add_attribute("pending", pending);
+        }
+        if let Some(effective) = self.effective {
+            event = event.add_attribute("effective", effective.to_string());
+        }
+        event
+    }
+}
+
+/// Canonical event emitted when a player excludes themselves from staking.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SelfExclusionEvent {
+    /// player excluded
+    pub player: Addr,
+    /// block time in seconds until which the player cannot stake
+    pub until: u64,
+}
+
+impl SelfExclusionEvent {
+    pub fn into_event(self) -> Event {
+        Event::new("self_excluded")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("player", self.player)
+            .add_attribute("until", self.until.to_string())
+    }
+}
+
+/// Canonical event emitted when fees or referral rewards are claimed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimEvent {
+    /// address the tokens were owed to
+    pub recipient: Addr,
+    /// tokens sent
+    pub amount: GenericBalance,
+}
+
+impl ClaimEvent {
+    pub fn into_event(self) -> StdResult<Event> {
+        Ok(Event::new("claimed")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("recipient", self.recipient)
+            .add_attribute("amount", to_json_string(&self.amount)?))
+    }
+}
+
+/// Canonical event emitted when the owner or a player commits the secret of the jackpot draw
+/// of a wager.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JackpotCommitEvent {
+    /// wager whose settlement is drawn
+    pub wager_id: u64,
+    /// owner or player who committed
+    pub committer: Addr,
+    /// SHA-256 hash of the secret
+    pub commitment: Binary,
+}
+
+impl JackpotCommitEvent {
+    pub fn into_event(self) -> Event {
+        Event::new("jackpot_committed")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("wager_id", self.wager_id.to_string())
+            .add_attribute("committer", self.committer)
+            .add_attribute("commitment", self.commitment.to_base64())
+    }
+}
+
+/// Canonical event emitted when a secret of a commit-reveal draw is revealed and the draw still
+/// waits for the other secret.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JackpotRevealEvent {
+    /// wager whose settlement is drawn
+    pub wager_id: u64,
+    /// owner or winner who revealed
+    pub revealer: Addr,
+}
+
+impl JackpotRevealEvent {
+    pub fn into_event(self) -> Event {
+        Event::new("jackpot_seed_revealed")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("wager_id", self.wager_id.to_string())
+            .add_attribute("revealer", self.revealer)
+    }
+}
+
+/// Canonical event emitted by every jackpot draw, at settlement for beacon draws and when the
+/// last secret is revealed, or the reveal deadline claimed, for commit-reveal draws.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct JackpotDrawEvent {
+    /// wager whose settlement was drawn
+    pub wager_id: u64,
+    /// winner of the wager
+    pub winner: Addr,
+    /// true if the draw won the jackpot
+    pub won: bool,
+    /// jackpot paid to the winner, empty if the draw lost
+    pub amount: GenericBalance,
+}
+
+impl JackpotDrawEvent {
+    pub fn into_event(self) -> StdResult<Event> {
+        Ok(Event::new("jackpot_drawn")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("wager_id", self.wager_id.to_string())
+            .add_attribute("winner", self.winner)
+            .add_attribute("won", self.won.to_string())
+            .add_attribute("amount", to_json_string(&self.amount)?))
+    }
+}
+
+/// Canonical event emitted when a player of a game of chance reveals their committed choice
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChoiceEvent {
+    /// id of the wager
+    pub wager_id: u64,
+    /// player who revealed
+    pub player: Addr,
+    /// revealed choice
+    pub choice: u8,
+}
+
+impl ChoiceEvent {
+    pub fn into_event(self) -> Event {
+        Event::new("choice_revealed")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("wager_id", self.wager_id.to_string())
+            .add_attribute("player", self.player)
+            .add_attribute("choice", self.choice.to_string())
+    }
+}
+
+/// Canonical event emitted for every round of a series the arbiter reports
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoundEvent {
+    /// id of the wager
+    pub wager_id: u64,
+    /// number of the round, from 1
+    pub round: u32,
+    /// winner of the round
+    pub winner: Addr,
+    /// rounds won by the creator so far
+    pub user1_wins: u32,
+    /// rounds won by the opponent so far
+    pub user2_wins: u32,
+}
+
+impl RoundEvent {
+    pub fn into_event(self) -> Event {
+        Event::new("round_reported")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("wager_id", self.wager_id.to_string())
+            .add_attribute("round", self.round.to_string())
+            .add_attribute("winner", self.winner)
+            .add_attribute("user1_wins", self.user1_wins.to_string())
+            .add_attribute("user2_wins", self.user2_wins.to_string())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SidePoolEventKind {
+    /// A spectator bet on a side of a matched wager
+    SideBetPlaced,
+    /// The arbiter closed the pool to new bets
+    SideBetsLocked,
+    /// The wager ended and the pool was split between the winning bets or refunded
+    SidePoolClosed,
+    /// A spectator claimed their winnings or refund
+    SideBetClaimed,
+}
+
+impl SidePoolEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SidePoolEventKind::SideBetPlaced => "side_bet_placed",
+            SidePoolEventKind::SideBetsLocked => "side_bets_locked",
+            SidePoolEventKind::SidePoolClosed => "side_pool_closed",
+            SidePoolEventKind::SideBetClaimed => "side_bet_claimed",
+        }
+    }
+}
+
+/// Canonical event emitted whenever the side pool of a wager changes. Fields that do not apply
+/// to the event type are left out.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SidePoolEvent {
+    /// event type
+    pub kind: SidePoolEventKind,
+    /// id of the wager
+    pub wager_id: u64,
+    /// status of the pool once the handler is done
+    pub status: SidePoolStatus,
+    /// asset of the bets, unset until the first bet
+    pub asset: Option<Denom>,
+    /// amount bet on the creator
+    pub user1_total: Uint128,
+    /// amount bet on the opponent
+    pub user2_total: Uint128,
+    /// spectator who bet or claimed
+    pub bettor: Option<Addr>,
+    /// side of a bet
+    pub side: Option<Side>,
+    /// amount bet or claimed
+    pub amount: Option<Uint128>,
+    /// fee taken from the pool when it closed
+    pub fee: Option<Uint128>,
+}
+
+impl SidePoolEvent {
+    pub fn new(kind: SidePoolEventKind, wager_id: u64, pool: &SidePool) -> Self {
+        SidePoolEvent {
+            kind,
+            wager_id,
+            status: pool.status.clone(),
+            asset: pool.asset.clone(),
+            user1_total: pool.user1_total,
+            user2_total: pool.user2_total,
+            bettor: None,
+            side: None,
+            amount: None,
+            fee: None,
+        }
+    }
+
+    pub fn bet(mut self, bettor: Addr, side: Option<Side>, amount: Uint128) -> Self {
+        self.bettor = Some(bettor);
+        self.side = side;
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn fee(mut self, fee: Uint128) -> Self {
+        self.fee = Some(fee);
+        self
+    }
+
+    pub fn into_event(self) -> StdResult<Event> {
+        let mut event = Event::new(self.kind.as_str())
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("wager_id", self.wager_id.to_string())
+            .add_attribute("status", self.status.to_string());
+        if let SidePoolStatus::Won { side } = &self.status {
+            event = event.add_attribute("winning_side", side.to_string());
+        }
+        if let Some(asset) = &self.asset {
+            event = event.add_attribute("asset", to_json_string(asset)?);
+        }
+        event = event
+            .add_attribute("user1_total", self.user1_total)
+            .add_attribute("user2_total", self.user2_total);
+        if let Some(bettor) = self.bettor {
+            event = event.add_attribute("bettor", bettor);
+        }
+        if let Some(side) = self.side {
+            event = event.add_attribute("side", side.to_string());
+        }
+        if let Some(amount) = self.amount {
+            event = event.add_attribute("amount", amount);
+        }
+        if let Some(fee) = self.fee {
+            event = event.add_attribute("fee", fee);
+        }
+        Ok(event)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoleEventKind {
+    /// An owner granted a role
+    RoleGranted,
+    /// An owner revoked a role
+    RoleRevoked,
+}
+
+impl RoleEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoleEventKind::RoleGranted => "role_granted",
+            RoleEventKind::RoleRevoked => "role_revoked",
+        }
+    }
+}
+
+/// Canonical event emitted when a role is granted or revoked.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoleEvent {
+    /// event type
+    pub kind: RoleEventKind,
+    /// role granted or revoked
+    pub role: Role,
+    /// address gaining or losing the role
+    pub address: Addr,
+    /// game the arbiter role is scoped to, if any
+    pub game_id: Option<String>,
+}
+
+impl RoleEvent {
+    pub fn into_event(self) -> Event {
+        let mut event = Event::new(self.kind.as_str())
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("role", self.role.as_str())
+            .add_attribute("address", self.address);
+        if let Some(game_id) = self.game_id {
+            event = event.add_attribute("game_id", game_id);
+        }
+        event
+    }
+}
+
+/// Canonical event emitted when the contract is paused or unpaused.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseEvent {
+    /// true once paused, false once unpaused
+    pub paused: bool,
+    /// guardian or owner who changed the pause
+    pub sender: Addr,
+}
+
+impl PauseEvent {
+    pub fn into_event(self) -> Event {
+        let ty = if self.paused { "paused" } else { "unpaused" };
+        Event::new(ty)
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("sender", self.sender)
+    }
+}
+
+/// Canonical event emitted when an arbiter votes for the winner of a wager.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoteEvent {
+    /// id of the wager
+    pub wager_id: u64,
+    /// arbiter who voted
+    pub arbiter: Addr,
+    /// winner voted for
+    pub winner: Addr,
+    /// votes for this winner so far
+    pub approvals: u32,
+    /// votes for the same winner needed to settle
+    pub required: u32,
+}
+
+impl VoteEvent {
+    pub fn into_event(self) -> Event {
+        Event::new("result_voted")
+            .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+            .add_attribute("wager_id", self.wager_id.to_string())
+            .add_attribute("arbiter", self.arbiter)
+            .add_attribute("winner", self.winner)
+            .add_attribute("approvals", self.approvals.to_string())
+            .add_attribute("required", self.required.to_string())
+    }
+}
+
+fn to_json_string<T: Serialize>(value: &T) -> StdResult<String> {
+    String::from_utf8(to_vec(value)?).map_err(StdError::from)
+}
@@ -1,5 +1,7 @@
+mod address;
 pub mod contract;
 mod error;
+pub mod events;
 pub mod msg;
 pub mod state;
 
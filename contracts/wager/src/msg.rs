@@ -1,10 +1,13 @@
-use cw20::Cw20ReceiveMsg;
+use cw20::{Cw20ReceiveMsg, Denom, Expiration};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Uint128};
 
-use crate::state::GenericBalance;
+use crate::state::{
+    BlockedPolicy, ChanceGameKind, ConfigChange, GenericBalance, JackpotWin, PlayerLimits,
+    QueueEntry, RandomnessSource, Role, Session, Side, StakeLimits, Vote,
+};
 
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct InstantiateMsg {
@@ -18,11 +21,98 @@ pub enum ExecuteMsg {
     // Reacts to the event of CW20 tokens being sent to this contract for creating wagers
     // and adding funds to wagers.
     Receive(Cw20ReceiveMsg),
+    // Reacts to the event of a cw721 NFT being sent to this contract for creating NFT wagers
+    // and depositing NFT stakes.
+    ReceiveNft(Cw721ReceiveMsg),
     // Creates an instance of the escrow and adds native funds from User 1.
-    // Creates an escrow ID that can later be referenced. Sets User 1
-    // cancel permissions by adding wallet address to the escrow information bucket.
+    // The contract assigns the escrow ID, returned in the response data, that can later
+    // be referenced. Sets User 1 cancel permissions by adding wallet address to the escrow
+    // information bucket. An optional label of 3 to 20 bytes is stored with the escrow.
+    // A stake made of several tokens can be given, the funds sent are then the first
+    // deposit and the escrow opens for User 2 once the rest is added with DepositNative
+    // or the Deposit hook. Instead of a token stake, a stake value in the price oracle's
+    // reference unit can be given, both players may then deposit any tokens worth it.
+    // With an odd series_length, the wager is a best-of-N series settled by ReportRound.
     CreateWagerNative {
-        wager_id: String,
+        label: Option<String>,
+        stake: Option<GenericBalance>,
+        stake_value: Option<Uint128>,
+        series_length: Option<u32>,
+    },
+
+    // Creates an escrow like CreateWagerNative with CW20 tokens pulled from User 1 through
+    // TransferFrom, which needs an allowance of at least the amount for this contract. The
+    // escrow is only recorded once the contract received the whole amount.
+    CreateWagerCw20 {
+        token: String,
+        amount: Uint128,
+        label: Option<String>,
+        stake: Option<GenericBalance>,
+        stake_value: Option<Uint128>,
+        series_length: Option<u32>,
+    },
+
+    // Adds CW20 funds into an existing escrow like AddFundsNative, pulling them from User 2
+    // through TransferFrom. Needs an allowance of at least the amount for this contract.
+    JoinWagerCw20 {
+        wager_id: u64,
+        token: String,
+        amount: Uint128,
+    },
+
+    // Creates a game of chance with native funds, settled by the contract without the
+    // arbiter. The commitment is the SHA-256 hash of the creator's choice byte followed by a
+    // secret salt. Once matched, both players have reveal_period blocks, at most 100800, to
+    // reveal with RevealChoice, a player who does not reveal in time forfeits.
+    CreateChanceWager {
+        game: ChanceGameKind,
+        commitment: Binary,
+        reveal_period: u64,
+    },
+
+    // Creates a king-of-the-hill wager with native funds. Challengers join it like any wager,
+    // but once settled the pot, less the platform fee, stays escrowed as the stake of the
+    // winner, the champion, in a new wager for the next challenger, whose id is returned in
+    // the next_id attribute. The champion can cash out between matches with CashOut.
+    CreateHillWager {
+        label: Option<String>,
+    },
+
+    // Ends a king-of-the-hill wager between matches and sends the champion their stake. Only
+    // the champion can use this function.
+    CashOut {
+        wager_id: u64,
+    },
+
+    // Creates a wager whose players stake different things fixed at creation, such as an NFT
+    // against tokens, with native funds as the first deposit. The creator's stake defaults
+    // to the funds sent and can hold NFTs deposited with the Deposit hook of ReceiveNft, the
+    // counter stake is what User 2 deposits to match it. One of the stakes must hold an NFT.
+    CreateNftWager {
+        label: Option<String>,
+        stake: Option<GenericBalance>,
+        counter_stake: GenericBalance,
+    },
+
+    // Joins a game of chance with native funds equal to the creator's stake and the
+    // commitment of the opponent's choice.
+    JoinChanceWager {
+        wager_id: u64,
+        commitment: Binary,
+    },
+
+    // Reveals the choice and salt committed to in a matched game of chance. The contract
+    // settles the wager once both players revealed.
+    RevealChoice {
+        wager_id: u64,
+        choice: u8,
+        salt: Binary,
+    },
+
+    // Settles a game of chance after its reveal deadline. The only player who revealed wins,
+    // the stakes are refunded if nobody did.
+    ClaimRevealTimeout {
+        wager_id: u64,
     },
 
     // Adds native funds into an existing escrow using an escrow ID.
@@ -30,29 +120,543 @@ pub enum ExecuteMsg {
     // address from the escrow information bucket. At this point,
     // the escrow is not cancellable.
     AddFundsNative {
-        wager_id: String,
+        wager_id: u64,
+    },
+
+    // Adds native funds towards a stake made of several tokens. User 1 uses it to complete
+    // the stake, then User 2 to match it. The escrow is matched once User 2 deposited the
+    // whole stake.
+    DepositNative {
+        wager_id: u64,
     },
 
     // Cancels the match. Either User 1 or Game can execute this
     Cancel {
-        wager_id: String,
+        wager_id: u64,
+    },
+
+    // Proposes to the other player of a matched wager to cancel it. Only the players can use
+    // this function.
+    ProposeCancel {
+        wager_id: u64,
+    },
+
+    // Accepts the cancellation the other player proposed, both stakes are refunded.
+    AcceptCancel {
+        wager_id: u64,
+    },
+
+    // Withdraws the cancellation the caller proposed. A proposal is also dropped once a round
+    // is reported or an arbiter votes on the wager.
+    RetractCancel {
+        wager_id: u64,
     },
 
-    // When winner is determined, the game sends out the wager
-    // pot to the winning User. Only the Game can use this function.
+    // Concedes a matched wager: the opponent wins and is paid like with SendFunds, fees
+    // included. Only the players themselves can use this function, not their session keys.
+    Forfeit {
+        wager_id: u64,
+    },
+
+    // Refunds both stakes of a matched wager whose game never started. Only the Game can use
+    // this function.
+    VoidWager {
+        wager_id: u64,
+    },
+
+    // Asks to keep the winnings of a wager, once settled, as claimable tokens of the caller
+    // instead of sending them, so that they can fund a rematch. Only the players can use this
+    // function, on wagers that can be rematched.
+    RequestRematch {
+        wager_id: u64,
+    },
+
+    // Offers the opponent of a settled wager a rematch: a new wager with the same players,
+    // game, arbiter and stake that only the opponent can join. The caller funds it with the
+    // native funds sent or, with from_claimable, with the stake taken from their claimable
+    // tokens, such as winnings kept with RequestRematch. A settled wager can be rematched
+    // once, within a day, games of chance, king-of-the-hill and NFT wagers cannot be.
+    OfferRematch {
+        settled_wager_id: u64,
+        from_claimable: bool,
+    },
+
+    // Records the winner of the next round of a series, rounds are numbered from 1. The pot is
+    // sent to the player who won the majority of the rounds. Only the Game can use this
+    // function.
+    ReportRound {
+        wager_id: u64,
+        round: u32,
+        winner: String,
+    },
+
+    // Bets the native funds sent, a single coin, on a side of a matched wager. Bets of a wager
+    // form a parimutuel pool in the asset of its first bet, open until the Game locks it or
+    // play starts: a round is reported, an arbiter votes or a player reveals their choice.
+    // Players of the wager and arbiters who can settle it cannot bet.
+    PlaceSideBet {
+        wager_id: u64,
+        side: Side,
+    },
+
+    // Closes the side pool of a wager to new bets before play starts. Only the Game can use
+    // this function.
+    LockSideBets {
+        wager_id: u64,
+    },
+
+    // Pays the caller's side bets on a wager once it ended. The bets on the winner share the
+    // pool less the platform fee in proportion to their amount. Bets are refunded if the wager
+    // ended without a winner or nobody bet on one of the sides.
+    ClaimSideBet {
+        wager_id: u64,
+    },
+
+    // Votes for the winner of a wager, as an arbiter. The wager is settled once the required
+    // number of arbiters voted for the same winner, and disputed if arbiters disagree.
+    // Wagers whose stake reaches the approval threshold can only be settled this way.
+    VoteResult {
+        wager_id: u64,
+        winner: String,
+    },
+
+    // When winner is determined, the game sends out the pot of a matched
+    // wager to the winning User. Only the Game can use this function.
     SendFunds {
-        wager_id: String,
+        wager_id: u64,
         winner_address: Addr,
     },
+
+    // Joins the matchmaking queue of a game with the native funds sent, which must equal
+    // the stake. If one of the 30 longest waiting players with the same stake and the caller
+    // are within each other's rating band, a matched wager is created. Otherwise the caller
+    // waits in the queue.
+    JoinQueue {
+        game_id: String,
+        stake: Coin,
+        max_rating_diff: Option<u32>,
+    },
+
+    // Leaves the matchmaking queue of a game and refunds the queued stake.
+    LeaveQueue {
+        game_id: String,
+    },
+
+    // Sets the rating of a player used for matchmaking. Only operators can use this function.
+    SetRating {
+        player: String,
+        rating: u32,
+    },
+
+    // Updates the fields of the configuration that are set. Only the owner can use this
+    // function, and only while no config delay is set.
+    UpdateConfig(ConfigUpdate),
+
+    // Queues a configuration, role, relayer or blocklist update that can be executed once the
+    // config delay has passed. Only the holders of the role the update needs can use this
+    // function.
+    ProposeConfigChange {
+        update: TimelockedUpdate,
+    },
+
+    // Applies a queued update once its eta has passed. Only the holders of the role the
+    // update needs can use this function.
+    ExecuteConfigChange {
+        id: u64,
+    },
+
+    // Drops a queued update. Only the holders of the role the update needs can use this
+    // function.
+    CancelConfigChange {
+        id: u64,
+    },
+
+    // Grants a role to an address. With a game_id, the arbiter role only covers the wagers of
+    // that game, other roles cannot be scoped. Only owners can use this function, and only
+    // while no config delay is set.
+    GrantRole {
+        role: Role,
+        address: String,
+        game_id: Option<String>,
+    },
+
+    // Revokes a role from an address, the arbiter role of a game with its game_id. Only owners
+    // can use this function, while no config delay is set, and an owner cannot revoke their
+    // own owner role. The owner that revokes the creator's owner role takes over the platform
+    // fees and new wagers.
+    RevokeRole {
+        role: Role,
+        address: String,
+        game_id: Option<String>,
+    },
+
+    // Refuses new wagers, deposits and side bets until unpaused. Wagers can still be
+    // cancelled and settled and tokens claimed. Only guardians can use this function.
+    Pause {},
+
+    // Lifts a pause. Only owners can use this function.
+    Unpause {},
+
+    // Sets the most the caller can stake per token over a day, a week and a month, in wagers,
+    // side bets and queue entries. A cap of zero forbids staking the token. Tighter limits
+    // apply at once, looser ones after a cool-down of a day.
+    SetStakeLimits {
+        limits: StakeLimits,
+    },
+
+    // Refuses stakes of the caller for the given number of seconds, at most 5 years. An
+    // exclusion can be extended but not shortened.
+    SelfExclude {
+        seconds: u64,
+    },
+
+    // Registers the referrer of the caller. A player can only set a referrer once.
+    SetReferrer {
+        referrer: String,
+    },
+
+    // Commits the SHA-256 hash of a secret for the commit-reveal jackpot draw of a wager that
+    // is not settled yet. The players of the wager can commit their own secret, anyone else
+    // must be an owner. The wager is only drawn if an owner committed.
+    CommitJackpotSeed {
+        wager_id: u64,
+        commitment: Binary,
+    },
+
+    // Reveals a secret committed for the jackpot draw of a settled wager, the winner reveals
+    // theirs and owners the owner's. The jackpot is drawn once every secret is revealed.
+    RevealJackpotSeed {
+        wager_id: u64,
+        secret: Binary,
+    },
+
+    // Decides a commit-reveal draw whose reveal deadline passed: a winner who did not reveal
+    // loses it, otherwise the owner did not and the winner wins the jackpot.
+    ClaimDrawTimeout {
+        wager_id: u64,
+    },
+
+    // Sends the caller the tokens owed to them: platform fees for the owner and referral
+    // rewards for referrers.
+    Claim {},
+
+    // Creates or joins a wager on behalf of a player who signed the intent off-chain, so the
+    // player needs no gas. Only whitelisted relayers can submit intents. The signature is
+    // checked over the SHA-256 hash of the RelayDoc against the key carried in the intent,
+    // which must derive to the player address, or else the key the player registered with
+    // SetRelayKey. The stake is pulled from the player's CW20 allowance.
+    Relay {
+        intent: RelayIntent,
+        signature: Binary,
+    },
+
+    // Registers the secp256k1 public key, compressed or not, whose signatures the contract
+    // accepts for intents of the caller. No key disables relaying for the caller.
+    SetRelayKey {
+        pubkey: Option<Binary>,
+    },
+
+    // Adds and removes relayers allowed to submit intents. Only operators can use this
+    // function, and only while no config delay is set.
+    UpdateRelayers {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+
+    // Adds and removes addresses from the blocklist. Blocked addresses cannot create, join or
+    // fund wagers. Only operators can use this function, and only while no config delay is
+    // set.
+    UpdateBlocklist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+
+    // Ends a wager of a blocked player. An unmatched wager is refunded, a matched one is
+    // forfeited to the opponent or refunded as the blocked policy says. Only operators can
+    // use this function.
+    ForceCancel {
+        wager_id: u64,
+    },
+
+    // Removes a blocked player from the matchmaking queue of a game and refunds the queued
+    // stake. Only operators can use this function.
+    ForceLeaveQueue {
+        game_id: String,
+        player: String,
+    },
+
+    // Lets the key address create, join and cancel wagers for the caller until it expires,
+    // once the key accepted it with AcceptSession. Stakes are pulled from the caller's CW20
+    // allowance with CreateWagerCw20 and JoinWagerCw20 and refunds and winnings go to the
+    // caller. A stake put up by the key must be within max_stake, if given.
+    GrantSession {
+        key: String,
+        expires: Expiration,
+        max_stake: Option<GenericBalance>,
+    },
+
+    // Accepts the session the player granted the caller, the caller then acts for the player.
+    AcceptSession {
+        player: String,
+    },
+
+    // Revokes a session key granted by the caller, or the caller's own session as a key.
+    RevokeSession {
+        key: String,
+    },
+
+    // Sends the tokens of an asset held above what is escrowed for players, such as coins or
+    // CW20 tokens sent to the contract by mistake, to the recipient. Only the owner can use
+    // this function.
+    Sweep {
+        asset: Denom,
+        recipient: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
     // Creates a wager with the sent CW20 tokens.
-    CreateWager { wager_id: String },
+    CreateWager {
+        label: Option<String>,
+        stake: Option<GenericBalance>,
+        stake_value: Option<Uint128>,
+        series_length: Option<u32>,
+    },
+    // Creates a game of chance with the sent CW20 tokens.
+    CreateChanceWager {
+        game: ChanceGameKind,
+        commitment: Binary,
+        reveal_period: u64,
+    },
+    // Creates a king-of-the-hill wager with the sent CW20 tokens.
+    CreateHillWager {
+        label: Option<String>,
+    },
+    // Creates a wager with different stakes for each player with the sent CW20 tokens.
+    CreateNftWager {
+        label: Option<String>,
+        stake: Option<GenericBalance>,
+        counter_stake: GenericBalance,
+    },
+    // Joins a game of chance with the sent CW20 tokens.
+    JoinChanceWager {
+        wager_id: u64,
+        commitment: Binary,
+    },
     // Adds funds to a wager with the sent CW20 tokens.
-    AddFunds { wager_id: String },
+    AddFunds {
+        wager_id: u64,
+    },
+    // Adds the sent CW20 tokens towards a stake made of several tokens.
+    Deposit {
+        wager_id: u64,
+    },
+    // Bets the sent CW20 tokens on a side of a matched wager.
+    PlaceSideBet {
+        wager_id: u64,
+        side: Side,
+    },
+    // Joins the matchmaking queue of a game with the sent CW20 tokens as the stake.
+    JoinQueue {
+        game_id: String,
+        max_rating_diff: Option<u32>,
+    },
+    // Offers a rematch of a settled wager funded with the sent CW20 tokens.
+    OfferRematch {
+        settled_wager_id: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ConfigUpdate {
+    /// price oracle used to value stakes
+    pub oracle: Option<String>,
+    /// tolerance, in basis points, within which deposits must match a valued stake
+    pub price_tolerance_bps: Option<u64>,
+    /// platform fee taken from each side of a settled pot, in basis points
+    pub fee_bps: Option<u64>,
+    /// share of the fee on a referred player's side paid to their referrer, in basis points
+    pub referral_share_bps: Option<u64>,
+    /// share of the owner's part of the fee added to the jackpot, in basis points
+    pub jackpot_share_bps: Option<u64>,
+    /// a settled wager wins the jackpot with a chance of one in this many, never if 0
+    pub jackpot_odds: Option<u64>,
+    /// source of the randomness of jackpot draws
+    pub randomness: Option<RandomnessSource>,
+    /// stake from which wagers are settled by arbiter votes, an empty balance disables it
+    pub approval_threshold: Option<GenericBalance>,
+    /// number of arbiters that must agree on the winner of such a wager
+    pub required_approvals: Option<u32>,
+    /// seconds proposed changes wait before they can be executed, at most 30 days
+    pub config_delay: Option<u64>,
+    /// outcome of force-cancelled matched wagers of blocked players
+    pub blocked_policy: Option<BlockedPolicy>,
+}
+
+/// Update that waits for the config delay, made by owners or, for relayers and the
+/// blocklist, by operators
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelockedUpdate {
+    Config(ConfigUpdate),
+    GrantRole {
+        role: Role,
+        address: String,
+        game_id: Option<String>,
+    },
+    RevokeRole {
+        role: Role,
+        address: String,
+        game_id: Option<String>,
+    },
+    UpdateRelayers {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    UpdateBlocklist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+}
+
+impl TimelockedUpdate {
+    /// Role needed to propose, execute or cancel the update
+    pub fn role(&self) -> Role {
+        match self {
+            TimelockedUpdate::Config(_)
+            | TimelockedUpdate::GrantRole { .. }
+            | TimelockedUpdate::RevokeRole { .. } => Role::Owner,
+            TimelockedUpdate::UpdateRelayers { .. } | TimelockedUpdate::UpdateBlocklist { .. } => {
+                Role::Operator
+            }
+        }
+    }
+}
+
+/// Wager action a player signs off-chain for a relayer to submit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RelayIntent {
+    /// player the stake is pulled from and who plays the wager
+    pub player: String,
+    pub action: RelayAction,
+    /// must equal the next nonce of the player, see QueryMsg::Nonce
+    pub nonce: u64,
+    /// the intent is refused once expired
+    pub expires: Expiration,
+    /// secp256k1 key of the player, compressed or not, must derive to the player address.
+    /// When None the key registered with SetRelayKey is used.
+    pub pubkey: Option<Binary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayAction {
+    // Same as ExecuteMsg::CreateWagerCw20.
+    CreateWager {
+        token: String,
+        amount: Uint128,
+        label: Option<String>,
+        stake: Option<GenericBalance>,
+        stake_value: Option<Uint128>,
+        series_length: Option<u32>,
+    },
+    // Same as ExecuteMsg::JoinWagerCw20.
+    JoinWager {
+        wager_id: u64,
+        token: String,
+        amount: Uint128,
+    },
+}
+
+/// Document signed by the player, binding the intent to a chain and to this contract. The
+/// signature covers the SHA-256 hash of its JSON encoding.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RelayDoc {
+    pub chain_id: String,
+    pub contract: String,
+    pub intent: RelayIntent,
+}
+
+/// Message a cw721 contract sends along with ReceiveNft when an NFT is sent to this contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw721ReceiveMsg {
+    pub sender: String,
+    pub token_id: String,
+    pub msg: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721HookMsg {
+    // Creates a wager with different stakes for each player with the sent NFT.
+    CreateNftWager {
+        label: Option<String>,
+        stake: Option<GenericBalance>,
+        counter_stake: GenericBalance,
+    },
+    // Adds the sent NFT towards a stake holding NFTs.
+    Deposit {
+        wager_id: u64,
+    },
+}
+
+/// Execute interface of the cw721 contracts holding NFT stakes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721ExecuteMsg {
+    // Transfers the NFT to the recipient.
+    TransferNft { recipient: String, token_id: String },
+}
+
+/// Query interface of the randomness beacon used for jackpot draws
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BeaconQueryMsg {
+    // Returns the latest randomness of the beacon.
+    Randomness {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RandomnessResponse {
+    pub randomness: Binary,
+}
+
+/// Query interface of the price oracle used to value stakes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OracleQueryMsg {
+    // Returns the price of the smallest unit of an asset in the reference unit.
+    Price { asset: Denom },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceResponse {
+    pub price: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VotesResponse {
+    pub votes: Vec<Vote>,
+    /// votes for the same winner needed to settle
+    pub required: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RolesResponse {
+    pub role: Role,
+    pub game_id: Option<String>,
+    pub addresses: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoundsResponse {
+    pub series_length: u32,
+    /// winner of each reported round, in order
+    pub rounds: Vec<Addr>,
+    pub user1_wins: u32,
+    pub user2_wins: u32,
 }
 
 pub fn is_valid_name(name: &str) -> bool {
@@ -68,19 +672,59 @@ pub fn is_valid_name(name: &str) -> bool {
 pub enum QueryMsg {
     //TODO: query messages
     Config {},
-    Wager { id: String },
+    Wager { id: u64 },
+    // Returns the reported rounds of a series.
+    Rounds { wager_id: u64 },
+    // Lists the holders of a role, the arbiters of a game with its game_id.
+    Roles { role: Role, game_id: Option<String> },
+    // Returns the arbiter votes on a wager.
+    Votes { wager_id: u64 },
+    // Returns the terms of the rematch a player can offer after a settled wager.
+    Rematch { settled_wager_id: u64 },
+    // Lists the configuration changes waiting for their timelock, oldest first.
+    ConfigChanges {},
+    // Returns the stake limits and self-exclusion of a player and what they staked in each
+    // window.
+    PlayerLimits { player: String },
+    // Returns the side pool of a wager.
+    SidePool { wager_id: u64 },
+    // Returns the side bets of a spectator on a wager.
+    SideBet { wager_id: u64, bettor: String },
+    // Lists the players waiting in the matchmaking queue of a game.
+    Queue { game_id: String },
+    // Returns whether an address is blocked.
+    Blocked { address: String },
+    // Returns the matchmaking rating of a player.
+    Rating { player: String },
+    // Compares the tokens escrowed for players with the contract's bank and CW20 holdings.
+    Solvency {},
+    // Returns the nonce the next relayed intent of a player must carry.
+    Nonce { player: String },
+    // Returns the session granted to a key, if any.
+    Session { key: String },
+    // Lists the players a referrer brought in, their settled volume and the referrer's
+    // earnings.
+    ReferralStats { referrer: String },
+    // Returns the wins, losses and forfeits of a player in settled wagers.
+    PlayerStats { player: String },
+    // Returns the tokens an address can claim.
+    Claimable { address: String },
+    // Returns the current jackpot and the wins of the latest wagers, highest wager id first.
+    Jackpot { limit: Option<u32> },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct ListResponse {
     /// list all registered ids
-    pub wagers: Vec<String>,
+    pub wagers: Vec<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct DetailsResponse {
     /// id of this escrow
-    pub id: String,
+    pub id: u64,
+    /// label given by the creator
+    pub label: Option<String>,
     /// arbiter can decide to approve or refund the escrow, this is typically the game address
     pub arbiter: Addr,
     /// creator of contract
@@ -104,3 +748,100 @@ pub struct DetailsResponse {
     // pub cw20_whitelist: Vec<Addr>, // TODO: WHITELIST?
     // WHITELIST? -> Only DUEL tokens available for wager
 }
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ConfigChangesResponse {
+    /// pending changes, oldest first
+    pub changes: Vec<ConfigChange>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PlayerLimitsResponse {
+    pub limits: PlayerLimits,
+    /// stakes of the player today
+    pub daily_staked: GenericBalance,
+    /// stakes of the player over the last 7 days
+    pub weekly_staked: GenericBalance,
+    /// stakes of the player over the last 30 days
+    pub monthly_staked: GenericBalance,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct QueueResponse {
+    /// players waiting for an opponent, oldest first
+    pub entries: Vec<QueueEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BlockedResponse {
+    pub address: Addr,
+    pub blocked: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RatingResponse {
+    pub player: Addr,
+    pub rating: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SolvencyResponse {
+    /// true if the holdings of every asset cover what is escrowed
+    pub solvent: bool,
+    /// every asset that is escrowed or held by the contract
+    pub assets: Vec<AssetSolvency>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AssetSolvency {
+    pub asset: Denom,
+    /// amount owed to players
+    pub escrowed: Uint128,
+    /// amount held by the contract
+    pub held: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct NonceResponse {
+    pub player: Addr,
+    pub nonce: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SessionResponse {
+    pub key: Addr,
+    pub session: Option<Session>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ReferralStatsResponse {
+    pub referrer: Addr,
+    /// players that registered the referrer
+    pub players: Vec<Addr>,
+    /// stakes of referred players in settled wagers
+    pub volume: GenericBalance,
+    /// rewards credited to the referrer, claimed or not
+    pub earnings: GenericBalance,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PlayerStatsResponse {
+    pub player: Addr,
+    pub wins: u32,
+    /// forfeits included
+    pub losses: u32,
+    pub forfeits: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ClaimableResponse {
+    pub address: Addr,
+    pub claimable: GenericBalance,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct JackpotResponse {
+    /// jackpot waiting to be won
+    pub jackpot: GenericBalance,
+    pub wins: Vec<JackpotWin>,
+}
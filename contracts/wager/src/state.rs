@@ -1,16 +1,33 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Coin, Order, StdError, StdResult, Storage};
-use cw_storage_plus::Map;
+use cosmwasm_std::to_vec;
+use cosmwasm_std::{
+    Addr, Binary, Coin, Empty, Order, OverflowError, OverflowOperation, StdError, StdResult,
+    Storage, Timestamp, Uint128,
+};
+use cw_storage_plus::{Item, Map, U64Key};
+use std::convert::TryInto;
+use std::fmt;
 
 use cosmwasm_storage::{singleton, singleton_read, ReadonlySingleton, Singleton};
-use cw20::{Balance, Cw20CoinVerified};
+use cw20::{Balance, Cw20CoinVerified, Denom, Expiration};
+
+use crate::msg::TimelockedUpdate;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct GenericBalance {
     pub native: Vec<Coin>,
     pub cw20: Vec<Cw20CoinVerified>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cw721: Vec<Nft>,
+}
+
+/// NFT of a cw721 contract. NFTs are held whole, they are never split or taken as fees.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, JsonSchema, Debug)]
+pub struct Nft {
+    pub contract: Addr,
+    pub token_id: String,
 }
 
 pub static CONFIG_KEY: &[u8] = b"config";
@@ -19,13 +36,125 @@ pub static CONFIG_KEY: &[u8] = b"config";
 pub struct State {
     pub creator: Addr,
     pub owner: Addr,
+    /// Price source used to value stakes set in the reference unit
+    pub oracle: Option<Addr>,
+    /// How far, in basis points, a deposit's value may be from a valued stake
+    pub price_tolerance_bps: u64,
+    /// Platform fee taken from each side of a settled pot, in basis points
+    pub fee_bps: u64,
+    /// Share of the fee on a referred player's side paid to their referrer, in basis points
+    pub referral_share_bps: u64,
+    /// Share of the owner's part of the fee added to the jackpot, in basis points
+    pub jackpot_share_bps: u64,
+    /// A settled wager wins the jackpot with a chance of one in this many, never if 0
+    pub jackpot_odds: u64,
+    /// Source of the randomness jackpot draws use, no draws if not set
+    pub randomness: Option<RandomnessSource>,
+    /// New wagers, deposits and bets are refused while paused, wagers can still end
+    pub paused: bool,
+    /// Wagers whose stakes reach the amount of any of these tokens are settled by arbiter
+    /// votes instead of SendFunds, no threshold if empty
+    pub approval_threshold: GenericBalance,
+    /// Number of arbiters that must vote for the same winner to settle such a wager
+    pub required_approvals: u32,
+    /// Seconds a proposed configuration change waits before it can be executed, at most
+    /// MAX_CONFIG_DELAY, changes are applied at once by UpdateConfig if 0
+    pub config_delay: u64,
+    /// What happens to the matched wagers of a blocked player that are force-cancelled
+    pub blocked_policy: BlockedPolicy,
+}
+
+/// Privileged role. Owners manage the configuration and roles, operators the relayer
+/// whitelist and player ratings, arbiters settle wagers and guardians pause the contract.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Owner,
+    Operator,
+    Arbiter,
+    Guardian,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Owner => "owner",
+            Role::Operator => "operator",
+            Role::Arbiter => "arbiter",
+            Role::Guardian => "guardian",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Holders of each role, keyed by role name and address
+pub const ROLES: Map<(&str, &Addr), Empty> = Map::new("role");
+
+/// Returns true if the address holds the role. Owners hold every role.
+pub fn has_role(storage: &dyn Storage, role: &Role, address: &Addr) -> StdResult<bool> {
+    Ok(ROLES
+        .may_load(storage, (Role::Owner.as_str(), address))?
+        .is_some()
+        || ROLES.may_load(storage, (role.as_str(), address))?.is_some())
+}
+
+/// Arbiters of a single game, keyed by game id and address
+pub const GAME_ARBITERS: Map<(&str, &Addr), Empty> = Map::new("game_arbiter");
+
+/// Returns true if the address can settle the wagers of the game, as an arbiter of the
+/// contract or of that game
+pub fn is_arbiter_of(
+    storage: &dyn Storage,
+    game_id: Option<&str>,
+    address: &Addr,
+) -> StdResult<bool> {
+    Ok(has_role(storage, &Role::Arbiter, address)?
+        || game_id.is_some_and(|game_id| GAME_ARBITERS.has(storage, (game_id, address))))
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RandomnessSource {
+    /// Randomness beacon contract queried at settlement
+    Beacon { contract: Addr },
+    /// The owner, and the players if they wish, commit the hash of a secret for a wager before
+    /// it is settled and reveal the secret after, the secrets are mixed with entropy of the
+    /// settlement block
+    CommitReveal {},
+}
+
+/// Outcome of a force-cancelled matched wager of a blocked player
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockedPolicy {
+    /// The opponent wins the pot, both stakes are refunded if both players are blocked
+    Forfeit,
+    /// Both stakes are refunded
+    Refund,
 }
 
-pub fn config(storage: &mut dyn Storage) -> Singleton<State> {
+impl BlockedPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockedPolicy::Forfeit => "forfeit",
+            BlockedPolicy::Refund => "refund",
+        }
+    }
+}
+
+/// Tolerance of valued stakes until the owner sets one, 1%
+pub const DEFAULT_PRICE_TOLERANCE_BPS: u64 = 100;
+
+pub fn config(storage: &mut dyn Storage) -> Singleton<'_, State> {
     singleton(storage, CONFIG_KEY)
 }
 
-pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<State> {
+pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<'_, State> {
     singleton_read(storage, CONFIG_KEY)
 }
 
@@ -34,10 +163,30 @@ impl GenericBalance {
         GenericBalance {
             native: vec![],
             cw20: vec![],
+            cw721: vec![],
         }
     }
 }
 
+impl From<Balance> for GenericBalance {
+    fn from(balance: Balance) -> GenericBalance {
+        let mut generic_balance = match balance {
+            Balance::Native(balance) => GenericBalance {
+                native: balance.0,
+                cw20: vec![],
+                cw721: vec![],
+            },
+            Balance::Cw20(token) => GenericBalance {
+                native: vec![],
+                cw20: vec![token],
+                cw721: vec![],
+            },
+        };
+        generic_balance.normalize();
+        generic_balance
+    }
+}
+
 impl GenericBalance {
     pub fn add_tokens(&mut self, add: Balance) {
         match add {
@@ -70,9 +219,306 @@ impl GenericBalance {
                 }
             }
         };
+        self.normalize();
+    }
+
+    /// Sorts the tokens by denom and address, merges duplicates and drops zero amounts,
+    /// so balances holding the same tokens compare equal.
+    pub fn normalize(&mut self) {
+        self.native.sort_by(|a, b| a.denom.cmp(&b.denom));
+        self.native.dedup_by(|next, kept| {
+            let duplicate = next.denom == kept.denom;
+            if duplicate {
+                kept.amount += next.amount;
+            }
+            duplicate
+        });
+        self.native.retain(|token| !token.amount.is_zero());
+
+        self.cw20.sort_by(|a, b| a.address.cmp(&b.address));
+        self.cw20.dedup_by(|next, kept| {
+            let duplicate = next.address == kept.address;
+            if duplicate {
+                kept.amount += next.amount;
+            }
+            duplicate
+        });
+        self.cw20.retain(|token| !token.amount.is_zero());
+
+        self.cw721.sort();
+        self.cw721.dedup();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.native.iter().all(|token| token.amount.is_zero())
+            && self.cw20.iter().all(|token| token.amount.is_zero())
+            && self.cw721.is_empty()
+    }
+
+    /// Returns true if this balance holds at least the amount of every token and every NFT of
+    /// the other
+    pub fn covers(&self, other: &GenericBalance) -> bool {
+        other.native.iter().all(|token| {
+            self.native
+                .iter()
+                .any(|exist| exist.denom == token.denom && exist.amount >= token.amount)
+                || token.amount.is_zero()
+        }) && other.cw20.iter().all(|token| {
+            self.cw20
+                .iter()
+                .any(|exist| exist.address == token.address && exist.amount >= token.amount)
+                || token.amount.is_zero()
+        }) && other.cw721.iter().all(|nft| self.cw721.contains(nft))
+    }
+
+    /// Returns true if this balance holds at least the amount of any non-zero token of the other
+    pub fn reaches_any(&self, other: &GenericBalance) -> bool {
+        other.native.iter().any(|token| {
+            !token.amount.is_zero()
+                && self
+                    .native
+                    .iter()
+                    .any(|exist| exist.denom == token.denom && exist.amount >= token.amount)
+        }) || other.cw20.iter().any(|token| {
+            !token.amount.is_zero()
+                && self
+                    .cw20
+                    .iter()
+                    .any(|exist| exist.address == token.address && exist.amount >= token.amount)
+        })
+    }
+
+    /// Returns the balance with every token multiplied by numerator / denominator, rounded
+    /// down. NFTs cannot be split and are left out.
+    pub fn scale<N: Into<u128> + Copy, D: Into<u128> + Copy>(
+        &self,
+        numerator: N,
+        denominator: D,
+    ) -> GenericBalance {
+        let mut scaled = GenericBalance {
+            native: self
+                .native
+                .iter()
+                .map(|token| Coin {
+                    denom: token.denom.clone(),
+                    amount: token.amount.multiply_ratio(numerator, denominator),
+                })
+                .collect(),
+            cw20: self
+                .cw20
+                .iter()
+                .map(|token| Cw20CoinVerified {
+                    address: token.address.clone(),
+                    amount: token.amount.multiply_ratio(numerator, denominator),
+                })
+                .collect(),
+            cw721: vec![],
+        };
+        scaled.normalize();
+        scaled
+    }
+
+    /// Returns a balance holding an amount of a single asset
+    pub fn from_asset(asset: &Denom, amount: Uint128) -> GenericBalance {
+        match asset {
+            Denom::Native(denom) => GenericBalance {
+                native: vec![Coin {
+                    denom: denom.clone(),
+                    amount,
+                }],
+                cw20: vec![],
+                cw721: vec![],
+            },
+            Denom::Cw20(address) => GenericBalance {
+                native: vec![],
+                cw20: vec![Cw20CoinVerified {
+                    address: address.clone(),
+                    amount,
+                }],
+                cw721: vec![],
+            },
+        }
+    }
+
+    /// Returns true if this balance holds more of any token than the cap of that token, tokens
+    /// without a cap are not limited
+    pub fn exceeds_any(&self, caps: &GenericBalance) -> bool {
+        self.native.iter().any(|token| {
+            caps.native
+                .iter()
+                .any(|cap| cap.denom == token.denom && token.amount > cap.amount)
+        }) || self.cw20.iter().any(|token| {
+            caps.cw20
+                .iter()
+                .any(|cap| cap.address == token.address && token.amount > cap.amount)
+        })
+    }
+
+    /// Returns the caps of both balances, the lower amount for tokens capped by both
+    pub fn tightest(&self, other: &GenericBalance) -> GenericBalance {
+        let mut caps = self.clone();
+        for token in &other.native {
+            match caps.native.iter_mut().find(|cap| cap.denom == token.denom) {
+                Some(cap) => cap.amount = cap.amount.min(token.amount),
+                None => caps.native.push(token.clone()),
+            }
+        }
+        for token in &other.cw20 {
+            match caps
+                .cw20
+                .iter_mut()
+                .find(|cap| cap.address == token.address)
+            {
+                Some(cap) => cap.amount = cap.amount.min(token.amount),
+                None => caps.cw20.push(token.clone()),
+            }
+        }
+        caps.normalize_caps();
+        caps
+    }
+
+    /// Sorts caps by denom and address and keeps the lowest of duplicates. Unlike normalize,
+    /// zero amounts are kept: a cap of zero forbids staking the token.
+    pub fn normalize_caps(&mut self) {
+        self.native
+            .sort_by(|a, b| a.denom.cmp(&b.denom).then(a.amount.cmp(&b.amount)));
+        self.native.dedup_by(|next, kept| next.denom == kept.denom);
+        self.cw20
+            .sort_by(|a, b| a.address.cmp(&b.address).then(a.amount.cmp(&b.amount)));
+        self.cw20
+            .dedup_by(|next, kept| next.address == kept.address);
+        self.cw721.clear();
+    }
+
+    /// Returns the asset and amount of a balance made of exactly one token
+    pub fn single_asset(&self) -> Option<(Denom, Uint128)> {
+        match (
+            self.native.as_slice(),
+            self.cw20.as_slice(),
+            self.cw721.as_slice(),
+        ) {
+            ([token], [], []) => Some((Denom::Native(token.denom.clone()), token.amount)),
+            ([], [token], []) => Some((Denom::Cw20(token.address.clone()), token.amount)),
+            _ => None,
+        }
+    }
+
+    /// Returns a balance holding a single NFT
+    pub fn from_nft(nft: Nft) -> GenericBalance {
+        GenericBalance {
+            native: vec![],
+            cw20: vec![],
+            cw721: vec![nft],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum WagerStatus {
+    /// The creator is still depositing the tokens of a basket stake
+    Funding,
+    /// Waiting for a second player
+    Open,
+    /// Both players funded the wager, waiting for the arbiter
+    Matched,
+    /// The stakes were refunded
+    Cancelled,
+    /// The pot was sent to the winner
+    Settled,
+    /// The wager ran out of time and the stakes were refunded
+    Expired,
+    /// Arbiters voted for different winners, waiting for an owner to settle
+    Disputed,
+}
+
+impl fmt::Display for WagerStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            WagerStatus::Funding => "funding",
+            WagerStatus::Open => "open",
+            WagerStatus::Matched => "matched",
+            WagerStatus::Cancelled => "cancelled",
+            WagerStatus::Settled => "settled",
+            WagerStatus::Expired => "expired",
+            WagerStatus::Disputed => "disputed",
+        };
+        write!(f, "{}", status)
+    }
+}
+
+impl GenericBalance {
+    /// Adds every token and NFT of another balance
+    pub fn add_balance(&mut self, add: &GenericBalance) {
+        if !add.native.is_empty() {
+            self.add_tokens(Balance::from(add.native.clone()));
+        }
+        for token in &add.cw20 {
+            self.add_tokens(Balance::from(token.clone()));
+        }
+        if !add.cw721.is_empty() {
+            self.cw721.extend(add.cw721.iter().cloned());
+            self.normalize();
+        }
+    }
+
+    /// Removes every token and NFT of another balance, tokens that reach zero are dropped.
+    /// Errors without changing the balance if a token would go below zero or an NFT is missing.
+    pub fn sub_balance(&mut self, sub: &GenericBalance) -> StdResult<()> {
+        let mut native = self.native.clone();
+        for token in &sub.native {
+            let exist = native
+                .iter_mut()
+                .find(|exist| exist.denom == token.denom)
+                .ok_or_else(|| {
+                    StdError::overflow(OverflowError::new(OverflowOperation::Sub, 0, token.amount))
+                })?;
+            exist.amount = exist.amount.checked_sub(token.amount)?;
+        }
+        let mut cw20 = self.cw20.clone();
+        for token in &sub.cw20 {
+            let exist = cw20
+                .iter_mut()
+                .find(|exist| exist.address == token.address)
+                .ok_or_else(|| {
+                    StdError::overflow(OverflowError::new(OverflowOperation::Sub, 0, token.amount))
+                })?;
+            exist.amount = exist.amount.checked_sub(token.amount)?;
+        }
+        let mut cw721 = self.cw721.clone();
+        for nft in &sub.cw721 {
+            let index = cw721
+                .iter()
+                .position(|exist| exist == nft)
+                .ok_or_else(|| StdError::not_found(format!("NFT {}", nft.token_id)))?;
+            cw721.remove(index);
+        }
+        self.native = native;
+        self.cw20 = cw20;
+        self.cw721 = cw721;
+        self.normalize();
+        Ok(())
     }
 }
 
+/// Sum of every balance held for players: open wagers, queued stakes and anything else that
+/// is owed. Contract holdings must always cover it.
+pub const TOTAL_ESCROWED: Item<GenericBalance> = Item::new("total_escrowed");
+
+/// Records tokens entering the escrow
+pub fn escrow_deposit(storage: &mut dyn Storage, balance: &GenericBalance) -> StdResult<()> {
+    let mut total = TOTAL_ESCROWED.may_load(storage)?.unwrap_or_default();
+    total.add_balance(balance);
+    TOTAL_ESCROWED.save(storage, &total)
+}
+
+/// Records tokens leaving the escrow
+pub fn escrow_withdraw(storage: &mut dyn Storage, balance: &GenericBalance) -> StdResult<()> {
+    let mut total = TOTAL_ESCROWED.may_load(storage)?.unwrap_or_default();
+    total.sub_balance(balance)?;
+    TOTAL_ESCROWED.save(storage, &total)
+}
+
 // DUEL DOJO CODE BELOW TODO: remove above
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Wager {
@@ -98,6 +544,127 @@ pub struct Wager {
     // /// All possible contracts that we accept tokens from
     // pub cw20_whitelist: Vec<Addr>, // TODO: WHITELIST?
     // WHITELIST? -> Only DUEL tokens available for wager
+    /// Game the wager was matched for, set when the wager comes from the matchmaking queue
+    pub game_id: Option<String>,
+    /// Optional label chosen by the creator, the wager itself is referenced by its generated id
+    pub label: Option<String>,
+    /// Stake each player puts up, possibly a basket deposited in several steps.
+    /// Empty for valued stakes, where any tokens worth stake_value can be deposited.
+    pub stake: GenericBalance,
+    /// Value of the stake in the oracle's reference unit, deposits are valued with the oracle
+    /// and must match it within the configured tolerance
+    pub stake_value: Option<Uint128>,
+    /// Funding until the creator deposited the whole stake, open until a second player
+    /// deposited it too, then matched
+    pub status: WagerStatus,
+    /// Game of chance settled by the contract from the players' revealed choices, without
+    /// the arbiter
+    pub chance: Option<ChanceGame>,
+    /// Best-of-N series whose rounds are reported by the arbiter, the pot is released once a
+    /// player won the majority
+    pub series: Option<Series>,
+    /// Only this player can join the wager, anyone can if not set
+    pub opponent: Option<Addr>,
+    /// King-of-the-hill wager whose pot, less fees, stays escrowed as the stake of the winner
+    /// for the next challenger, in a new wager, instead of being paid out
+    pub hill: Option<Hill>,
+    /// Player of a matched wager who proposed to cancel it, the stakes are refunded once the
+    /// other player accepts
+    pub cancel_proposed_by: Option<Addr>,
+    /// Players who asked to keep their winnings as claimable tokens to fund a rematch
+    pub rematch_requested_by: Vec<Addr>,
+    /// Stake the second player puts up when it differs from the creator's, like an NFT against
+    /// tokens. Fixed at creation, the second player matches the creator's stake if not set.
+    pub counter_stake: Option<GenericBalance>,
+}
+
+impl Wager {
+    /// Whether the players can play again once settled, games of chance need new commitments,
+    /// hills go on by themselves and NFTs changed hands
+    pub fn can_rematch(&self) -> bool {
+        self.chance.is_none() && self.hill.is_none() && self.counter_stake.is_none()
+    }
+
+    /// Stake the second player must deposit
+    pub fn user2_stake(&self) -> &GenericBalance {
+        self.counter_stake.as_ref().unwrap_or(&self.stake)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Hill {
+    /// matches in a row won by the champion, user1, since they took the hill
+    pub streak: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Series {
+    /// number of rounds, odd so that a player always reaches the majority
+    pub length: u32,
+    /// winner of each reported round, in order
+    pub rounds: Vec<Addr>,
+}
+
+impl Series {
+    /// Rounds won by the creator and by the opponent
+    pub fn wins(&self, user1: &Addr) -> (u32, u32) {
+        let user1_wins = self.rounds.iter().filter(|winner| *winner == user1).count() as u32;
+        (user1_wins, self.rounds.len() as u32 - user1_wins)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ChanceGameKind {
+    /// The creator calls heads (0) or tails (1) and wins if the coin lands on it
+    CoinFlip,
+    /// Each player rolls a die, the higher roll wins and equal rolls are a draw. Choices are 0.
+    Dice,
+    /// Rock (0), paper (1) or scissors (2), equal choices are a draw
+    RockPaperScissors,
+}
+
+impl ChanceGameKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChanceGameKind::CoinFlip => "coin_flip",
+            ChanceGameKind::Dice => "dice",
+            ChanceGameKind::RockPaperScissors => "rock_paper_scissors",
+        }
+    }
+
+    /// Number of choices a player has, choices go from 0 to this number excluded
+    pub fn choices(&self) -> u8 {
+        match self {
+            ChanceGameKind::CoinFlip => 2,
+            ChanceGameKind::Dice => 1,
+            ChanceGameKind::RockPaperScissors => 3,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ChanceGame {
+    pub kind: ChanceGameKind,
+    /// SHA-256 hash of the creator's choice byte followed by their salt
+    pub user1_commitment: Binary,
+    /// SHA-256 hash of the opponent's choice byte followed by their salt
+    pub user2_commitment: Option<Binary>,
+    /// blocks the players have to reveal once the wager is matched
+    pub reveal_period: u64,
+    /// block height after which a player who did not reveal forfeits, set on match
+    pub reveal_deadline: Option<u64>,
+    pub user1_reveal: Option<Reveal>,
+    pub user2_reveal: Option<Reveal>,
+}
+
+/// Longest reveal period of a game of chance, about a week of blocks
+pub const MAX_REVEAL_PERIOD: u64 = 100_800;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Reveal {
+    pub choice: u8,
+    pub salt: Binary,
 }
 
 impl Wager {
@@ -122,24 +689,548 @@ impl Wager {
     // } // TODO: uncomment if we implement end time and human whitelist
 }
 
-pub const WAGERS: Map<&str, Wager> = Map::new("wager");
+pub const WAGERS: Map<U64Key, Wager> = Map::new("wager");
+
+/// Terms of a settled wager that its players can play again
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Rematch {
+    pub arbiter: Addr,
+    pub user1: Addr,
+    pub user2: Addr,
+    pub game_id: Option<String>,
+    pub stake: GenericBalance,
+    pub stake_value: Option<Uint128>,
+    /// rounds of a series, the rematch starts a new series
+    pub series_length: Option<u32>,
+    /// block height after which the rematch can no longer be offered
+    pub expires: u64,
+}
+
+/// Blocks, about a day, during which the players of a settled wager can offer a rematch
+pub const REMATCH_PERIOD: u64 = 14_400;
+
+/// Rematch terms of settled wagers, keyed by the id of the settled wager, until a player
+/// offers the rematch or a newer wager of the same players replaces it
+pub const REMATCHES: Map<U64Key, Rematch> = Map::new("rematch");
+
+/// Settled wager whose rematch each pair of players can offer, keyed by the players in
+/// ascending order. A newer settlement between the same players replaces the older rematch.
+pub const REMATCH_PAIRS: Map<(&Addr, &Addr), u64> = Map::new("rematch_pair");
+
+/// Key of a pair of players in REMATCH_PAIRS
+pub fn rematch_pair<'a>(user1: &'a Addr, user2: &'a Addr) -> (&'a Addr, &'a Addr) {
+    if user1 <= user2 {
+        (user1, user2)
+    } else {
+        (user2, user1)
+    }
+}
+
+/// Last wager id handed out, ids are assigned by the contract in increasing order
+pub const WAGER_SEQ: Item<u64> = Item::new("wager_seq");
+
+/// Increments the wager sequence and returns the id for a new wager
+pub fn next_wager_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let id = WAGER_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    WAGER_SEQ.save(storage, &id)?;
+    Ok(id)
+}
 
 /// This returns the list of ids for all registered escrows
-pub fn all_wager_ids(storage: &dyn Storage) -> StdResult<Vec<String>> {
+pub fn all_wager_ids(storage: &dyn Storage) -> StdResult<Vec<u64>> {
     WAGERS
         .keys(storage, None, None, Order::Ascending)
-        .map(|k| String::from_utf8(k).map_err(|_| StdError::invalid_utf8("parsing escrow key")))
+        .map(|k| {
+            k.as_slice()
+                .try_into()
+                .map(u64::from_be_bytes)
+                .map_err(|_| StdError::parse_err("u64", "parsing escrow key"))
+        })
         .collect()
 }
 
-//TODO: Add tests for all_wager_ids.
+/// Rating given to players that have not been rated by an operator yet
+pub const DEFAULT_RATING: u32 = 1200;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct QueueEntry {
+    /// player waiting for an opponent
+    pub player: Addr,
+    /// stake escrowed by the player while waiting, an opponent must put up the same stake
+    pub stake: Balance,
+    /// rating of the player when joining the queue
+    pub rating: u32,
+    /// largest rating difference the player accepts from an opponent, any opponent if not set
+    pub max_rating_diff: Option<u32>,
+    /// block height the player joined the queue at
+    pub joined_at: u64,
+    /// position of the entry among all queue joins, older entries are matched first
+    pub seq: u64,
+}
+
+impl QueueEntry {
+    /// Returns true if both players accept each other's rating
+    pub fn accepts(&self, other: &QueueEntry) -> bool {
+        let diff = self.rating.abs_diff(other.rating);
+        self.max_rating_diff.is_none_or(|max| diff <= max)
+            && other.max_rating_diff.is_none_or(|max| diff <= max)
+    }
+
+    /// Groups the entries whose stakes can be matched with each other in QUEUE_ORDER
+    pub fn stake_key(&self) -> StdResult<Vec<u8>> {
+        to_vec(&self.stake)
+    }
+}
+
+/// Players waiting for an opponent, keyed by game id and player address
+pub const QUEUE: Map<(&str, &Addr), QueueEntry> = Map::new("queue");
+
+/// Players waiting for an opponent in the order they joined, keyed by game id, stake key and
+/// position, so that a join only reads the oldest entries with its stake
+pub const QUEUE_ORDER: Map<(&str, &[u8], U64Key), Addr> = Map::new("queue_order");
+
+/// Last position handed out to a queue entry
+pub const QUEUE_SEQ: Item<u64> = Item::new("queue_seq");
+
+/// Waiting players with the same stake a join looks at for an opponent, oldest first
+pub const MAX_QUEUE_SCAN: usize = 30;
+
+/// Player ratings used for skill-bracketed matchmaking, set by operators
+pub const RATINGS: Map<&Addr, u32> = Map::new("rating");
+
+/// What to do with CW20 tokens pulled with TransferFrom once they are received
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingAction {
+    CreateWager {
+        label: Option<String>,
+        stake: Option<GenericBalance>,
+        stake_value: Option<Uint128>,
+        series_length: Option<u32>,
+    },
+    JoinWager {
+        wager_id: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PendingTransfer {
+    /// player the tokens are pulled from
+    pub sender: Addr,
+    /// token contract
+    pub token: Addr,
+    /// amount the player allowed and must be received
+    pub amount: Uint128,
+    /// balance of the contract before the transfer
+    pub held_before: Uint128,
+    pub action: PendingAction,
+}
+
+/// TransferFrom in flight, saved before the submessage and removed in its reply
+pub const PENDING_TRANSFER: Item<PendingTransfer> = Item::new("pending_transfer");
+
+/// Relayers allowed to submit signed intents, managed by the owner
+pub const RELAYERS: Map<&Addr, Empty> = Map::new("relayer");
+
+/// Banned or sanctioned addresses, which cannot create, join or fund wagers, managed by
+/// operators
+pub const BLOCKLIST: Map<&Addr, Empty> = Map::new("blocked");
+
+/// Public keys players sign intents with
+pub const RELAY_KEYS: Map<&Addr, Binary> = Map::new("relay_key");
+
+/// Next intent nonce of each player, bumped by every relayed intent to prevent replays
+pub const NONCES: Map<&Addr, u64> = Map::new("nonce");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Session {
+    /// player the session key acts for, stakes are pulled from and refunded to this address
+    pub player: Addr,
+    /// the session key is refused once expired
+    pub expires: Expiration,
+    /// largest stake the session key can put up in a wager, any stake if not set
+    pub max_stake: Option<GenericBalance>,
+    /// the key acts for the player only once it accepted the grant
+    pub accepted: bool,
+}
+
+/// Session keys granted by players, keyed by the session key address
+pub const SESSIONS: Map<&Addr, Session> = Map::new("session");
+
+/// Tokens owed to an address and paid out on claim: platform fees for the owner and referral
+/// rewards for referrers. Claimable tokens stay in the escrow total until claimed.
+pub const CLAIMABLE: Map<&Addr, GenericBalance> = Map::new("claimable");
+
+/// Records tokens owed to an address
+pub fn credit_claimable(
+    storage: &mut dyn Storage,
+    owner: &Addr,
+    balance: &GenericBalance,
+) -> StdResult<()> {
+    if balance.is_empty() {
+        return Ok(());
+    }
+    let mut claimable = CLAIMABLE.may_load(storage, owner)?.unwrap_or_default();
+    claimable.add_balance(balance);
+    CLAIMABLE.save(storage, owner, &claimable)
+}
+
+/// Referrer of each player, set once by the player
+pub const REFERRERS: Map<&Addr, Addr> = Map::new("referrer");
+
+/// Players each referrer brought in, keyed by referrer and player
+pub const REFERRED: Map<(&Addr, &Addr), Empty> = Map::new("referred");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct ReferralStats {
+    /// stakes of referred players in settled wagers
+    pub volume: GenericBalance,
+    /// rewards credited to the referrer
+    pub earnings: GenericBalance,
+}
+
+pub const REFERRAL_STATS: Map<&Addr, ReferralStats> = Map::new("referral_stats");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct PlayerStats {
+    /// settled wagers the player won
+    pub wins: u32,
+    /// settled wagers the player lost, forfeits included
+    pub losses: u32,
+    /// wagers the player conceded or lost by not playing on
+    pub forfeits: u32,
+}
+
+/// Outcomes of the settled wagers of each player, draws and refunds excluded
+pub const PLAYER_STATS: Map<&Addr, PlayerStats> = Map::new("player_stats");
+
+/// Progressive jackpot funded from fees, in every asset it received. It stays in the escrow
+/// total until won.
+pub const JACKPOT: Item<GenericBalance> = Item::new("jackpot");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct JackpotWin {
+    /// wager whose settlement won the jackpot
+    pub wager_id: u64,
+    /// winner of the wager, who received the jackpot
+    pub winner: Addr,
+    pub amount: GenericBalance,
+    /// block height of the draw
+    pub height: u64,
+}
+
+/// Jackpots won, keyed by wager id
+pub const JACKPOT_WINS: Map<U64Key, JackpotWin> = Map::new("jackpot_win");
+
+/// Hash of the owner's secret for the commit-reveal draw of a wager, keyed by wager id. Wagers
+/// without one are not drawn.
+pub const OWNER_COMMITMENTS: Map<U64Key, Binary> = Map::new("owner_commitment");
+
+/// Hash of a player's secret for the commit-reveal draw of a wager, keyed by wager id and
+/// player. Only the winner's is used.
+pub const PLAYER_COMMITMENTS: Map<(U64Key, &Addr), Binary> = Map::new("player_commitment");
+
+/// Blocks, about a day, the secrets of a commit-reveal draw can be revealed in after settlement
+pub const JACKPOT_REVEAL_PERIOD: u64 = 14_400;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PendingDraw {
+    /// winner of the settled wager, who receives the jackpot if the draw wins
+    pub winner: Addr,
+    /// hash of the secret the owner must reveal
+    pub commitment: Binary,
+    /// hash of the secret the winner must reveal, if they committed one
+    pub player_commitment: Option<Binary>,
+    /// secret revealed by the owner
+    pub secret: Option<Binary>,
+    /// secret revealed by the winner
+    pub player_secret: Option<Binary>,
+    /// entropy of the settlement block mixed with the secrets
+    pub entropy: Binary,
+    /// block height after which the draw goes against whoever has not revealed: the winner
+    /// loses it, or else wins it from the owner
+    pub deadline: u64,
+}
+
+impl PendingDraw {
+    /// Whether every committed secret was revealed
+    pub fn is_revealed(&self) -> bool {
+        self.secret.is_some() && (self.player_commitment.is_none() || self.player_secret.is_some())
+    }
+}
+
+/// Commit-reveal draws of settled wagers waiting for the secrets, keyed by wager id
+pub const PENDING_DRAWS: Map<U64Key, PendingDraw> = Map::new("pending_draw");
+
+/// Side of a wager a spectator bets on
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    User1,
+    User2,
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = match self {
+            Side::User1 => "user1",
+            Side::User2 => "user2",
+        };
+        write!(f, "{}", side)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SidePoolStatus {
+    /// Spectators can place bets
+    Open,
+    /// The arbiter locked the pool, waiting for the wager to end
+    Locked,
+    /// The wager was settled, the bets on the winning side share the pool
+    Won { side: Side },
+    /// The wager ended without a winner or one side had no bets, every bet is refunded
+    Refunded,
+}
+
+impl fmt::Display for SidePoolStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            SidePoolStatus::Open => "open",
+            SidePoolStatus::Locked => "locked",
+            SidePoolStatus::Won { .. } => "won",
+            SidePoolStatus::Refunded => "refunded",
+        };
+        write!(f, "{}", status)
+    }
+}
+
+/// Parimutuel pool of the spectator bets on a matched wager
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SidePool {
+    pub status: SidePoolStatus,
+    /// asset of the bets, set by the first bet
+    pub asset: Option<Denom>,
+    /// amount bet on the creator
+    pub user1_total: Uint128,
+    /// amount bet on the opponent
+    pub user2_total: Uint128,
+    /// amount the winning bets share once settled, the pool less the fee
+    pub payout: Uint128,
+    /// part of the pool still escrowed for bettors
+    pub unclaimed: Uint128,
+}
+
+impl SidePool {
+    pub fn new() -> SidePool {
+        SidePool {
+            status: SidePoolStatus::Open,
+            asset: None,
+            user1_total: Uint128::zero(),
+            user2_total: Uint128::zero(),
+            payout: Uint128::zero(),
+            unclaimed: Uint128::zero(),
+        }
+    }
+}
+
+impl Default for SidePool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Side pools, keyed by wager id. They outlive their wager until every bet is claimed.
+pub const SIDE_POOLS: Map<U64Key, SidePool> = Map::new("side_pool");
+
+/// Amounts a spectator bet on each side of a wager
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct SideBet {
+    pub user1: Uint128,
+    pub user2: Uint128,
+}
+
+/// Spectator bets, keyed by wager id and bettor
+pub const SIDE_BETS: Map<(U64Key, &Addr), SideBet> = Map::new("side_bet");
+
+/// Vote of an arbiter for the winner of a wager
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Vote {
+    pub arbiter: Addr,
+    pub winner: Addr,
+}
+
+/// Arbiter votes on wagers settled by approvals, keyed by wager id like the wagers
+pub const VOTES: Map<U64Key, Vec<Vote>> = Map::new("vote");
+
+/// Configuration, role, relayer or blocklist change waiting for its timelock
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ConfigChange {
+    pub id: u64,
+    pub update: TimelockedUpdate,
+    /// block time from which the change can be executed
+    pub eta: Timestamp,
+}
+
+/// Pending configuration changes, keyed by id
+pub const CONFIG_CHANGES: Map<U64Key, ConfigChange> = Map::new("config_change");
+
+/// Id of the last proposed configuration change
+pub const CONFIG_CHANGE_SEQ: Item<u64> = Item::new("config_change_seq");
+
+/// Increments the configuration change sequence and returns the id for a new change
+pub fn next_config_change_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let id = CONFIG_CHANGE_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    CONFIG_CHANGE_SEQ.save(storage, &id)?;
+    Ok(id)
+}
+
+/// Most a player can stake per token over rolling windows, tokens not listed are not limited
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct StakeLimits {
+    pub daily: GenericBalance,
+    pub weekly: GenericBalance,
+    pub monthly: GenericBalance,
+}
+
+impl StakeLimits {
+    pub fn normalize(&mut self) {
+        self.daily.normalize_caps();
+        self.weekly.normalize_caps();
+        self.monthly.normalize_caps();
+    }
+
+    /// Returns the limits of both, the lower cap of every token in every window
+    pub fn tightest(&self, other: &StakeLimits) -> StakeLimits {
+        StakeLimits {
+            daily: self.daily.tightest(&other.daily),
+            weekly: self.weekly.tightest(&other.weekly),
+            monthly: self.monthly.tightest(&other.monthly),
+        }
+    }
+
+    /// Returns the windows, as their length in days, with their caps
+    pub fn windows(&self) -> [(u64, &GenericBalance); 3] {
+        [
+            (1, &self.daily),
+            (7, &self.weekly),
+            (STAKE_HISTORY_DAYS, &self.monthly),
+        ]
+    }
+}
+
+/// Looser stake limits a player asked for, applied once the cool-down has passed
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PendingLimits {
+    pub limits: StakeLimits,
+    /// block time from which the limits apply
+    pub effective: Timestamp,
+}
+
+/// Responsible-gaming controls a player set on themselves
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct PlayerLimits {
+    pub limits: StakeLimits,
+    pub pending: Option<PendingLimits>,
+    /// the player cannot stake before this block time
+    pub excluded_until: Option<Timestamp>,
+}
+
+impl PlayerLimits {
+    /// Applies the pending limits once they are effective
+    pub fn refresh(&mut self, now: Timestamp) {
+        if self
+            .pending
+            .as_ref()
+            .is_some_and(|pending| now >= pending.effective)
+        {
+            self.limits = self.pending.take().unwrap().limits;
+        }
+    }
+
+    pub fn is_excluded(&self, now: Timestamp) -> bool {
+        self.excluded_until.is_some_and(|until| now < until)
+    }
+}
+
+pub const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Seconds before looser stake limits apply, 1 day
+pub const LIMIT_COOLDOWN: u64 = SECONDS_PER_DAY;
+
+/// Longest self-exclusion a player can ask for at once, 5 years
+pub const MAX_SELF_EXCLUSION: u64 = 5 * 365 * SECONDS_PER_DAY;
+
+/// Longest config delay the owner can set, 30 days
+pub const MAX_CONFIG_DELAY: u64 = 30 * SECONDS_PER_DAY;
+
+/// Stake limits and self-exclusions of players
+pub const PLAYER_LIMITS: Map<&Addr, PlayerLimits> = Map::new("player_limits");
+
+/// Stakes of a player over a day, counted in whole UTC days
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct StakeBucket {
+    /// days since the epoch
+    pub day: u64,
+    pub staked: GenericBalance,
+}
+
+/// Days of stakes kept for every player, the longest limit window
+pub const STAKE_HISTORY_DAYS: u64 = 30;
+
+/// Stakes of the last days of every player, oldest day first
+pub const STAKE_HISTORY: Map<&Addr, Vec<StakeBucket>> = Map::new("stake_history");
+
+/// Sums the stakes of the days of a window ending today
+pub fn staked_since(history: &[StakeBucket], today: u64, days: u64) -> GenericBalance {
+    let mut staked = GenericBalance::new();
+    for bucket in history.iter().filter(|bucket| bucket.day + days > today) {
+        staked.add_balance(&bucket.staked);
+    }
+    staked
+}
 
 #[cfg(test)]
 mod tests {
 
+    mod wagers {
+        use super::super::*;
+        use cosmwasm_std::testing::MockStorage;
+
+        #[test]
+        fn test_all_wager_ids() {
+            let mut storage = MockStorage::new();
+            let wager = Wager {
+                arbiter: Addr::unchecked("creator"),
+                user1: Addr::unchecked("new_user"),
+                user2: Addr::unchecked("empty"),
+                user1_balance: GenericBalance::new(),
+                user2_balance: GenericBalance::new(),
+                game_id: None,
+                label: None,
+                stake: GenericBalance::new(),
+                stake_value: None,
+                chance: None,
+                series: None,
+                opponent: None,
+                hill: None,
+                cancel_proposed_by: None,
+                rematch_requested_by: vec![],
+                counter_stake: None,
+                status: WagerStatus::Open,
+            };
+            for _ in 0..3 {
+                let id = next_wager_id(&mut storage).unwrap();
+                WAGERS.save(&mut storage, id.into(), &wager).unwrap();
+            }
+            WAGERS.remove(&mut storage, 2.into());
+            assert_eq!(vec![1, 3], all_wager_ids(&storage).unwrap());
+        }
+    }
+
     mod generic_balance {
         use super::super::*;
-        use cosmwasm_std::{coins, Uint128};
+        use cosmwasm_std::{coin, coins, Uint128};
         use cw20::Cw20CoinVerified;
 
         #[test]
@@ -148,6 +1239,7 @@ mod tests {
             let expected_generic_balance = GenericBalance {
                 native: vec![],
                 cw20: vec![],
+                cw721: vec![],
             };
             assert_eq!(expected_generic_balance, generic_balance);
         }
@@ -160,6 +1252,7 @@ mod tests {
             let expected_generic_balance = GenericBalance {
                 native: coins(10, "uluna"),
                 cw20: vec![],
+                cw721: vec![],
             };
             assert_eq!(expected_generic_balance, generic_balance);
         }
@@ -176,6 +1269,7 @@ mod tests {
             let expected_generic_balance = GenericBalance {
                 native: vec![],
                 cw20: vec![cw20],
+                cw721: vec![],
             };
             assert_eq!(expected_generic_balance, generic_balance);
         }
@@ -185,6 +1279,7 @@ mod tests {
             let mut generic_balance = GenericBalance {
                 native: coins(10, "uluna"),
                 cw20: vec![],
+                cw721: vec![],
             };
             let balance = Balance::from(coins(10, "uluna"));
             generic_balance.add_tokens(balance);
@@ -200,10 +1295,120 @@ mod tests {
             let mut generic_balance = GenericBalance {
                 native: vec![],
                 cw20: vec![cw20.clone()],
+                cw721: vec![],
             };
             let balance = Balance::from(cw20);
             generic_balance.add_tokens(balance);
             assert_eq!(Uint128::new(200), generic_balance.cw20[0].amount);
         }
+
+        #[test]
+        fn test_sub_balance() {
+            let cw20 = Cw20CoinVerified {
+                address: Addr::unchecked("cw20-token"),
+                amount: Uint128::new(100),
+            };
+            let mut generic_balance = GenericBalance {
+                native: vec![coin(10, "uluna"), coin(5, "uusd")],
+                cw20: vec![cw20.clone()],
+                cw721: vec![],
+            };
+            let sub = GenericBalance {
+                native: coins(5, "uusd"),
+                cw20: vec![cw20],
+                cw721: vec![],
+            };
+            generic_balance.sub_balance(&sub).unwrap();
+            assert_eq!(coins(10, "uluna"), generic_balance.native);
+            assert!(generic_balance.cw20.is_empty());
+
+            // an underflow leaves the balance untouched
+            let sub = GenericBalance {
+                native: vec![coin(5, "uluna"), coin(5, "uusd")],
+                cw20: vec![],
+                cw721: vec![],
+            };
+            assert!(generic_balance.sub_balance(&sub).is_err());
+            assert_eq!(coins(10, "uluna"), generic_balance.native);
+
+            generic_balance.add_balance(&sub);
+            assert_eq!(
+                vec![coin(15, "uluna"), coin(5, "uusd")],
+                generic_balance.native
+            );
+        }
+
+        #[test]
+        fn test_normalize() {
+            let token = |address: &str, amount: u128| Cw20CoinVerified {
+                address: Addr::unchecked(address),
+                amount: Uint128::new(amount),
+            };
+            let mut generic_balance = GenericBalance {
+                native: vec![
+                    coin(5, "uusd"),
+                    coin(0, "ukrw"),
+                    coin(10, "uluna"),
+                    coin(5, "uusd"),
+                ],
+                cw20: vec![
+                    token("token-b", 1),
+                    token("token-a", 2),
+                    token("token-b", 3),
+                ],
+                cw721: vec![],
+            };
+            generic_balance.normalize();
+            assert_eq!(
+                GenericBalance {
+                    native: vec![coin(10, "uluna"), coin(10, "uusd")],
+                    cw20: vec![token("token-a", 2), token("token-b", 4)],
+                    cw721: vec![],
+                },
+                generic_balance
+            );
+
+            // balances deposited in a different order compare equal
+            let mut other = GenericBalance::new();
+            other.add_tokens(Balance::from(token("token-b", 4)));
+            other.add_tokens(Balance::from(coins(10, "uusd")));
+            other.add_tokens(Balance::from(token("token-a", 2)));
+            other.add_tokens(Balance::from(coins(10, "uluna")));
+            assert_eq!(generic_balance, other);
+        }
+
+        #[test]
+        fn test_covers_and_scale() {
+            let generic_balance = GenericBalance {
+                native: vec![coin(10, "uluna"), coin(7, "uusd")],
+                cw20: vec![],
+                cw721: vec![],
+            };
+            assert!(generic_balance.covers(&GenericBalance {
+                native: coins(7, "uusd"),
+                cw20: vec![],
+                cw721: vec![],
+            }));
+            assert!(!generic_balance.covers(&GenericBalance {
+                native: coins(11, "uluna"),
+                cw20: vec![],
+                cw721: vec![],
+            }));
+            assert!(!generic_balance.covers(&GenericBalance {
+                native: vec![],
+                cw20: vec![Cw20CoinVerified {
+                    address: Addr::unchecked("cw20-token"),
+                    amount: Uint128::new(1),
+                }],
+                cw721: vec![],
+            }));
+
+            // rounded down, zero amounts are dropped
+            assert_eq!(
+                vec![coin(5, "uluna"), coin(3, "uusd")],
+                generic_balance.scale(1u64, 2u64).native
+            );
+            assert_eq!(coins(1, "uluna"), generic_balance.scale(1u64, 8u64).native);
+        }
     }
 }
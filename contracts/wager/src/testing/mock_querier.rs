@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, AllBalanceResponse, BalanceResponse, BankMsg, BankQuery,
+    Binary, Coin, ContractResult, CosmosMsg, Decimal, Empty, OwnedDeps, Querier, QuerierResult,
+    QueryRequest, Response, SubMsg, SystemError, SystemResult, Uint128, WasmMsg, WasmQuery,
+};
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Denom};
+
+use crate::msg::{BeaconQueryMsg, OracleQueryMsg, PriceResponse, RandomnessResponse};
+
+/// Address of the mock price oracle, see `WasmMockQuerier::set_price`
+pub const MOCK_ORACLE_ADDR: &str = "oracle";
+
+/// Address of the mock randomness beacon, see `WasmMockQuerier::set_randomness`
+pub const MOCK_BEACON_ADDR: &str = "beacon";
+
+/// Dependencies whose querier also answers CW20 balance queries and tracks the contract's
+/// holdings, see `WasmMockQuerier`.
+pub fn mock_dependencies_with_querier(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let mut querier = WasmMockQuerier::default();
+    querier.deposit_native(contract_balance);
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier,
+    }
+}
+
+/// Querier keeping the bank and CW20 holdings of the mock contract. Tests move tokens in with
+/// `deposit_native` and `deposit_cw20` and out with `apply_response`, which replays the
+/// transfers a response sends, so holdings follow what a chain would do. It also plays the
+/// price oracle at `MOCK_ORACLE_ADDR` and the randomness beacon at `MOCK_BEACON_ADDR`.
+#[derive(Default)]
+pub struct WasmMockQuerier {
+    native: Vec<Coin>,
+    // token contract -> holder -> balance
+    cw20: HashMap<String, HashMap<String, Uint128>>,
+    prices: Vec<(Denom, Decimal)>,
+    randomness: Binary,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+        match request {
+            QueryRequest::Bank(BankQuery::AllBalances { address }) => {
+                let amount = if address == MOCK_CONTRACT_ADDR {
+                    self.native.clone()
+                } else {
+                    vec![]
+                };
+                SystemResult::Ok(ContractResult::from(to_binary(&AllBalanceResponse {
+                    amount,
+                })))
+            }
+            QueryRequest::Bank(BankQuery::Balance { address, denom }) => {
+                let amount = self
+                    .native
+                    .iter()
+                    .find(|coin| address == MOCK_CONTRACT_ADDR && &coin.denom == denom)
+                    .map(|coin| coin.amount)
+                    .unwrap_or_default();
+                SystemResult::Ok(ContractResult::from(to_binary(&BalanceResponse {
+                    amount: Coin {
+                        denom: denom.clone(),
+                        amount,
+                    },
+                })))
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg })
+                if contract_addr == MOCK_ORACLE_ADDR =>
+            {
+                match from_binary(msg) {
+                    Ok(OracleQueryMsg::Price { asset }) => match self.price(&asset) {
+                        Some(price) => {
+                            SystemResult::Ok(ContractResult::from(to_binary(&PriceResponse {
+                                price,
+                            })))
+                        }
+                        None => SystemResult::Ok(ContractResult::Err("no price".to_string())),
+                    },
+                    Err(e) => SystemResult::Ok(ContractResult::Err(e.to_string())),
+                }
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg })
+                if contract_addr == MOCK_BEACON_ADDR =>
+            {
+                match from_binary(msg) {
+                    Ok(BeaconQueryMsg::Randomness {}) => {
+                        SystemResult::Ok(ContractResult::from(to_binary(&RandomnessResponse {
+                            randomness: self.randomness.clone(),
+                        })))
+                    }
+                    Err(e) => SystemResult::Ok(ContractResult::Err(e.to_string())),
+                }
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => match from_binary(msg) {
+                Ok(Cw20QueryMsg::Balance { address }) => {
+                    let balance = self.cw20_balance(contract_addr, &address);
+                    SystemResult::Ok(ContractResult::from(to_binary(&Cw20BalanceResponse {
+                        balance,
+                    })))
+                }
+                _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                    kind: format!("smart query to {}", contract_addr),
+                }),
+            },
+            _ => MockQuerier::<Empty>::new(&[]).handle_query(request),
+        }
+    }
+
+    pub fn deposit_native(&mut self, coins: &[Coin]) {
+        for coin in coins {
+            match self.native.iter_mut().find(|c| c.denom == coin.denom) {
+                Some(existing) => existing.amount += coin.amount,
+                None => self.native.push(coin.clone()),
+            }
+        }
+    }
+
+    pub fn deposit_cw20(&mut self, token: &str, amount: Uint128) {
+        self.transfer_cw20(token, None, Some(MOCK_CONTRACT_ADDR), amount);
+    }
+
+    pub fn set_price(&mut self, asset: Denom, price: Decimal) {
+        self.prices.retain(|(existing, _)| existing != &asset);
+        self.prices.push((asset, price));
+    }
+
+    pub fn set_randomness(&mut self, randomness: &[u8]) {
+        self.randomness = Binary::from(randomness);
+    }
+
+    fn price(&self, asset: &Denom) -> Option<Decimal> {
+        self.prices
+            .iter()
+            .find(|(existing, _)| existing == asset)
+            .map(|(_, price)| *price)
+    }
+
+    pub fn cw20_balance(&self, token: &str, holder: &str) -> Uint128 {
+        self.cw20
+            .get(token)
+            .and_then(|holders| holders.get(holder))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Gives CW20 tokens to a player, to be pulled with TransferFrom
+    pub fn mint_cw20(&mut self, token: &str, holder: &str, amount: Uint128) {
+        self.transfer_cw20(token, None, Some(holder), amount);
+    }
+
+    /// Replays the bank sends and CW20 transfers of a response, including TransferFrom pulls
+    /// into the contract
+    pub fn apply_response(&mut self, res: &Response) {
+        for SubMsg { msg, .. } in &res.messages {
+            match msg {
+                CosmosMsg::Bank(BankMsg::Send { amount, .. }) => {
+                    for coin in amount {
+                        let existing = self
+                            .native
+                            .iter_mut()
+                            .find(|c| c.denom == coin.denom)
+                            .expect("contract does not hold denom");
+                        existing.amount = existing.amount.checked_sub(coin.amount).unwrap();
+                    }
+                }
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr, msg, ..
+                }) => match from_binary(msg) {
+                    Ok(Cw20ExecuteMsg::Transfer { recipient, amount }) => self.transfer_cw20(
+                        contract_addr,
+                        Some(MOCK_CONTRACT_ADDR),
+                        Some(&recipient),
+                        amount,
+                    ),
+                    Ok(Cw20ExecuteMsg::TransferFrom {
+                        owner,
+                        recipient,
+                        amount,
+                    }) => self.transfer_cw20(contract_addr, Some(&owner), Some(&recipient), amount),
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn transfer_cw20(
+        &mut self,
+        token: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+        amount: Uint128,
+    ) {
+        let holders = self.cw20.entry(token.to_string()).or_default();
+        if let Some(from) = from {
+            let balance = holders.entry(from.to_string()).or_default();
+            *balance = balance.checked_sub(amount).unwrap();
+        }
+        if let Some(to) = to {
+            *holders.entry(to.to_string()).or_default() += amount;
+        }
+    }
+}
@@ -1 +1,2 @@
+mod mock_querier;
 mod tests;
@@ -1,11 +1,34 @@
-use crate::contract::{execute, instantiate, query};
-use crate::msg::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{GenericBalance, State, Wager};
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use crate::address::{pubkey_matches_address, pubkey_to_address};
+use crate::contract::{execute, instantiate, query, reply};
+use crate::events::EVENT_SCHEMA_VERSION;
+use crate::msg::{
+    AssetSolvency, BlockedResponse, ConfigChangesResponse, ConfigUpdate, Cw20HookMsg,
+    Cw721ExecuteMsg, Cw721HookMsg, Cw721ReceiveMsg, ExecuteMsg, InstantiateMsg, JackpotResponse,
+    NonceResponse, PlayerLimitsResponse, PlayerStatsResponse, QueryMsg, QueueResponse,
+    RatingResponse, ReferralStatsResponse, RelayAction, RelayDoc, RelayIntent, RolesResponse,
+    RoundsResponse, SessionResponse, SolvencyResponse, TimelockedUpdate, VotesResponse,
+};
+use crate::state::{
+    all_wager_ids, BlockedPolicy, ChanceGameKind, GenericBalance, Nft, PendingLimits,
+    RandomnessSource, Rematch, Role, Side, SideBet, SidePool, SidePoolStatus, StakeLimits, State,
+    Vote, Wager, WagerStatus, BLOCKLIST, CLAIMABLE, JACKPOT, JACKPOT_REVEAL_PERIOD, LIMIT_COOLDOWN,
+    MAX_CONFIG_DELAY, MAX_QUEUE_SCAN, MAX_REVEAL_PERIOD, MAX_SELF_EXCLUSION, QUEUE, REMATCH_PERIOD,
+    SIDE_POOLS, TOTAL_ESCROWED, WAGERS,
+};
+use crate::testing::mock_querier::{
+    mock_dependencies_with_querier, WasmMockQuerier, MOCK_BEACON_ADDR, MOCK_ORACLE_ADDR,
+};
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockStorage};
 use cosmwasm_std::{
-    coin, coins, from_binary, to_binary, Addr, BankMsg, CosmosMsg, SubMsg, Uint128, WasmMsg,
+    coin, coins, from_binary, to_binary, to_vec, Addr, BankMsg, Binary, ContractResult, CosmosMsg,
+    Decimal, Event, MessageInfo, Order, OwnedDeps, Reply, Response, StdError, SubMsg,
+    SubMsgExecutionResponse, Uint128, WasmMsg,
 };
-use cw20::{Cw20CoinVerified, Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw20::{Cw20CoinVerified, Cw20ExecuteMsg, Cw20ReceiveMsg, Denom, Expiration};
+use k256::ecdsa::signature::Signer;
+use k256::ecdsa::{Signature, SigningKey};
+use sha2::{Digest, Sha256};
 
 #[test]
 fn test_initialization() {
@@ -38,7 +61,7 @@ fn test_execute_create_wager_native() {
 
     let _initialization_check = instantiate(deps.as_mut(), mock_env(), creator, inst_msg).unwrap();
 
-    let wager_id = String::from("test_id");
+    let wager_id = 1u64;
     let new_user = mock_info("new_user", &coins(10, "uluna"));
 
     let _res_create_wager = execute(
@@ -46,7 +69,10 @@ fn test_execute_create_wager_native() {
         mock_env(),
         new_user,
         ExecuteMsg::CreateWagerNative {
-            wager_id: wager_id.clone(),
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
         },
     )
     .unwrap();
@@ -62,12 +88,80 @@ fn test_execute_create_wager_native() {
     let test_user1_balance = GenericBalance {
         native: coins(10, "uluna"),
         cw20: vec![],
+        cw721: vec![],
     };
 
     assert_eq!(test_user1_balance, wager.user1_balance);
     assert_eq!(GenericBalance::new(), wager.user2_balance);
 }
 
+#[test]
+fn test_execute_create_wager_assigns_ids() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check = instantiate(deps.as_mut(), mock_env(), creator, inst_msg).unwrap();
+
+    let new_user = mock_info("new_user", &coins(10, "uluna"));
+
+    let res_create_invalid_label = execute(
+        deps.as_mut(),
+        mock_env(),
+        new_user.clone(),
+        ExecuteMsg::CreateWagerNative {
+            label: Some(String::from("x")),
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    assert_eq!(
+        res_create_invalid_label,
+        Err(ContractError::InvalidLabel {})
+    );
+
+    let res_create_first = execute(
+        deps.as_mut(),
+        mock_env(),
+        new_user.clone(),
+        ExecuteMsg::CreateWagerNative {
+            label: Some(String::from("friday duel")),
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    )
+    .unwrap();
+    let first_id: u64 = from_binary(res_create_first.data.as_ref().unwrap()).unwrap();
+    assert_eq!(1, first_id);
+    assert_eq!(("id", "1"), attr_pair(&res_create_first, "id"));
+
+    // the same label can be used again, ids stay unique
+    let res_create_second = execute(
+        deps.as_mut(),
+        mock_env(),
+        new_user,
+        ExecuteMsg::CreateWagerNative {
+            label: Some(String::from("friday duel")),
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    )
+    .unwrap();
+    let second_id: u64 = from_binary(&res_create_second.data.unwrap()).unwrap();
+    assert_eq!(2, second_id);
+
+    let res_query_wager =
+        query(deps.as_ref(), mock_env(), QueryMsg::Wager { id: second_id }).unwrap();
+    let wager: Wager = from_binary(&res_query_wager).unwrap();
+    assert_eq!(Some(String::from("friday duel")), wager.label);
+}
+
 #[test]
 fn test_execute_create_wager_cw20() {
     let creator = mock_info("creator", &[]);
@@ -82,13 +176,16 @@ fn test_execute_create_wager_cw20() {
 
     let token_contract = mock_info("cw20-token", &[]);
 
-    let wager_id = String::from("test_id");
+    let wager_id = 1u64;
 
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "new_user".to_string(),
         amount: Uint128::from(100u128),
         msg: to_binary(&Cw20HookMsg::CreateWager {
-            wager_id: wager_id.clone(),
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
         })
         .unwrap(),
     });
@@ -110,6 +207,7 @@ fn test_execute_create_wager_cw20() {
             address: Addr::unchecked("cw20-token"),
             amount: Uint128::new(100),
         }],
+        cw721: vec![],
     };
 
     assert_eq!(test_user1_balance, wager.user1_balance);
@@ -130,13 +228,16 @@ fn test_execute_add_funds_cw20() {
 
     let token_contract = mock_info("cw20-token", &[]);
 
-    let wager_id = String::from("test_id");
+    let wager_id = 1u64;
 
     let create_wager_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "new_user".to_string(),
         amount: Uint128::from(100u128),
         msg: to_binary(&Cw20HookMsg::CreateWager {
-            wager_id: wager_id.clone(),
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
         })
         .unwrap(),
     });
@@ -152,10 +253,7 @@ fn test_execute_add_funds_cw20() {
     let add_funds_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "new_user2".to_string(),
         amount: Uint128::from(99u128),
-        msg: to_binary(&Cw20HookMsg::AddFunds {
-            wager_id: wager_id.clone(),
-        })
-        .unwrap(),
+        msg: to_binary(&Cw20HookMsg::AddFunds { wager_id }).unwrap(),
     });
 
     let res_add_funds_unsuccessfully = execute(
@@ -172,15 +270,13 @@ fn test_execute_add_funds_cw20() {
             address: Addr::unchecked("cw20-token"),
             amount: Uint128::new(100),
         }],
+        cw721: vec![],
     };
 
     let add_funds_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "new_user2".to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::AddFunds {
-            wager_id: wager_id.clone(),
-        })
-        .unwrap(),
+        msg: to_binary(&Cw20HookMsg::AddFunds { wager_id }).unwrap(),
     });
     let _res_add_funds_successfully =
         execute(deps.as_mut(), mock_env(), token_contract, add_funds_msg).unwrap();
@@ -196,6 +292,7 @@ fn test_execute_add_funds_cw20() {
             address: Addr::unchecked("cw20-token"),
             amount: Uint128::new(100),
         }],
+        cw721: vec![],
     };
     assert_eq!("creator", wager.arbiter);
     assert_eq!("new_user", wager.user1);
@@ -218,14 +315,17 @@ fn test_execute_cancel_wager() {
 
     let new_user = mock_info("new_user", &coins(10, "uluna"));
 
-    let wager_id = String::from("test_id");
+    let wager_id = 1u64;
 
     let _res_create_wager = execute(
         deps.as_mut(),
         mock_env(),
         new_user.clone(),
         ExecuteMsg::CreateWagerNative {
-            wager_id: wager_id.clone(),
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
         },
     )
     .unwrap();
@@ -236,18 +336,14 @@ fn test_execute_cancel_wager() {
         deps.as_mut(),
         mock_env(),
         sneaky_user,
-        ExecuteMsg::Cancel {
-            wager_id: wager_id.clone(),
-        },
+        ExecuteMsg::Cancel { wager_id },
     );
     assert!(_res_cancel_fail.is_err());
     let _res_cancel_fail = execute(
         deps.as_mut(),
         mock_env(),
         new_user,
-        ExecuteMsg::Cancel {
-            wager_id: wager_id.clone(),
-        },
+        ExecuteMsg::Cancel { wager_id },
     );
 
     // wager doesn't exist.
@@ -269,27 +365,40 @@ fn test_execute_send_funds_native() {
 
     let new_user = mock_info("new_user", &coins(10, "uluna"));
 
-    let wager_id = String::from("test_id");
+    let wager_id = 1u64;
 
     let _res_create_wager = execute(
         deps.as_mut(),
         mock_env(),
         new_user.clone(),
         ExecuteMsg::CreateWagerNative {
-            wager_id: wager_id.clone(),
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
         },
     )
     .unwrap();
 
+    // a wager without an opponent cannot be settled
+    let res_send_funds_open = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::SendFunds {
+            wager_id,
+            winner_address: new_user.sender.clone(),
+        },
+    );
+    assert_eq!(res_send_funds_open, Err(ContractError::NotMatched {}));
+
     let new_user2 = mock_info("new_user2", &coins(10, "uluna"));
 
     let _res_add_funds = execute(
         deps.as_mut(),
         mock_env(),
         new_user2,
-        ExecuteMsg::AddFundsNative {
-            wager_id: wager_id.clone(),
-        },
+        ExecuteMsg::AddFundsNative { wager_id },
     );
 
     let res_send_funds_fail = execute(
@@ -297,7 +406,7 @@ fn test_execute_send_funds_native() {
         mock_env(),
         new_user.clone(),
         ExecuteMsg::SendFunds {
-            wager_id: wager_id.clone(),
+            wager_id,
             winner_address: new_user.sender.clone(),
         },
     );
@@ -309,7 +418,7 @@ fn test_execute_send_funds_native() {
         mock_env(),
         creator,
         ExecuteMsg::SendFunds {
-            wager_id: wager_id.clone(),
+            wager_id,
             winner_address: new_user.sender.clone(),
         },
     )
@@ -350,7 +459,7 @@ fn test_execute_send_funds_cw20() {
 
     let token_contract = mock_info("cw20-token", &[]);
 
-    let wager_id = String::from("test_id");
+    let wager_id = 1u64;
 
     let new_user = mock_info("new_user", &[]);
     let new_user2 = mock_info("new_user2", &[]);
@@ -359,7 +468,10 @@ fn test_execute_send_funds_cw20() {
         sender: new_user.sender.to_string(),
         amount: Uint128::from(100u128),
         msg: to_binary(&Cw20HookMsg::CreateWager {
-            wager_id: wager_id.clone(),
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
         })
         .unwrap(),
     });
@@ -375,10 +487,7 @@ fn test_execute_send_funds_cw20() {
     let add_funds_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: new_user2.sender.to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::AddFunds {
-            wager_id: wager_id.clone(),
-        })
-        .unwrap(),
+        msg: to_binary(&Cw20HookMsg::AddFunds { wager_id }).unwrap(),
     });
 
     let _res_add_funds_successfully =
@@ -389,7 +498,7 @@ fn test_execute_send_funds_cw20() {
         mock_env(),
         new_user.clone(),
         ExecuteMsg::SendFunds {
-            wager_id: wager_id.clone(),
+            wager_id,
             winner_address: new_user.sender.clone(),
         },
     );
@@ -401,7 +510,7 @@ fn test_execute_send_funds_cw20() {
         mock_env(),
         creator,
         ExecuteMsg::SendFunds {
-            wager_id: wager_id.clone(),
+            wager_id,
             winner_address: new_user.sender.clone(),
         },
     )
@@ -424,3 +533,5346 @@ fn test_execute_send_funds_cw20() {
     assert_eq!(res_send_funds_success.messages[0], expected_msg);
     assert_eq!(res_send_funds_success.messages[1], expected_msg);
 }
+
+#[test]
+fn test_execute_join_queue_matches_same_stake() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check = instantiate(deps.as_mut(), mock_env(), creator, inst_msg).unwrap();
+
+    let join_msg = ExecuteMsg::JoinQueue {
+        game_id: String::from("chess"),
+        stake: coin(10, "uluna"),
+        max_rating_diff: None,
+    };
+
+    // funds must match the declared stake
+    let res_join_invalid = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(5, "uluna")),
+        join_msg.clone(),
+    );
+    assert_eq!(res_join_invalid, Err(ContractError::InvalidStake {}));
+
+    let _res_join = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(10, "uluna")),
+        join_msg.clone(),
+    )
+    .unwrap();
+
+    let res_query_queue = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Queue {
+            game_id: String::from("chess"),
+        },
+    )
+    .unwrap();
+    let queue: QueueResponse = from_binary(&res_query_queue).unwrap();
+    assert_eq!(1, queue.entries.len());
+    assert_eq!("new_user", queue.entries[0].player);
+
+    // a player with a different stake keeps waiting
+    let _res_join_other_stake = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("rich_user", &coins(20, "uluna")),
+        ExecuteMsg::JoinQueue {
+            game_id: String::from("chess"),
+            stake: coin(20, "uluna"),
+            max_rating_diff: None,
+        },
+    )
+    .unwrap();
+
+    let res_match = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &coins(10, "uluna")),
+        join_msg,
+    )
+    .unwrap();
+    assert_eq!(("id", "1"), attr_pair(&res_match, "id"));
+
+    let res_query_wager = query(deps.as_ref(), mock_env(), QueryMsg::Wager { id: 1 }).unwrap();
+    let wager: Wager = from_binary(&res_query_wager).unwrap();
+    assert_eq!("new_user", wager.user1);
+    assert_eq!("new_user2", wager.user2);
+    assert_eq!(Some(String::from("chess")), wager.game_id);
+    assert_eq!(coins(10, "uluna"), wager.user1_balance.native);
+    assert_eq!(wager.user1_balance, wager.user2_balance);
+
+    let res_query_queue = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Queue {
+            game_id: String::from("chess"),
+        },
+    )
+    .unwrap();
+    let queue: QueueResponse = from_binary(&res_query_queue).unwrap();
+    assert_eq!(1, queue.entries.len());
+    assert_eq!("rich_user", queue.entries[0].player);
+}
+
+#[test]
+fn test_execute_join_queue_rating_band() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    let set_rating_msg = ExecuteMsg::SetRating {
+        player: String::from("new_user2"),
+        rating: 1500,
+    };
+
+    // only the owner can rate players
+    let res_set_rating_fail = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &[]),
+        set_rating_msg.clone(),
+    );
+    assert_eq!(
+        res_set_rating_fail,
+        Err(ContractError::MissingRole {
+            role: Role::Operator
+        })
+    );
+
+    let _res_set_rating = execute(deps.as_mut(), mock_env(), creator, set_rating_msg).unwrap();
+
+    let res_query_rating = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Rating {
+            player: String::from("new_user2"),
+        },
+    )
+    .unwrap();
+    let rating: RatingResponse = from_binary(&res_query_rating).unwrap();
+    assert_eq!(1500, rating.rating);
+
+    let token_contract = mock_info("cw20-token", &[]);
+
+    let join_msg = |sender: &str, max_rating_diff: Option<u32>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::JoinQueue {
+                game_id: String::from("chess"),
+                max_rating_diff,
+            })
+            .unwrap(),
+        })
+    };
+
+    let _res_join = execute(
+        deps.as_mut(),
+        mock_env(),
+        token_contract.clone(),
+        join_msg("new_user", Some(200)),
+    )
+    .unwrap();
+
+    // 1500 is too far from the default rating of 1200
+    let res_join_outside_band = execute(
+        deps.as_mut(),
+        mock_env(),
+        token_contract.clone(),
+        join_msg("new_user2", None),
+    )
+    .unwrap();
+    assert!(res_join_outside_band
+        .attributes
+        .iter()
+        .all(|attr| attr.key != "id"));
+
+    // a player can only wait once per game
+    let res_join_twice = execute(
+        deps.as_mut(),
+        mock_env(),
+        token_contract.clone(),
+        join_msg("new_user2", None),
+    );
+    assert_eq!(res_join_twice, Err(ContractError::AlreadyQueued {}));
+
+    let res_join_inside_band = execute(
+        deps.as_mut(),
+        mock_env(),
+        token_contract,
+        join_msg("new_user3", None),
+    )
+    .unwrap();
+    assert_eq!(("id", "1"), attr_pair(&res_join_inside_band, "id"));
+
+    let res_query_wager = query(deps.as_ref(), mock_env(), QueryMsg::Wager { id: 1 }).unwrap();
+    let wager: Wager = from_binary(&res_query_wager).unwrap();
+    assert_eq!("new_user", wager.user1);
+    assert_eq!("new_user3", wager.user2);
+}
+
+#[test]
+fn test_execute_leave_queue() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check = instantiate(deps.as_mut(), mock_env(), creator, inst_msg).unwrap();
+
+    let new_user = mock_info("new_user", &coins(10, "uluna"));
+
+    let _res_join = execute(
+        deps.as_mut(),
+        mock_env(),
+        new_user.clone(),
+        ExecuteMsg::JoinQueue {
+            game_id: String::from("chess"),
+            stake: coin(10, "uluna"),
+            max_rating_diff: None,
+        },
+    )
+    .unwrap();
+
+    let res_leave_fail = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("sneaky_user", &[]),
+        ExecuteMsg::LeaveQueue {
+            game_id: String::from("chess"),
+        },
+    );
+    assert_eq!(res_leave_fail, Err(ContractError::NotInQueue {}));
+
+    let res_leave = execute(
+        deps.as_mut(),
+        mock_env(),
+        new_user.clone(),
+        ExecuteMsg::LeaveQueue {
+            game_id: String::from("chess"),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        res_leave.messages[0],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from(new_user.sender),
+            amount: vec![coin(10, "uluna")],
+        }))
+    );
+
+    let res_query_queue = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Queue {
+            game_id: String::from("chess"),
+        },
+    )
+    .unwrap();
+    let queue: QueueResponse = from_binary(&res_query_queue).unwrap();
+    assert!(queue.entries.is_empty());
+}
+
+#[test]
+fn test_queue_evicts_ineligible_players() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+    execute_tracked(
+        &mut deps,
+        creator,
+        ExecuteMsg::GrantRole {
+            role: Role::Operator,
+            address: "operator".to_string(),
+            game_id: None,
+        },
+    );
+
+    let join = |game_id: &str| ExecuteMsg::JoinQueue {
+        game_id: game_id.to_string(),
+        stake: coin(10, "uluna"),
+        max_rating_diff: None,
+    };
+    let block = |player: &str| ExecuteMsg::UpdateBlocklist {
+        add: vec![player.to_string()],
+        remove: vec![],
+    };
+    execute_tracked(
+        &mut deps,
+        mock_info("cheater", &coins(10, "uluna")),
+        join("chess"),
+    );
+    execute_tracked(&mut deps, mock_info("operator", &[]), block("cheater"));
+    execute_tracked(
+        &mut deps,
+        mock_info("excluded_user", &coins(10, "uluna")),
+        join("go"),
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("excluded_user", &[]),
+        ExecuteMsg::SelfExclude { seconds: 3600 },
+    );
+
+    // the blocked and the self-excluded players are refunded instead of matched
+    for (game_id, waiting) in [("chess", "cheater"), ("go", "excluded_user")] {
+        let res_join = execute_tracked(
+            &mut deps,
+            mock_info("new_user", &coins(10, "uluna")),
+            join(game_id),
+        );
+        assert_eq!(("evicted", waiting), attr_pair(&res_join, "evicted"));
+        assert_eq!("queue_left", res_join.events[0].ty);
+        assert_eq!("queue_joined", res_join.events[1].ty);
+        assert_eq!(
+            res_join.messages,
+            vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: waiting.to_string(),
+                amount: coins(10, "uluna"),
+            }))]
+        );
+        let queue: QueueResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Queue {
+                    game_id: game_id.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(1, queue.entries.len());
+        assert_eq!(Addr::unchecked("new_user"), queue.entries[0].player);
+    }
+    assert!(all_wager_ids(&deps.storage).unwrap().is_empty());
+
+    // operators only remove blocked players from the queue
+    let force_leave = ExecuteMsg::ForceLeaveQueue {
+        game_id: "chess".to_string(),
+        player: "new_user".to_string(),
+    };
+    let res_not_operator = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &[]),
+        force_leave.clone(),
+    );
+    assert_eq!(
+        res_not_operator,
+        Err(ContractError::MissingRole {
+            role: Role::Operator
+        })
+    );
+    let res_not_blocked = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        force_leave.clone(),
+    );
+    assert_eq!(res_not_blocked, Err(ContractError::NotBlocked {}));
+    execute_tracked(&mut deps, mock_info("operator", &[]), block("new_user"));
+    let res_force = execute_tracked(&mut deps, mock_info("operator", &[]), force_leave);
+    assert_eq!("queue_left", res_force.events[0].ty);
+    assert_eq!(
+        res_force.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "new_user".to_string(),
+            amount: coins(10, "uluna"),
+        }))]
+    );
+}
+
+fn attr_pair<'a>(res: &'a Response, key: &str) -> (&'a str, &'a str) {
+    let attr = res
+        .attributes
+        .iter()
+        .find(|attr| attr.key == key)
+        .expect("attribute not found");
+    (attr.key.as_str(), attr.value.as_str())
+}
+
+#[test]
+fn test_wager_events() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    let res_create_wager = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(10, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(1, res_create_wager.events.len());
+    let event = &res_create_wager.events[0];
+    assert_eq!("wager_created", event.ty);
+    assert_eq!(
+        Some(EVENT_SCHEMA_VERSION),
+        event_attr(event, "schema_version")
+    );
+    assert_eq!(Some("1"), event_attr(event, "wager_id"));
+    assert_eq!(Some("creator"), event_attr(event, "arbiter"));
+    assert_eq!(Some("new_user"), event_attr(event, "user1"));
+    assert_eq!(None, event_attr(event, "user2"));
+    assert_eq!(
+        Some(r#"{"native":[{"denom":"uluna","amount":"10"}],"cw20":[]}"#),
+        event_attr(event, "user1_balance")
+    );
+    assert_eq!(Some("open"), event_attr(event, "status"));
+
+    let res_add_funds = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &coins(10, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    )
+    .unwrap();
+
+    assert_eq!(1, res_add_funds.events.len());
+    let event = &res_add_funds.events[0];
+    assert_eq!("wager_joined", event.ty);
+    assert_eq!(Some("new_user2"), event_attr(event, "user2"));
+    assert_eq!(Some("matched"), event_attr(event, "status"));
+
+    let res_send_funds = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator,
+        ExecuteMsg::SendFunds {
+            wager_id: 1,
+            winner_address: Addr::unchecked("new_user2"),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(1, res_send_funds.events.len());
+    let event = &res_send_funds.events[0];
+    assert_eq!("wager_settled", event.ty);
+    assert_eq!(Some("settled"), event_attr(event, "status"));
+    assert_eq!(Some("new_user2"), event_attr(event, "winner"));
+}
+
+#[test]
+fn test_queue_scan_is_bounded() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+    for player in ["new_user", "new_user2"] {
+        execute_tracked(
+            &mut deps,
+            creator.clone(),
+            ExecuteMsg::SetRating {
+                player: player.to_string(),
+                rating: 1500,
+            },
+        );
+    }
+
+    let join = |max_rating_diff| ExecuteMsg::JoinQueue {
+        game_id: "chess".to_string(),
+        stake: coin(10, "uluna"),
+        max_rating_diff,
+    };
+    // a flood of entries the caller cannot be matched with, nor with each other
+    for i in 0..MAX_QUEUE_SCAN {
+        execute_tracked(
+            &mut deps,
+            creator.clone(),
+            ExecuteMsg::SetRating {
+                player: format!("picky{}", i),
+                rating: 1000 + i as u32,
+            },
+        );
+        execute_tracked(
+            &mut deps,
+            mock_info(&format!("picky{}", i), &coins(10, "uluna")),
+            join(Some(0)),
+        );
+    }
+    // entries with another stake are not looked at
+    execute_tracked(
+        &mut deps,
+        mock_info("other_stake", &coins(20, "uluna")),
+        ExecuteMsg::JoinQueue {
+            game_id: "chess".to_string(),
+            stake: coin(20, "uluna"),
+            max_rating_diff: None,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("late", &coins(10, "uluna")),
+        join(None),
+    );
+
+    // only the oldest entries with the same stake are read, the caller waits in turn
+    let res_join = execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(10, "uluna")),
+        join(None),
+    );
+    assert_eq!("queue_joined", res_join.events[0].ty);
+
+    // once a picky player leaves, the late one is within reach
+    execute_tracked(
+        &mut deps,
+        mock_info("picky0", &[]),
+        ExecuteMsg::LeaveQueue {
+            game_id: "chess".to_string(),
+        },
+    );
+    let res_match = execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(10, "uluna")),
+        join(None),
+    );
+    assert_eq!("wager_created", res_match.events[0].ty);
+    assert_eq!(Some("late"), event_attr(&res_match.events[0], "user1"));
+}
+
+#[test]
+fn test_queue_events() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check = instantiate(deps.as_mut(), mock_env(), creator, inst_msg).unwrap();
+
+    let join_msg = ExecuteMsg::JoinQueue {
+        game_id: String::from("chess"),
+        stake: coin(10, "uluna"),
+        max_rating_diff: None,
+    };
+
+    let res_join = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(10, "uluna")),
+        join_msg.clone(),
+    )
+    .unwrap();
+
+    assert_eq!(1, res_join.events.len());
+    assert_eq!("queue_joined", res_join.events[0].ty);
+    assert_eq!(Some("chess"), event_attr(&res_join.events[0], "game_id"));
+
+    // a match creates the wager and joins it, with an event for each
+    let res_match = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &coins(10, "uluna")),
+        join_msg,
+    )
+    .unwrap();
+
+    assert_eq!(2, res_match.events.len());
+    let event = &res_match.events[0];
+    assert_eq!("wager_created", event.ty);
+    assert_eq!(Some("chess"), event_attr(event, "game_id"));
+    assert_eq!(Some("new_user"), event_attr(event, "user1"));
+    assert_eq!(None, event_attr(event, "user2"));
+    assert_eq!(Some("open"), event_attr(event, "status"));
+    let event = &res_match.events[1];
+    assert_eq!("wager_joined", event.ty);
+    assert_eq!(Some("chess"), event_attr(event, "game_id"));
+    assert_eq!(Some("new_user2"), event_attr(event, "user2"));
+    assert_eq!(Some("matched"), event_attr(event, "status"));
+}
+
+fn event_attr<'a>(event: &'a Event, key: &str) -> Option<&'a str> {
+    event
+        .attributes
+        .iter()
+        .find(|attr| attr.key == key)
+        .map(|attr| attr.value.as_str())
+}
+
+#[test]
+fn test_solvency_invariant() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+    assert_escrow_invariant(&deps);
+
+    let cw20_msg = |sender: &str, amount: u128, msg: Cw20HookMsg| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&msg).unwrap(),
+        })
+    };
+    let token_contract = mock_info("cw20-token", &[]);
+
+    // wager 1 in native tokens, wager 2 in cw20 tokens
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(10, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        token_contract.clone(),
+        cw20_msg(
+            "new_user2",
+            100,
+            Cw20HookMsg::CreateWager {
+                label: None,
+                stake: None,
+                stake_value: None,
+                series_length: None,
+            },
+        ),
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user3", &coins(10, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    );
+
+    // queued stakes are escrowed too, wager 3 comes from the queue
+    let join_queue_msg = |game_id: &str| ExecuteMsg::JoinQueue {
+        game_id: game_id.to_string(),
+        stake: coin(5, "uluna"),
+        max_rating_diff: None,
+    };
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user4", &coins(5, "uluna")),
+        join_queue_msg("chess"),
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user5", &coins(5, "uluna")),
+        join_queue_msg("chess"),
+    );
+    execute_tracked(
+        &mut deps,
+        token_contract,
+        cw20_msg(
+            "new_user6",
+            50,
+            Cw20HookMsg::JoinQueue {
+                game_id: String::from("go"),
+                max_rating_diff: None,
+            },
+        ),
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user6", &[]),
+        ExecuteMsg::LeaveQueue {
+            game_id: String::from("go"),
+        },
+    );
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &[]),
+        ExecuteMsg::Cancel { wager_id: 2 },
+    );
+    execute_tracked(
+        &mut deps,
+        creator.clone(),
+        ExecuteMsg::SendFunds {
+            wager_id: 1,
+            winner_address: Addr::unchecked("new_user3"),
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        creator,
+        ExecuteMsg::SendFunds {
+            wager_id: 3,
+            winner_address: Addr::unchecked("new_user4"),
+        },
+    );
+
+    let solvency: SolvencyResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Solvency {}).unwrap()).unwrap();
+    assert!(solvency
+        .assets
+        .iter()
+        .all(|asset| asset.escrowed.is_zero() && asset.held.is_zero()));
+}
+
+#[test]
+fn test_solvency_detects_shortfall() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check = instantiate(deps.as_mut(), mock_env(), creator, inst_msg).unwrap();
+
+    // the cw20 tokens never reach the contract
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("cw20-token", &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "new_user".to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::CreateWager {
+                label: None,
+                stake: None,
+                stake_value: None,
+                series_length: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap();
+
+    let solvency: SolvencyResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Solvency {}).unwrap()).unwrap();
+    assert!(!solvency.solvent);
+    assert_eq!(
+        vec![AssetSolvency {
+            asset: Denom::Cw20(Addr::unchecked("cw20-token")),
+            escrowed: Uint128::new(100),
+            held: Uint128::zero(),
+        }],
+        solvency.assets
+    );
+}
+
+/// Executes a message, moving the tokens it carries into the mock contract holdings and the
+/// tokens it sends out of them, then checks the escrow invariant.
+fn execute_tracked(
+    deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Response {
+    deps.querier.deposit_native(&info.funds);
+    if let ExecuteMsg::Receive(receive) = &msg {
+        deps.querier
+            .deposit_cw20(info.sender.as_str(), receive.amount);
+    }
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    deps.querier.apply_response(&res);
+    assert_escrow_invariant(deps);
+    res
+}
+
+/// The escrow total must equal the open wagers, the queued stakes, the claimable tokens, the
+/// jackpot and the unclaimed side bets, and be exactly what the contract holds.
+fn assert_escrow_invariant(deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) {
+    let mut expected = GenericBalance::new();
+    for id in all_wager_ids(&deps.storage).unwrap() {
+        let wager = WAGERS.load(&deps.storage, id.into()).unwrap();
+        expected.add_balance(&wager.user1_balance);
+        expected.add_balance(&wager.user2_balance);
+    }
+    for item in QUEUE.range(&deps.storage, None, None, Order::Ascending) {
+        let (_, entry) = item.unwrap();
+        expected.add_balance(&GenericBalance::from(entry.stake));
+    }
+    for item in CLAIMABLE.range(&deps.storage, None, None, Order::Ascending) {
+        let (_, claimable) = item.unwrap();
+        expected.add_balance(&claimable);
+    }
+    expected.add_balance(&JACKPOT.may_load(&deps.storage).unwrap().unwrap_or_default());
+    for item in SIDE_POOLS.range(&deps.storage, None, None, Order::Ascending) {
+        let (_, pool) = item.unwrap();
+        if let Some(asset) = &pool.asset {
+            expected.add_balance(&GenericBalance::from_asset(asset, pool.unclaimed));
+        }
+    }
+    let escrowed = TOTAL_ESCROWED
+        .may_load(&deps.storage)
+        .unwrap()
+        .unwrap_or_default();
+    assert_eq!(expected, escrowed);
+
+    let solvency: SolvencyResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Solvency {}).unwrap()).unwrap();
+    assert!(solvency.solvent);
+    for asset in solvency.assets {
+        assert_eq!(asset.escrowed, asset.held);
+    }
+}
+
+#[test]
+fn test_execute_sweep() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(10, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+
+    let sweep_luna = ExecuteMsg::Sweep {
+        asset: Denom::Native(String::from("uluna")),
+        recipient: String::from("treasury"),
+    };
+
+    // the open wager's funds cannot be swept
+    let res_sweep_nothing = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        sweep_luna.clone(),
+    );
+    assert_eq!(res_sweep_nothing, Err(ContractError::NothingToSweep {}));
+
+    // stray coins and a cw20 transfer that bypassed the receive hook
+    deps.querier.deposit_native(&coins(3, "uluna"));
+    deps.querier.deposit_cw20("cw20-token", Uint128::new(40));
+
+    let res_sweep_fail = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        sweep_luna.clone(),
+    );
+    assert_eq!(
+        res_sweep_fail,
+        Err(ContractError::MissingRole { role: Role::Owner })
+    );
+
+    let res_sweep_luna = execute(deps.as_mut(), mock_env(), creator.clone(), sweep_luna).unwrap();
+    assert_eq!(
+        res_sweep_luna.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("treasury"),
+            amount: coins(3, "uluna"),
+        }))]
+    );
+    deps.querier.apply_response(&res_sweep_luna);
+
+    let res_sweep_cw20 = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator,
+        ExecuteMsg::Sweep {
+            asset: Denom::Cw20(Addr::unchecked("cw20-token")),
+            recipient: String::from("treasury"),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res_sweep_cw20.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("cw20-token"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("treasury"),
+                amount: Uint128::new(40),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    deps.querier.apply_response(&res_sweep_cw20);
+
+    assert_escrow_invariant(&deps);
+}
+
+#[test]
+fn test_execute_deposit_basket_stake() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    let duel = Cw20CoinVerified {
+        address: Addr::unchecked("cw20-token"),
+        amount: Uint128::new(100),
+    };
+    // unsorted, with a zero coin, to check the stake is normalised
+    let basket = GenericBalance {
+        native: vec![coin(10, "uluna"), coin(0, "uusd")],
+        cw20: vec![duel.clone()],
+        cw721: vec![],
+    };
+    let deposit_cw20 = |sender: &str, amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::Deposit { wager_id: 1 }).unwrap(),
+        })
+    };
+    let token_contract = mock_info("cw20-token", &[]);
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(10, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: Some(basket),
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    let wager: Wager =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Wager { id: 1 }).unwrap()).unwrap();
+    assert_eq!(WagerStatus::Funding, wager.status);
+    assert_eq!(coins(10, "uluna"), wager.stake.native);
+
+    // nobody can join before the creator deposited the whole stake
+    let res_join_early = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &coins(10, "uluna")),
+        ExecuteMsg::DepositNative { wager_id: 1 },
+    );
+    assert_eq!(res_join_early, Err(ContractError::AlreadyInUse {}));
+
+    let res_over_deposit = execute(
+        deps.as_mut(),
+        mock_env(),
+        token_contract.clone(),
+        deposit_cw20("new_user", 101),
+    );
+    assert_eq!(res_over_deposit, Err(ContractError::StakeExceeded {}));
+
+    let res_complete = execute_tracked(
+        &mut deps,
+        token_contract.clone(),
+        deposit_cw20("new_user", 100),
+    );
+    assert_eq!("wager_deposited", res_complete.events[0].ty);
+    assert_eq!(Some("open"), event_attr(&res_complete.events[0], "status"));
+
+    // the opponent deposits the basket in two steps and holds the seat in between
+    let res_partial = execute_tracked(
+        &mut deps,
+        token_contract.clone(),
+        deposit_cw20("new_user2", 100),
+    );
+    assert_eq!(Some("open"), event_attr(&res_partial.events[0], "status"));
+
+    let res_seat_taken = execute(
+        deps.as_mut(),
+        mock_env(),
+        token_contract,
+        deposit_cw20("new_user3", 100),
+    );
+    assert_eq!(res_seat_taken, Err(ContractError::AlreadyInUse {}));
+
+    let res_match = execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(10, "uluna")),
+        ExecuteMsg::DepositNative { wager_id: 1 },
+    );
+    assert_eq!("wager_joined", res_match.events[0].ty);
+
+    let wager: Wager =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Wager { id: 1 }).unwrap()).unwrap();
+    assert_eq!(WagerStatus::Matched, wager.status);
+    assert_eq!(wager.stake, wager.user1_balance);
+    assert_eq!(wager.stake, wager.user2_balance);
+
+    let res_send_funds = execute_tracked(
+        &mut deps,
+        creator,
+        ExecuteMsg::SendFunds {
+            wager_id: 1,
+            winner_address: Addr::unchecked("new_user2"),
+        },
+    );
+    assert_eq!(4, res_send_funds.messages.len());
+    assert_eq!(
+        Uint128::new(200),
+        deps.querier.cw20_balance("cw20-token", "new_user2")
+    );
+}
+
+#[test]
+fn test_execute_cancel_refunds_partial_deposit() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check = instantiate(deps.as_mut(), mock_env(), creator, inst_msg).unwrap();
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &[coin(10, "uluna"), coin(20, "uusd")]),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(10, "uluna")),
+        ExecuteMsg::DepositNative { wager_id: 1 },
+    );
+
+    let res_cancel = execute_tracked(
+        &mut deps,
+        mock_info("new_user", &[]),
+        ExecuteMsg::Cancel { wager_id: 1 },
+    );
+    assert_eq!(
+        res_cancel.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("new_user"),
+                amount: vec![coin(10, "uluna"), coin(20, "uusd")],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("new_user2"),
+                amount: vec![coin(10, "uluna")],
+            })),
+        ]
+    );
+}
+
+#[test]
+fn test_execute_valued_stake() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    let create_valued = ExecuteMsg::CreateWagerNative {
+        label: None,
+        stake: None,
+        stake_value: Some(Uint128::new(1000)),
+        series_length: None,
+    };
+
+    // valued stakes need an oracle
+    let res_no_oracle = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(12, "uluna")),
+        create_valued.clone(),
+    );
+    assert_eq!(res_no_oracle, Err(ContractError::NoOracle {}));
+
+    let update_config = ExecuteMsg::UpdateConfig(ConfigUpdate {
+        oracle: Some(MOCK_ORACLE_ADDR.to_string()),
+        price_tolerance_bps: Some(200),
+        ..ConfigUpdate::default()
+    });
+    let res_unauthorized = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        update_config.clone(),
+    );
+    assert_eq!(
+        res_unauthorized,
+        Err(ContractError::MissingRole { role: Role::Owner })
+    );
+
+    let res_invalid_tolerance = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            price_tolerance_bps: Some(10_001),
+            ..ConfigUpdate::default()
+        }),
+    );
+    assert_eq!(
+        res_invalid_tolerance,
+        Err(ContractError::InvalidTolerance {})
+    );
+
+    let res_update = execute(deps.as_mut(), mock_env(), creator.clone(), update_config).unwrap();
+    assert_eq!("config_updated", res_update.events[0].ty);
+    assert_eq!(
+        Some(MOCK_ORACLE_ADDR),
+        event_attr(&res_update.events[0], "oracle")
+    );
+    assert_eq!(
+        Some("200"),
+        event_attr(&res_update.events[0], "price_tolerance_bps")
+    );
+
+    deps.querier.set_price(
+        Denom::Native("uluna".to_string()),
+        Decimal::from_ratio(80u128, 1u128),
+    );
+    deps.querier
+        .set_price(Denom::Native("uusd".to_string()), Decimal::one());
+    deps.querier.set_price(
+        Denom::Cw20(Addr::unchecked("cw20-token")),
+        Decimal::percent(50),
+    );
+
+    let res_conflicting = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(12, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: Some(GenericBalance {
+                native: coins(12, "uluna"),
+                cw20: vec![],
+                cw721: vec![],
+            }),
+            stake_value: Some(Uint128::new(1000)),
+            series_length: None,
+        },
+    );
+    assert_eq!(res_conflicting, Err(ContractError::ConflictingStake {}));
+
+    // 12 LUNA are worth 960, short of the 980 the tolerance allows
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(12, "uluna")),
+        create_valued,
+    );
+    let wager: Wager =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Wager { id: 1 }).unwrap()).unwrap();
+    assert_eq!(WagerStatus::Funding, wager.status);
+    assert_eq!(Some(Uint128::new(1000)), wager.stake_value);
+
+    let res_over_deposit = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(250, "uusd")),
+        ExecuteMsg::DepositNative { wager_id: 1 },
+    );
+    assert_eq!(res_over_deposit, Err(ContractError::StakeExceeded {}));
+
+    // 960 + 30 is within 2% of the stake value
+    let res_complete = execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(30, "uusd")),
+        ExecuteMsg::DepositNative { wager_id: 1 },
+    );
+    assert_eq!(Some("open"), event_attr(&res_complete.events[0], "status"));
+
+    let join_cw20 = |amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "new_user2".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::AddFunds { wager_id: 1 }).unwrap(),
+        })
+    };
+    let token_contract = mock_info("cw20-token", &[]);
+
+    // 2100 DUEL are worth 1050, above the tolerance
+    let res_unequal = execute(
+        deps.as_mut(),
+        mock_env(),
+        token_contract.clone(),
+        join_cw20(2100),
+    );
+    assert_eq!(res_unequal, Err(ContractError::UnequalBalance {}));
+
+    let res_join = execute_tracked(&mut deps, token_contract, join_cw20(2000));
+    assert_eq!("wager_joined", res_join.events[0].ty);
+    assert_eq!(Some("matched"), event_attr(&res_join.events[0], "status"));
+
+    // the winner takes the pooled assets of both sides
+    let res_send_funds = execute_tracked(
+        &mut deps,
+        creator,
+        ExecuteMsg::SendFunds {
+            wager_id: 1,
+            winner_address: Addr::unchecked("new_user"),
+        },
+    );
+    assert_eq!(
+        res_send_funds.messages[0],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "new_user".to_string(),
+            amount: vec![coin(12, "uluna"), coin(30, "uusd")],
+        }))
+    );
+    assert_eq!(
+        Uint128::new(2000),
+        deps.querier.cw20_balance("cw20-token", "new_user")
+    );
+
+    // a value that does not fit fails instead of panicking
+    deps.querier.set_price(
+        Denom::Native("uusd".to_string()),
+        Decimal::from_ratio(1_000_000u128, 1u128),
+    );
+    let res_overflow = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(10u128.pow(33), "uusd")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: Some(Uint128::new(1000)),
+            series_length: None,
+        },
+    );
+    assert!(matches!(
+        res_overflow,
+        Err(ContractError::Std(StdError::Overflow { .. }))
+    ));
+}
+
+/// Runs the reply of a successful TransferFrom submessage
+fn reply_transfer_from(
+    deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+    res: &Response,
+) -> Result<Response, ContractError> {
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: res.messages[0].id,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+}
+
+#[test]
+fn test_execute_wager_cw20_transfer_from() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check = instantiate(deps.as_mut(), mock_env(), creator, inst_msg).unwrap();
+
+    for player in &["new_user", "new_user2", "new_user3"] {
+        deps.querier
+            .mint_cw20("cw20-token", player, Uint128::new(100));
+    }
+
+    let res_create = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::CreateWagerCw20 {
+            token: "cw20-token".to_string(),
+            amount: Uint128::new(100),
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res_create.messages[0],
+        SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "cw20-token".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: "new_user".to_string(),
+                    recipient: "cosmos2contract".to_string(),
+                    amount: Uint128::new(100),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            res_create.messages[0].id,
+        )
+    );
+    // nothing is recorded before the tokens arrived
+    assert!(all_wager_ids(&deps.storage).unwrap().is_empty());
+
+    deps.querier.apply_response(&res_create);
+    let res_created = reply_transfer_from(&mut deps, &res_create).unwrap();
+    assert_eq!("wager_created", res_created.events[0].ty);
+    assert_eq!(
+        1u64,
+        from_binary::<u64>(res_created.data.as_ref().unwrap()).unwrap()
+    );
+    assert_escrow_invariant(&deps);
+
+    let res_join = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &[]),
+        ExecuteMsg::JoinWagerCw20 {
+            wager_id: 1,
+            token: "cw20-token".to_string(),
+            amount: Uint128::new(100),
+        },
+    )
+    .unwrap();
+    deps.querier.apply_response(&res_join);
+    let res_joined = reply_transfer_from(&mut deps, &res_join).unwrap();
+    assert_eq!("wager_joined", res_joined.events[0].ty);
+    assert_escrow_invariant(&deps);
+
+    let wager: Wager =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Wager { id: 1 }).unwrap()).unwrap();
+    assert_eq!(WagerStatus::Matched, wager.status);
+    assert_eq!(Addr::unchecked("new_user2"), wager.user2);
+
+    // a token taking a fee on transfer delivers less than the allowance pulled
+    let res_create_fee = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user3", &[]),
+        ExecuteMsg::CreateWagerCw20 {
+            token: "cw20-token".to_string(),
+            amount: Uint128::new(100),
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    )
+    .unwrap();
+    deps.querier.deposit_cw20("cw20-token", Uint128::new(99));
+    let res_fee = reply_transfer_from(&mut deps, &res_create_fee);
+    assert_eq!(res_fee, Err(ContractError::TransferNotReceived {}));
+
+    let res_zero = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user3", &[]),
+        ExecuteMsg::JoinWagerCw20 {
+            wager_id: 1,
+            token: "cw20-token".to_string(),
+            amount: Uint128::zero(),
+        },
+    );
+    assert_eq!(res_zero, Err(ContractError::EmptyBalance {}));
+}
+
+/// Signs the relay document of an intent as a player's wallet would
+fn sign_intent(key: &SigningKey, intent: &RelayIntent) -> Binary {
+    let env = mock_env();
+    let doc = RelayDoc {
+        chain_id: env.block.chain_id,
+        contract: env.contract.address.to_string(),
+        intent: intent.clone(),
+    };
+    let signature: Signature = key.sign(&to_vec(&doc).unwrap());
+    Binary::from(signature.as_ref())
+}
+
+#[test]
+fn test_execute_relay() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    deps.querier
+        .mint_cw20("cw20-token", "new_user", Uint128::new(100));
+
+    let key = SigningKey::from_bytes(&[7u8; 32]).unwrap();
+    let pubkey = Binary::from(&key.verifying_key().to_bytes()[..]);
+    let res_key = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::SetRelayKey {
+            pubkey: Some(pubkey),
+        },
+    )
+    .unwrap();
+    assert_eq!("relay_key_set", res_key.events[0].ty);
+
+    let intent = RelayIntent {
+        player: "new_user".to_string(),
+        action: RelayAction::CreateWager {
+            token: "cw20-token".to_string(),
+            amount: Uint128::new(100),
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+        nonce: 0,
+        expires: Expiration::AtHeight(mock_env().block.height + 10),
+        pubkey: None,
+    };
+    let relay = ExecuteMsg::Relay {
+        intent: intent.clone(),
+        signature: sign_intent(&key, &intent),
+    };
+
+    // only whitelisted relayers submit intents
+    let res_not_relayer = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("relayer", &[]),
+        relay.clone(),
+    );
+    assert_eq!(res_not_relayer, Err(ContractError::Unauthorized {}));
+
+    let update_relayers = ExecuteMsg::UpdateRelayers {
+        add: vec!["relayer".to_string()],
+        remove: vec![],
+    };
+    let res_not_owner = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("relayer", &[]),
+        update_relayers.clone(),
+    );
+    assert_eq!(
+        res_not_owner,
+        Err(ContractError::MissingRole {
+            role: Role::Operator
+        })
+    );
+    let res_relayers = execute(deps.as_mut(), mock_env(), creator, update_relayers).unwrap();
+    assert_eq!("relayers_updated", res_relayers.events[0].ty);
+    assert_eq!(
+        Some("[\"relayer\"]"),
+        event_attr(&res_relayers.events[0], "added")
+    );
+
+    // the relayer pays the gas, the stake comes from the player's allowance
+    let res_relay = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("relayer", &[]),
+        relay.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res_relay.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "cw20-token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: "new_user".to_string(),
+                recipient: "cosmos2contract".to_string(),
+                amount: Uint128::new(100),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    deps.querier.apply_response(&res_relay);
+    reply_transfer_from(&mut deps, &res_relay).unwrap();
+    assert_escrow_invariant(&deps);
+
+    let wager: Wager =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Wager { id: 1 }).unwrap()).unwrap();
+    assert_eq!(Addr::unchecked("new_user"), wager.user1);
+    assert_eq!(WagerStatus::Open, wager.status);
+
+    let nonce: NonceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Nonce {
+                player: "new_user".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(1, nonce.nonce);
+
+    let res_replay = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), relay);
+    assert_eq!(res_replay, Err(ContractError::InvalidNonce {}));
+
+    // the relayer cannot change what the player signed
+    let mut tampered = intent.clone();
+    tampered.nonce = 1;
+    let signature = sign_intent(&key, &tampered);
+    tampered.action = RelayAction::CreateWager {
+        token: "cw20-token".to_string(),
+        amount: Uint128::new(1000),
+        label: None,
+        stake: None,
+        stake_value: None,
+        series_length: None,
+    };
+    let res_tampered = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("relayer", &[]),
+        ExecuteMsg::Relay {
+            intent: tampered,
+            signature,
+        },
+    );
+    assert_eq!(res_tampered, Err(ContractError::InvalidSignature {}));
+
+    let mut expired = intent;
+    expired.nonce = 1;
+    expired.expires = Expiration::AtHeight(mock_env().block.height);
+    let res_expired = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("relayer", &[]),
+        ExecuteMsg::Relay {
+            signature: sign_intent(&key, &expired),
+            intent: expired,
+        },
+    );
+    assert_eq!(res_expired, Err(ContractError::IntentExpired {}));
+
+    // a player without a registered key carries it in the intent instead
+    let key = SigningKey::from_bytes(&[9u8; 32]).unwrap();
+    let pubkey = Binary::from(&key.verifying_key().to_bytes()[..]);
+    let player = pubkey_to_address(&pubkey, "terra").unwrap();
+    deps.querier
+        .mint_cw20("cw20-token", &player, Uint128::new(100));
+    let mut carried = RelayIntent {
+        player: "new_user2".to_string(),
+        action: RelayAction::JoinWager {
+            wager_id: 1,
+            token: "cw20-token".to_string(),
+            amount: Uint128::new(100),
+        },
+        nonce: 0,
+        expires: Expiration::AtHeight(mock_env().block.height + 10),
+        pubkey: Some(pubkey),
+    };
+    let res_mismatch = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("relayer", &[]),
+        ExecuteMsg::Relay {
+            signature: sign_intent(&key, &carried),
+            intent: carried.clone(),
+        },
+    );
+    assert_eq!(res_mismatch, Err(ContractError::PubkeyMismatch {}));
+
+    carried.player = player.clone();
+    let res_carried = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("relayer", &[]),
+        ExecuteMsg::Relay {
+            signature: sign_intent(&key, &carried),
+            intent: carried,
+        },
+    )
+    .unwrap();
+    deps.querier.apply_response(&res_carried);
+    reply_transfer_from(&mut deps, &res_carried).unwrap();
+    assert_escrow_invariant(&deps);
+    let wager: Wager =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Wager { id: 1 }).unwrap()).unwrap();
+    assert_eq!(Addr::unchecked(player), wager.user2);
+}
+
+#[test]
+fn test_pubkey_to_address() {
+    // key and address from the cosmjs test fixtures
+    let pubkey = from_hex("034f04181eeba35391b858633a765c4a0c189697b40d216354d50890d350c70290");
+    let address = "cosmos1pkptre7fdkl6gfrzlesjjvhxhlc3r4gmmk8rs6";
+    assert_eq!(
+        Some(address),
+        pubkey_to_address(&pubkey, "cosmos").as_deref()
+    );
+    assert!(pubkey_matches_address(&pubkey, address));
+    assert!(pubkey_matches_address(
+        &pubkey,
+        "terra1pkptre7fdkl6gfrzlesjjvhxhlc3r4gmajarj6"
+    ));
+
+    // the secp256k1 generator point, compressed and not, hashes to 751e76e8...33bd6
+    let x = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+    let y = "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+    let compressed = from_hex(&format!("02{}", x));
+    let uncompressed = from_hex(&format!("04{}{}", x, y));
+    let expected = "terra1w508d6qejxtdg4y5r3zarvary0c5xw7kued6dc";
+    assert!(pubkey_matches_address(&compressed, expected));
+    assert!(pubkey_matches_address(&uncompressed, expected));
+    assert_eq!(None, pubkey_to_address(&compressed[1..], "terra"));
+
+    // only valid bech32 addresses of the key match
+    assert!(!pubkey_matches_address(&pubkey, expected));
+    assert!(!pubkey_matches_address(
+        &pubkey,
+        "cosmos1pkptre7fdkl6gfrzlesjjvhxhlc3r4gmmk8rs7"
+    ));
+    assert!(!pubkey_matches_address(
+        &pubkey,
+        "COSMOS1pkptre7fdkl6gfrzlesjjvhxhlc3r4gmmk8rs6"
+    ));
+    assert!(!pubkey_matches_address(&pubkey, "new_user"));
+}
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_blocked_session_key() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::GrantSession {
+            key: "session_key".to_string(),
+            expires: Expiration::Never {},
+            max_stake: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("session_key", &[]),
+        ExecuteMsg::AcceptSession {
+            player: "new_user".to_string(),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator,
+        ExecuteMsg::UpdateBlocklist {
+            add: vec!["session_key".to_string()],
+            remove: vec![],
+        },
+    )
+    .unwrap();
+
+    // the key is blocked even though the player it acts for is not
+    let res_create = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("session_key", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    assert_eq!(res_create, Err(ContractError::Blocked {}));
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    let res_join = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("session_key", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    );
+    assert_eq!(res_join, Err(ContractError::Blocked {}));
+}
+
+#[test]
+fn test_execute_session_keys() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check = instantiate(deps.as_mut(), mock_env(), creator, inst_msg).unwrap();
+
+    deps.querier
+        .mint_cw20("cw20-token", "new_user", Uint128::new(100));
+
+    let expires = Expiration::AtHeight(mock_env().block.height + 100);
+    let grant = |key: &str| ExecuteMsg::GrantSession {
+        key: key.to_string(),
+        expires,
+        max_stake: Some(GenericBalance {
+            native: vec![],
+            cw20: vec![Cw20CoinVerified {
+                address: Addr::unchecked("cw20-token"),
+                amount: Uint128::new(100),
+            }],
+            cw721: vec![],
+        }),
+    };
+    let create_cw20 = |amount: u128| ExecuteMsg::CreateWagerCw20 {
+        token: "cw20-token".to_string(),
+        amount: Uint128::new(amount),
+        label: None,
+        stake: None,
+        stake_value: None,
+        series_length: None,
+    };
+
+    let res_self = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        grant("new_user"),
+    );
+    assert_eq!(res_self, Err(ContractError::InvalidSession {}));
+
+    let res_grant = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        grant("game_key"),
+    )
+    .unwrap();
+    assert_eq!("session_granted", res_grant.events[0].ty);
+    assert_eq!(Some("new_user"), event_attr(&res_grant.events[0], "player"));
+
+    let res_accept_other = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("game_key", &[]),
+        ExecuteMsg::AcceptSession {
+            player: "new_user2".to_string(),
+        },
+    );
+    assert_eq!(res_accept_other, Err(ContractError::Unauthorized {}));
+    let res_accept = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("game_key", &[]),
+        ExecuteMsg::AcceptSession {
+            player: "new_user".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!("session_accepted", res_accept.events[0].ty);
+
+    // a key plays for one player only
+    let res_taken = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &[]),
+        grant("game_key"),
+    );
+    assert_eq!(res_taken, Err(ContractError::InvalidSession {}));
+
+    let res_over_limit = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("game_key", &[]),
+        create_cw20(150),
+    );
+    assert_eq!(res_over_limit, Err(ContractError::SessionLimitExceeded {}));
+
+    // the stake is pulled from the player, who owns the wager
+    let res_create = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("game_key", &[]),
+        create_cw20(100),
+    )
+    .unwrap();
+    deps.querier.apply_response(&res_create);
+    reply_transfer_from(&mut deps, &res_create).unwrap();
+    assert_escrow_invariant(&deps);
+
+    let wager: Wager =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Wager { id: 1 }).unwrap()).unwrap();
+    assert_eq!(Addr::unchecked("new_user"), wager.user1);
+
+    // and refunds go back to the player
+    execute_tracked(
+        &mut deps,
+        mock_info("game_key", &[]),
+        ExecuteMsg::Cancel { wager_id: 1 },
+    );
+    assert_eq!(
+        Uint128::new(100),
+        deps.querier.cw20_balance("cw20-token", "new_user")
+    );
+
+    let mut env_expired = mock_env();
+    env_expired.block.height += 100;
+    let res_expired = execute(
+        deps.as_mut(),
+        env_expired,
+        mock_info("game_key", &[]),
+        create_cw20(100),
+    );
+    assert_eq!(res_expired, Err(ContractError::SessionExpired {}));
+
+    let res_revoke_other = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &[]),
+        ExecuteMsg::RevokeSession {
+            key: "game_key".to_string(),
+        },
+    );
+    assert_eq!(res_revoke_other, Err(ContractError::Unauthorized {}));
+
+    let res_revoke = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::RevokeSession {
+            key: "game_key".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!("session_revoked", res_revoke.events[0].ty);
+
+    let session: SessionResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Session {
+                key: "game_key".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(None, session.session);
+}
+
+#[test]
+fn test_execute_referral_fees() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    let res_invalid_fee = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            fee_bps: Some(10_001),
+            ..ConfigUpdate::default()
+        }),
+    );
+    assert_eq!(res_invalid_fee, Err(ContractError::InvalidFee {}));
+
+    // 5% fee, a fifth of which goes to the referrer
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            fee_bps: Some(500),
+            referral_share_bps: Some(2000),
+            ..ConfigUpdate::default()
+        }),
+    )
+    .unwrap();
+
+    let set_referrer = ExecuteMsg::SetReferrer {
+        referrer: "referrer".to_string(),
+    };
+    let res_referrer = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        set_referrer.clone(),
+    )
+    .unwrap();
+    assert_eq!("referrer_set", res_referrer.events[0].ty);
+
+    let res_again = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        set_referrer,
+    );
+    assert_eq!(res_again, Err(ContractError::ReferrerAlreadySet {}));
+
+    let res_self = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &[]),
+        ExecuteMsg::SetReferrer {
+            referrer: "new_user2".to_string(),
+        },
+    );
+    assert_eq!(res_self, Err(ContractError::InvalidReferrer {}));
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    );
+
+    let res_send_funds = execute_tracked(
+        &mut deps,
+        creator.clone(),
+        ExecuteMsg::SendFunds {
+            wager_id: 1,
+            winner_address: Addr::unchecked("new_user2"),
+        },
+    );
+    assert_eq!(
+        res_send_funds.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "new_user2".to_string(),
+                amount: coins(950, "uluna"),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "new_user2".to_string(),
+                amount: coins(950, "uluna"),
+            })),
+        ]
+    );
+    assert_eq!(
+        Some("{\"native\":[{\"denom\":\"uluna\",\"amount\":\"100\"}],\"cw20\":[]}"),
+        event_attr(&res_send_funds.events[0], "fee")
+    );
+
+    let stats: ReferralStatsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ReferralStats {
+                referrer: "referrer".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(vec![Addr::unchecked("new_user")], stats.players);
+    assert_eq!(coins(1000, "uluna"), stats.volume.native);
+    assert_eq!(coins(10, "uluna"), stats.earnings.native);
+
+    let res_claim = execute_tracked(&mut deps, mock_info("referrer", &[]), ExecuteMsg::Claim {});
+    assert_eq!("claimed", res_claim.events[0].ty);
+    assert_eq!(
+        res_claim.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "referrer".to_string(),
+            amount: coins(10, "uluna"),
+        }))]
+    );
+
+    let res_claim_again = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("referrer", &[]),
+        ExecuteMsg::Claim {},
+    );
+    assert_eq!(res_claim_again, Err(ContractError::NothingToClaim {}));
+
+    // the owner keeps the rest of the fee
+    let res_owner_claim = execute_tracked(&mut deps, creator, ExecuteMsg::Claim {});
+    assert_eq!(
+        res_owner_claim.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "creator".to_string(),
+            amount: coins(90, "uluna"),
+        }))]
+    );
+}
+
+/// Instantiates with a 10% fee, half of the owner's part going to a jackpot won by every draw,
+/// and settles a matched wager of 1000 uluna per side
+fn settle_with_jackpot(
+    randomness: RandomnessSource,
+) -> (OwnedDeps<MockStorage, MockApi, WasmMockQuerier>, Response) {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            fee_bps: Some(1000),
+            jackpot_share_bps: Some(5000),
+            jackpot_odds: Some(1),
+            randomness: Some(randomness),
+            ..ConfigUpdate::default()
+        }),
+    )
+    .unwrap();
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    );
+    // the owner and the later winner commit their secrets for the draw of the wager
+    for (committer, secret) in [("creator", "secret"), ("new_user2", "player secret")] {
+        let res_commit = execute_tracked(
+            &mut deps,
+            mock_info(committer, &[]),
+            ExecuteMsg::CommitJackpotSeed {
+                wager_id: 1,
+                commitment: Binary::from(&Sha256::digest(secret.as_bytes())[..]),
+            },
+        );
+        assert_eq!(
+            Some(committer),
+            event_attr(&res_commit.events[0], "committer")
+        );
+    }
+    let res_send_funds = execute_tracked(
+        &mut deps,
+        creator,
+        ExecuteMsg::SendFunds {
+            wager_id: 1,
+            winner_address: Addr::unchecked("new_user2"),
+        },
+    );
+    (deps, res_send_funds)
+}
+
+#[test]
+fn test_jackpot_beacon_draw() {
+    let (mut deps, res_send_funds) = settle_with_jackpot(RandomnessSource::Beacon {
+        contract: Addr::unchecked(MOCK_BEACON_ADDR),
+    });
+
+    // each side pays 100, 50 of which go to the jackpot, won at once
+    assert_eq!("jackpot_drawn", res_send_funds.events[1].ty);
+    assert_eq!(Some("true"), event_attr(&res_send_funds.events[1], "won"));
+    assert_eq!(
+        res_send_funds.messages[2],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "new_user2".to_string(),
+            amount: coins(100, "uluna"),
+        }))
+    );
+
+    let jackpot: JackpotResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Jackpot { limit: None }).unwrap())
+            .unwrap();
+    assert!(jackpot.jackpot.is_empty());
+    assert_eq!(1, jackpot.wins.len());
+    assert_eq!(Addr::unchecked("new_user2"), jackpot.wins[0].winner);
+    assert_eq!(coins(100, "uluna"), jackpot.wins[0].amount.native);
+
+    let res_owner_claim =
+        execute_tracked(&mut deps, mock_info("creator", &[]), ExecuteMsg::Claim {});
+    assert_eq!(
+        res_owner_claim.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "creator".to_string(),
+            amount: coins(100, "uluna"),
+        }))]
+    );
+
+    // a draw with long odds loses and the jackpot keeps growing
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            jackpot_odds: Some(u64::MAX),
+            ..ConfigUpdate::default()
+        }),
+    )
+    .unwrap();
+    deps.querier.set_randomness(b"next round");
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 2 },
+    );
+    let res_lost = execute_tracked(
+        &mut deps,
+        mock_info("creator", &[]),
+        ExecuteMsg::SendFunds {
+            wager_id: 2,
+            winner_address: Addr::unchecked("new_user"),
+        },
+    );
+    assert_eq!(Some("false"), event_attr(&res_lost.events[1], "won"));
+    assert_eq!(2, res_lost.messages.len());
+    let jackpot: JackpotResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Jackpot { limit: None }).unwrap())
+            .unwrap();
+    assert_eq!(coins(100, "uluna"), jackpot.jackpot.native);
+
+    let res_invalid_share = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            jackpot_share_bps: Some(10_001),
+            ..ConfigUpdate::default()
+        }),
+    );
+    assert_eq!(res_invalid_share, Err(ContractError::InvalidFee {}));
+}
+
+#[test]
+fn test_jackpot_commit_reveal_draw() {
+    let (mut deps, res_send_funds) = settle_with_jackpot(RandomnessSource::CommitReveal {});
+
+    // the draw waits for the secret
+    assert_eq!(1, res_send_funds.events.len());
+    assert_eq!(2, res_send_funds.messages.len());
+
+    let reveal = |secret: &[u8]| ExecuteMsg::RevealJackpotSeed {
+        wager_id: 1,
+        secret: Binary::from(secret),
+    };
+    let res_not_owner = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        reveal(b"secret"),
+    );
+    assert_eq!(
+        res_not_owner,
+        Err(ContractError::MissingRole { role: Role::Owner })
+    );
+
+    let res_wrong_secret = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        reveal(b"other secret"),
+    );
+    assert_eq!(res_wrong_secret, Err(ContractError::InvalidReveal {}));
+
+    // the draw waits for the winner's secret too
+    let res_owner_reveal = execute_tracked(&mut deps, mock_info("creator", &[]), reveal(b"secret"));
+    assert_eq!("jackpot_seed_revealed", res_owner_reveal.events[0].ty);
+    assert!(res_owner_reveal.messages.is_empty());
+    let res_again = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        reveal(b"secret"),
+    );
+    assert_eq!(res_again, Err(ContractError::AlreadyRevealed {}));
+
+    let res_reveal = execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &[]),
+        reveal(b"player secret"),
+    );
+    assert_eq!("jackpot_drawn", res_reveal.events[0].ty);
+    assert_eq!(
+        res_reveal.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "new_user2".to_string(),
+            amount: coins(100, "uluna"),
+        }))]
+    );
+
+    let res_reveal_again = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        reveal(b"secret"),
+    );
+    assert_eq!(res_reveal_again, Err(ContractError::NoPendingDraw {}));
+
+    // every wager is drawn against its own commitments, an owner who withholds the secret
+    // loses the draw to the winner once the deadline passed
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 2 },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("creator", &[]),
+        ExecuteMsg::CommitJackpotSeed {
+            wager_id: 2,
+            commitment: Binary::from(&Sha256::digest(b"second secret")[..]),
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("creator", &[]),
+        ExecuteMsg::SendFunds {
+            wager_id: 2,
+            winner_address: Addr::unchecked("new_user"),
+        },
+    );
+    let timeout = ExecuteMsg::ClaimDrawTimeout { wager_id: 2 };
+    let res_early = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        timeout.clone(),
+    );
+    assert_eq!(res_early, Err(ContractError::RevealPeriodOpen {}));
+    let mut env = mock_env();
+    env.block.height += JACKPOT_REVEAL_PERIOD + 1;
+    let res_late_reveal = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("creator", &[]),
+        ExecuteMsg::RevealJackpotSeed {
+            wager_id: 2,
+            secret: Binary::from(b"second secret"),
+        },
+    );
+    assert_eq!(res_late_reveal, Err(ContractError::RevealPeriodOver {}));
+    let res_timeout = execute(deps.as_mut(), env, mock_info("new_user", &[]), timeout).unwrap();
+    assert_eq!("jackpot_drawn", res_timeout.events[0].ty);
+    assert_eq!(Some("true"), event_attr(&res_timeout.events[0], "won"));
+    assert_eq!(
+        res_timeout.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "new_user".to_string(),
+            amount: coins(100, "uluna"),
+        }))]
+    );
+}
+
+fn commit_choice(choice: u8, salt: &[u8]) -> Binary {
+    Binary::from(&Sha256::new().chain([choice]).chain(salt).finalize()[..])
+}
+
+/// Creates game of chance 1 between new_user and new_user2 with a stake of 1000uluna each and
+/// a reveal period of 10 blocks.
+fn match_chance_wager(
+    game: ChanceGameKind,
+    user1_choice: u8,
+    user2_choice: u8,
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check = instantiate(deps.as_mut(), mock_env(), creator, inst_msg).unwrap();
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateChanceWager {
+            game,
+            commitment: commit_choice(user1_choice, b"salt1"),
+            reveal_period: 10,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::JoinChanceWager {
+            wager_id: 1,
+            commitment: commit_choice(user2_choice, b"salt2"),
+        },
+    );
+    deps
+}
+
+fn reveal_choice(choice: u8, salt: &[u8]) -> ExecuteMsg {
+    ExecuteMsg::RevealChoice {
+        wager_id: 1,
+        choice,
+        salt: Binary::from(salt),
+    }
+}
+
+#[test]
+fn test_chance_wager_rock_paper_scissors() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    let res_zero_period = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateChanceWager {
+            game: ChanceGameKind::RockPaperScissors,
+            commitment: commit_choice(0, b"salt1"),
+            reveal_period: 0,
+        },
+    );
+    assert_eq!(res_zero_period, Err(ContractError::InvalidRevealPeriod {}));
+    let res_long_period = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateChanceWager {
+            game: ChanceGameKind::RockPaperScissors,
+            commitment: commit_choice(0, b"salt1"),
+            reveal_period: MAX_REVEAL_PERIOD + 1,
+        },
+    );
+    assert_eq!(res_long_period, Err(ContractError::InvalidRevealPeriod {}));
+
+    // rock against scissors
+    let res_create = execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateChanceWager {
+            game: ChanceGameKind::RockPaperScissors,
+            commitment: commit_choice(0, b"salt1"),
+            reveal_period: 10,
+        },
+    );
+    assert_eq!(1, res_create.events.len());
+    assert_eq!("wager_created", res_create.events[0].ty);
+    assert_eq!(
+        Some("rock_paper_scissors"),
+        event_attr(&res_create.events[0], "chance_game")
+    );
+
+    // the opponent must commit to a choice to join
+    let res_add_funds = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    );
+    assert_eq!(res_add_funds, Err(ContractError::CommitmentRequired {}));
+
+    let res_reveal_open = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        reveal_choice(0, b"salt1"),
+    );
+    assert_eq!(res_reveal_open, Err(ContractError::Unauthorized {}));
+
+    let res_join = execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::JoinChanceWager {
+            wager_id: 1,
+            commitment: commit_choice(2, b"salt2"),
+        },
+    );
+    assert_eq!("wager_joined", res_join.events[0].ty);
+    let wager: Wager =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Wager { id: 1 }).unwrap()).unwrap();
+    assert_eq!(WagerStatus::Matched, wager.status);
+    assert_eq!(
+        Some(mock_env().block.height + 10),
+        wager.chance.unwrap().reveal_deadline
+    );
+
+    // the game server cannot pick the winner
+    let res_send_funds = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator,
+        ExecuteMsg::SendFunds {
+            wager_id: 1,
+            winner_address: Addr::unchecked("new_user2"),
+        },
+    );
+    assert_eq!(res_send_funds, Err(ContractError::Unauthorized {}));
+
+    let res_wrong_salt = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        reveal_choice(0, b"other salt"),
+    );
+    assert_eq!(res_wrong_salt, Err(ContractError::InvalidReveal {}));
+
+    let res_invalid_choice = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        reveal_choice(3, b"salt1"),
+    );
+    assert_eq!(res_invalid_choice, Err(ContractError::InvalidChoice {}));
+
+    let res_stranger = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("stranger", &[]),
+        reveal_choice(0, b"salt1"),
+    );
+    assert_eq!(res_stranger, Err(ContractError::Unauthorized {}));
+
+    let res_reveal1 = execute_tracked(
+        &mut deps,
+        mock_info("new_user", &[]),
+        reveal_choice(0, b"salt1"),
+    );
+    assert_eq!(1, res_reveal1.events.len());
+    assert_eq!("choice_revealed", res_reveal1.events[0].ty);
+    assert_eq!(
+        Some("new_user"),
+        event_attr(&res_reveal1.events[0], "player")
+    );
+    assert_eq!(Some("0"), event_attr(&res_reveal1.events[0], "choice"));
+    assert!(res_reveal1.messages.is_empty());
+
+    // a revealed choice gives the outcome away, bets are closed even without a pool
+    let res_bet = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("spectator1", &coins(100, "uluna")),
+        ExecuteMsg::PlaceSideBet {
+            wager_id: 1,
+            side: Side::User1,
+        },
+    );
+    assert_eq!(res_bet, Err(ContractError::SideBetsClosed {}));
+
+    let res_reveal_again = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        reveal_choice(0, b"salt1"),
+    );
+    assert_eq!(res_reveal_again, Err(ContractError::AlreadyRevealed {}));
+
+    // rock beats scissors, the contract settles as soon as both revealed
+    let res_reveal2 = execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &[]),
+        reveal_choice(2, b"salt2"),
+    );
+    assert_eq!("choice_revealed", res_reveal2.events[0].ty);
+    assert_eq!("wager_settled", res_reveal2.events[1].ty);
+    assert_eq!(
+        Some("new_user"),
+        event_attr(&res_reveal2.events[1], "winner")
+    );
+    assert_eq!(
+        res_reveal2.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "new_user".to_string(),
+                amount: coins(1000, "uluna"),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "new_user".to_string(),
+                amount: coins(1000, "uluna"),
+            })),
+        ]
+    );
+    assert!(all_wager_ids(&deps.storage).unwrap().is_empty());
+}
+
+#[test]
+fn test_chance_wager_draw_refunds() {
+    let mut deps = match_chance_wager(ChanceGameKind::RockPaperScissors, 1, 1);
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &[]),
+        reveal_choice(1, b"salt1"),
+    );
+    let res_reveal = execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &[]),
+        reveal_choice(1, b"salt2"),
+    );
+    assert_eq!("wager_settled", res_reveal.events[1].ty);
+    assert_eq!(None, event_attr(&res_reveal.events[1], "winner"));
+    assert_eq!(
+        res_reveal.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "new_user".to_string(),
+                amount: coins(1000, "uluna"),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "new_user2".to_string(),
+                amount: coins(1000, "uluna"),
+            })),
+        ]
+    );
+}
+
+#[test]
+fn test_chance_wager_coin_flip() {
+    // the coin lands on the parity of the first byte of the hash of both salts
+    let side = Sha256::new().chain(b"salt1").chain(b"salt2").finalize()[0] % 2;
+    let mut deps = match_chance_wager(ChanceGameKind::CoinFlip, side, 0);
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &[]),
+        reveal_choice(0, b"salt2"),
+    );
+    let res_reveal = execute_tracked(
+        &mut deps,
+        mock_info("new_user", &[]),
+        reveal_choice(side, b"salt1"),
+    );
+    assert_eq!(
+        Some("new_user"),
+        event_attr(&res_reveal.events[1], "winner")
+    );
+}
+
+#[test]
+fn test_chance_wager_reveal_timeout() {
+    let mut deps = match_chance_wager(ChanceGameKind::Dice, 0, 0);
+    let mut env_late = mock_env();
+    env_late.block.height += 11;
+
+    let res_early = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::ClaimRevealTimeout { wager_id: 1 },
+    );
+    assert_eq!(res_early, Err(ContractError::RevealPeriodOpen {}));
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &[]),
+        reveal_choice(0, b"salt2"),
+    );
+
+    let res_late_reveal = execute(
+        deps.as_mut(),
+        env_late.clone(),
+        mock_info("new_user", &[]),
+        reveal_choice(0, b"salt1"),
+    );
+    assert_eq!(res_late_reveal, Err(ContractError::RevealPeriodOver {}));
+
+    // the player who did not reveal forfeits
+    let res_timeout = execute(
+        deps.as_mut(),
+        env_late.clone(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::ClaimRevealTimeout { wager_id: 1 },
+    )
+    .unwrap();
+    deps.querier.apply_response(&res_timeout);
+    assert_escrow_invariant(&deps);
+    assert_eq!("wager_settled", res_timeout.events[0].ty);
+    assert_eq!(
+        Some("new_user2"),
+        event_attr(&res_timeout.events[0], "winner")
+    );
+    assert_eq!(2, res_timeout.messages.len());
+
+    // nobody revealed, both stakes are refunded
+    let mut deps = match_chance_wager(ChanceGameKind::Dice, 0, 0);
+    let res_expired = execute(
+        deps.as_mut(),
+        env_late,
+        mock_info("anyone", &[]),
+        ExecuteMsg::ClaimRevealTimeout { wager_id: 1 },
+    )
+    .unwrap();
+    deps.querier.apply_response(&res_expired);
+    assert_escrow_invariant(&deps);
+    assert_eq!("wager_expired", res_expired.events[0].ty);
+    assert_eq!(
+        Some("expired"),
+        event_attr(&res_expired.events[0], "status")
+    );
+    assert_eq!(
+        res_expired.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "new_user".to_string(),
+                amount: coins(1000, "uluna"),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "new_user2".to_string(),
+                amount: coins(1000, "uluna"),
+            })),
+        ]
+    );
+}
+
+#[test]
+fn test_series_wager_report_rounds() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    let create_series = |series_length| ExecuteMsg::CreateWagerNative {
+        label: None,
+        stake: None,
+        stake_value: None,
+        series_length: Some(series_length),
+    };
+    let res_even = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(1000, "uluna")),
+        create_series(4),
+    );
+    assert_eq!(res_even, Err(ContractError::InvalidSeriesLength {}));
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        create_series(3),
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    );
+
+    let report = |round, winner: &str| ExecuteMsg::ReportRound {
+        wager_id: 1,
+        round,
+        winner: winner.to_string(),
+    };
+    let res_not_owner = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        report(1, "new_user"),
+    );
+    assert_eq!(
+        res_not_owner,
+        Err(ContractError::MissingRole {
+            role: Role::Arbiter
+        })
+    );
+
+    let res_stranger = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        report(1, "stranger"),
+    );
+    assert_eq!(res_stranger, Err(ContractError::UserDoesNotExist {}));
+
+    let res_skipped = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        report(2, "new_user"),
+    );
+    assert_eq!(res_skipped, Err(ContractError::InvalidRound {}));
+
+    let res_round1 = execute_tracked(&mut deps, creator.clone(), report(1, "new_user2"));
+    assert_eq!(1, res_round1.events.len());
+    assert_eq!("round_reported", res_round1.events[0].ty);
+    assert_eq!(Some("1"), event_attr(&res_round1.events[0], "round"));
+    assert_eq!(
+        Some("new_user2"),
+        event_attr(&res_round1.events[0], "winner")
+    );
+    assert_eq!(Some("0"), event_attr(&res_round1.events[0], "user1_wins"));
+    assert_eq!(Some("1"), event_attr(&res_round1.events[0], "user2_wins"));
+    assert!(res_round1.messages.is_empty());
+
+    let res_replayed = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        report(1, "new_user2"),
+    );
+    assert_eq!(res_replayed, Err(ContractError::InvalidRound {}));
+
+    execute_tracked(&mut deps, creator.clone(), report(2, "new_user"));
+
+    let rounds: RoundsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Rounds { wager_id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(
+        rounds,
+        RoundsResponse {
+            series_length: 3,
+            rounds: vec![Addr::unchecked("new_user2"), Addr::unchecked("new_user")],
+            user1_wins: 1,
+            user2_wins: 1,
+        }
+    );
+
+    // the deciding round releases the pot
+    let res_round3 = execute_tracked(&mut deps, creator, report(3, "new_user"));
+    assert_eq!("round_reported", res_round3.events[0].ty);
+    assert_eq!("wager_settled", res_round3.events[1].ty);
+    assert_eq!(
+        Some("new_user"),
+        event_attr(&res_round3.events[1], "winner")
+    );
+    assert_eq!(
+        res_round3.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "new_user".to_string(),
+                amount: coins(1000, "uluna"),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "new_user".to_string(),
+                amount: coins(1000, "uluna"),
+            })),
+        ]
+    );
+    assert!(all_wager_ids(&deps.storage).unwrap().is_empty());
+}
+
+#[test]
+fn test_report_round_needs_series() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    );
+
+    let res_report = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator,
+        ExecuteMsg::ReportRound {
+            wager_id: 1,
+            round: 1,
+            winner: "new_user".to_string(),
+        },
+    );
+    assert_eq!(res_report, Err(ContractError::NotSeries {}));
+    assert!(query(deps.as_ref(), mock_env(), QueryMsg::Rounds { wager_id: 1 }).is_err());
+}
+
+#[test]
+fn test_side_bets_paid_to_winning_side() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            fee_bps: Some(1000),
+            ..ConfigUpdate::default()
+        }),
+    )
+    .unwrap();
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+
+    let bet = |side| ExecuteMsg::PlaceSideBet { wager_id: 1, side };
+    let res_open = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("spectator1", &coins(300, "uluna")),
+        bet(Side::User1),
+    );
+    assert_eq!(res_open, Err(ContractError::SideBetsClosed {}));
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    );
+
+    let res_player = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &coins(300, "uluna")),
+        bet(Side::User2),
+    );
+    assert_eq!(res_player, Err(ContractError::Unauthorized {}));
+
+    let res_bet = execute_tracked(
+        &mut deps,
+        mock_info("spectator1", &coins(300, "uluna")),
+        bet(Side::User1),
+    );
+    assert_eq!("side_bet_placed", res_bet.events[0].ty);
+    assert_eq!(Some("user1"), event_attr(&res_bet.events[0], "side"));
+    assert_eq!(Some("300"), event_attr(&res_bet.events[0], "user1_total"));
+    execute_tracked(
+        &mut deps,
+        mock_info("spectator2", &coins(100, "uluna")),
+        bet(Side::User2),
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("spectator3", &coins(100, "uluna")),
+        bet(Side::User1),
+    );
+
+    // the first bet set the asset of the pool
+    let res_other_asset = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("spectator2", &coins(100, "uusd")),
+        bet(Side::User2),
+    );
+    assert_eq!(res_other_asset, Err(ContractError::InvalidSideBet {}));
+
+    let res_early_claim = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("spectator1", &[]),
+        ExecuteMsg::ClaimSideBet { wager_id: 1 },
+    );
+    assert_eq!(res_early_claim, Err(ContractError::SideBetsPending {}));
+
+    let res_lock_not_owner = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::LockSideBets { wager_id: 1 },
+    );
+    assert_eq!(
+        res_lock_not_owner,
+        Err(ContractError::MissingRole {
+            role: Role::Arbiter
+        })
+    );
+    let res_lock = execute_tracked(
+        &mut deps,
+        creator.clone(),
+        ExecuteMsg::LockSideBets { wager_id: 1 },
+    );
+    assert_eq!("side_bets_locked", res_lock.events[0].ty);
+
+    let res_locked = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("spectator2", &coins(100, "uluna")),
+        bet(Side::User2),
+    );
+    assert_eq!(res_locked, Err(ContractError::SideBetsClosed {}));
+
+    let res_send_funds = execute_tracked(
+        &mut deps,
+        creator,
+        ExecuteMsg::SendFunds {
+            wager_id: 1,
+            winner_address: Addr::unchecked("new_user"),
+        },
+    );
+    assert_eq!("side_pool_closed", res_send_funds.events[1].ty);
+    assert_eq!(
+        Some("user1"),
+        event_attr(&res_send_funds.events[1], "winning_side")
+    );
+    assert_eq!(Some("50"), event_attr(&res_send_funds.events[1], "fee"));
+
+    let pool: SidePool = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SidePool { wager_id: 1 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(SidePoolStatus::Won { side: Side::User1 }, pool.status);
+    assert_eq!(Uint128::new(450), pool.payout);
+
+    // the winning bets share the pool less the fee in proportion to their amount
+    let claim = ExecuteMsg::ClaimSideBet { wager_id: 1 };
+    let res_claim1 = execute_tracked(&mut deps, mock_info("spectator1", &[]), claim.clone());
+    assert_eq!(
+        res_claim1.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "spectator1".to_string(),
+            amount: coins(337, "uluna"),
+        }))]
+    );
+    let res_claim3 = execute_tracked(&mut deps, mock_info("spectator3", &[]), claim.clone());
+    assert_eq!(
+        res_claim3.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "spectator3".to_string(),
+            amount: coins(112, "uluna"),
+        }))]
+    );
+
+    let res_claim_again = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("spectator1", &[]),
+        claim.clone(),
+    );
+    assert_eq!(res_claim_again, Err(ContractError::NothingToClaim {}));
+    let res_claim_lost = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("spectator2", &[]),
+        claim,
+    );
+    assert_eq!(res_claim_lost, Err(ContractError::NothingToClaim {}));
+
+    let bet: SideBet = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SideBet {
+                wager_id: 1,
+                bettor: "spectator2".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(Uint128::new(100), bet.user2);
+}
+
+#[test]
+fn test_side_bets_refunded_without_winner() {
+    let mut deps = match_chance_wager(ChanceGameKind::CoinFlip, 0, 0);
+
+    execute_tracked(
+        &mut deps,
+        mock_info("spectator1", &coins(300, "uluna")),
+        ExecuteMsg::PlaceSideBet {
+            wager_id: 1,
+            side: Side::User1,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("spectator2", &coins(100, "uluna")),
+        ExecuteMsg::PlaceSideBet {
+            wager_id: 1,
+            side: Side::User2,
+        },
+    );
+
+    // nobody revealed, the wager and the side pool are refunded
+    let mut env_late = mock_env();
+    env_late.block.height += 11;
+    let res_expired = execute(
+        deps.as_mut(),
+        env_late,
+        mock_info("anyone", &[]),
+        ExecuteMsg::ClaimRevealTimeout { wager_id: 1 },
+    )
+    .unwrap();
+    deps.querier.apply_response(&res_expired);
+    assert_escrow_invariant(&deps);
+    assert_eq!("side_pool_closed", res_expired.events[1].ty);
+    assert_eq!(
+        Some("refunded"),
+        event_attr(&res_expired.events[1], "status")
+    );
+
+    let res_claim = execute_tracked(
+        &mut deps,
+        mock_info("spectator1", &[]),
+        ExecuteMsg::ClaimSideBet { wager_id: 1 },
+    );
+    assert_eq!(
+        res_claim.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "spectator1".to_string(),
+            amount: coins(300, "uluna"),
+        }))]
+    );
+}
+
+#[test]
+fn test_side_bets_close_when_play_starts() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+    execute_tracked(
+        &mut deps,
+        creator.clone(),
+        ExecuteMsg::GrantRole {
+            role: Role::Arbiter,
+            address: "referee".to_string(),
+            game_id: None,
+        },
+    );
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: Some(3),
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    );
+
+    // any arbiter able to settle the wager is barred, not only its own
+    let bet = |side| ExecuteMsg::PlaceSideBet { wager_id: 1, side };
+    let res_referee = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("referee", &coins(100, "uluna")),
+        bet(Side::User1),
+    );
+    assert_eq!(res_referee, Err(ContractError::Unauthorized {}));
+
+    execute_tracked(
+        &mut deps,
+        mock_info("spectator1", &coins(100, "uluna")),
+        bet(Side::User1),
+    );
+
+    // the first reported round closes the pool without a LockSideBets
+    let res_round = execute_tracked(
+        &mut deps,
+        mock_info("referee", &[]),
+        ExecuteMsg::ReportRound {
+            wager_id: 1,
+            round: 1,
+            winner: "new_user".to_string(),
+        },
+    );
+    assert_eq!("round_reported", res_round.events[0].ty);
+    assert_eq!("side_bets_locked", res_round.events[1].ty);
+
+    let res_late = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("spectator2", &coins(100, "uluna")),
+        bet(Side::User1),
+    );
+    assert_eq!(res_late, Err(ContractError::SideBetsClosed {}));
+    let res_lock = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator,
+        ExecuteMsg::LockSideBets { wager_id: 1 },
+    );
+    assert_eq!(res_lock, Err(ContractError::SideBetsClosed {}));
+}
+
+#[test]
+fn test_roles_and_pause() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    let roles = |deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>, role| {
+        let res: RolesResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Roles {
+                    role,
+                    game_id: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.addresses
+    };
+    assert_eq!(vec![Addr::unchecked("creator")], roles(&deps, Role::Owner));
+
+    let grant = |role, address: &str| ExecuteMsg::GrantRole {
+        role,
+        address: address.to_string(),
+        game_id: None,
+    };
+    let res_not_owner = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        grant(Role::Operator, "operator"),
+    );
+    assert_eq!(
+        res_not_owner,
+        Err(ContractError::MissingRole { role: Role::Owner })
+    );
+
+    let res_grant = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        grant(Role::Operator, "operator"),
+    )
+    .unwrap();
+    assert_eq!("role_granted", res_grant.events[0].ty);
+    assert_eq!(Some("operator"), event_attr(&res_grant.events[0], "role"));
+    for (role, address) in [(Role::Arbiter, "arbiter"), (Role::Guardian, "guardian")] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            creator.clone(),
+            grant(role, address),
+        )
+        .unwrap();
+    }
+    assert_eq!(
+        vec![Addr::unchecked("operator")],
+        roles(&deps, Role::Operator)
+    );
+
+    // operators manage ratings but not the configuration
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::SetRating {
+            player: "new_user".to_string(),
+            rating: 1500,
+        },
+    )
+    .unwrap();
+    let res_config = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::UpdateConfig(ConfigUpdate::default()),
+    );
+    assert_eq!(
+        res_config,
+        Err(ContractError::MissingRole { role: Role::Owner })
+    );
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    );
+
+    // guardians pause, new wagers are refused but running ones can end
+    let res_pause_operator = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::Pause {},
+    );
+    assert_eq!(
+        res_pause_operator,
+        Err(ContractError::MissingRole {
+            role: Role::Guardian
+        })
+    );
+    let res_pause = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian", &[]),
+        ExecuteMsg::Pause {},
+    )
+    .unwrap();
+    assert_eq!("paused", res_pause.events[0].ty);
+
+    let res_paused = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    assert_eq!(res_paused, Err(ContractError::Paused {}));
+
+    let res_send_funds = execute_tracked(
+        &mut deps,
+        mock_info("arbiter", &[]),
+        ExecuteMsg::SendFunds {
+            wager_id: 1,
+            winner_address: Addr::unchecked("new_user2"),
+        },
+    );
+    assert_eq!("wager_settled", res_send_funds.events[0].ty);
+
+    let res_unpause_guardian = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian", &[]),
+        ExecuteMsg::Unpause {},
+    );
+    assert_eq!(
+        res_unpause_guardian,
+        Err(ContractError::MissingRole { role: Role::Owner })
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::Unpause {},
+    )
+    .unwrap();
+    let state: State =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert!(!state.paused);
+
+    let revoke = |role, address: &str| ExecuteMsg::RevokeRole {
+        role,
+        address: address.to_string(),
+        game_id: None,
+    };
+    let res_revoke_self = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        revoke(Role::Owner, "creator"),
+    );
+    assert_eq!(res_revoke_self, Err(ContractError::OwnOwnerRole {}));
+
+    let res_revoke = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator,
+        revoke(Role::Operator, "operator"),
+    )
+    .unwrap();
+    assert_eq!("role_revoked", res_revoke.events[0].ty);
+    assert!(roles(&deps, Role::Operator).is_empty());
+}
+
+#[test]
+fn test_vote_result_high_stakes() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    let threshold = GenericBalance {
+        native: coins(1000, "uluna"),
+        cw20: vec![],
+        cw721: vec![],
+    };
+    let res_no_approvals = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            approval_threshold: Some(threshold.clone()),
+            ..ConfigUpdate::default()
+        }),
+    );
+    assert_eq!(res_no_approvals, Err(ContractError::InvalidApprovals {}));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            approval_threshold: Some(threshold),
+            required_approvals: Some(2),
+            ..ConfigUpdate::default()
+        }),
+    )
+    .unwrap();
+    for arbiter in ["arbiter1", "arbiter2", "arbiter3"] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            creator.clone(),
+            ExecuteMsg::GrantRole {
+                role: Role::Arbiter,
+                address: arbiter.to_string(),
+                game_id: None,
+            },
+        )
+        .unwrap();
+    }
+
+    for wager_id in 1..=2 {
+        execute_tracked(
+            &mut deps,
+            mock_info("new_user", &coins(1000, "uluna")),
+            ExecuteMsg::CreateWagerNative {
+                label: None,
+                stake: None,
+                stake_value: None,
+                series_length: None,
+            },
+        );
+        execute_tracked(
+            &mut deps,
+            mock_info("new_user2", &coins(1000, "uluna")),
+            ExecuteMsg::AddFundsNative { wager_id },
+        );
+    }
+
+    let res_send_funds = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::SendFunds {
+            wager_id: 1,
+            winner_address: Addr::unchecked("new_user"),
+        },
+    );
+    assert_eq!(res_send_funds, Err(ContractError::ApprovalsRequired {}));
+
+    let vote = |wager_id, winner: &str| ExecuteMsg::VoteResult {
+        wager_id,
+        winner: winner.to_string(),
+    };
+    let res_not_arbiter = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        vote(1, "new_user"),
+    );
+    assert_eq!(
+        res_not_arbiter,
+        Err(ContractError::MissingRole {
+            role: Role::Arbiter
+        })
+    );
+
+    let res_vote1 = execute_tracked(&mut deps, mock_info("arbiter1", &[]), vote(1, "new_user"));
+    assert_eq!(1, res_vote1.events.len());
+    assert_eq!("result_voted", res_vote1.events[0].ty);
+    assert_eq!(Some("1"), event_attr(&res_vote1.events[0], "approvals"));
+    assert_eq!(Some("2"), event_attr(&res_vote1.events[0], "required"));
+
+    let res_vote_again = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("arbiter1", &[]),
+        vote(1, "new_user"),
+    );
+    assert_eq!(res_vote_again, Err(ContractError::AlreadyVoted {}));
+
+    let votes: VotesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Votes { wager_id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(
+        vec![Vote {
+            arbiter: Addr::unchecked("arbiter1"),
+            winner: Addr::unchecked("new_user"),
+        }],
+        votes.votes
+    );
+
+    // the second matching vote releases the pot
+    let res_vote2 = execute_tracked(&mut deps, mock_info("arbiter2", &[]), vote(1, "new_user"));
+    assert_eq!("wager_settled", res_vote2.events[1].ty);
+    assert_eq!(Some("new_user"), event_attr(&res_vote2.events[1], "winner"));
+    assert_eq!(2, res_vote2.messages.len());
+
+    // conflicting votes dispute the wager, which only an owner can settle
+    execute_tracked(&mut deps, mock_info("arbiter1", &[]), vote(2, "new_user"));
+    let res_conflict = execute_tracked(&mut deps, mock_info("arbiter2", &[]), vote(2, "new_user2"));
+    assert_eq!("wager_disputed", res_conflict.events[1].ty);
+    assert_eq!(
+        Some("disputed"),
+        event_attr(&res_conflict.events[1], "status")
+    );
+
+    let res_vote_disputed = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("arbiter3", &[]),
+        vote(2, "new_user2"),
+    );
+    assert_eq!(res_vote_disputed, Err(ContractError::Disputed {}));
+    let res_cancel = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::Cancel { wager_id: 2 },
+    );
+    assert_eq!(res_cancel, Err(ContractError::Unauthorized {}));
+    let res_arbiter_settle = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("arbiter3", &[]),
+        ExecuteMsg::SendFunds {
+            wager_id: 2,
+            winner_address: Addr::unchecked("new_user2"),
+        },
+    );
+    assert_eq!(
+        res_arbiter_settle,
+        Err(ContractError::MissingRole { role: Role::Owner })
+    );
+
+    let res_resolve = execute_tracked(
+        &mut deps,
+        creator,
+        ExecuteMsg::SendFunds {
+            wager_id: 2,
+            winner_address: Addr::unchecked("new_user2"),
+        },
+    );
+    assert_eq!("wager_settled", res_resolve.events[0].ty);
+    assert!(all_wager_ids(&deps.storage).unwrap().is_empty());
+}
+
+#[test]
+fn test_timelocked_config_change() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    // a delay longer than 30 days could lock the configuration for good
+    let res_long_delay = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            config_delay: Some(MAX_CONFIG_DELAY + 1),
+            ..ConfigUpdate::default()
+        }),
+    );
+    assert_eq!(res_long_delay, Err(ContractError::InvalidConfigDelay {}));
+
+    // without a delay updates apply at once, including the one setting the delay
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            config_delay: Some(86_400),
+            ..ConfigUpdate::default()
+        }),
+    )
+    .unwrap();
+    let fee_update = ConfigUpdate {
+        fee_bps: Some(500),
+        ..ConfigUpdate::default()
+    };
+    let res_update = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::UpdateConfig(fee_update.clone()),
+    );
+    assert_eq!(res_update, Err(ContractError::Timelocked {}));
+
+    let res_invalid = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::ProposeConfigChange {
+            update: TimelockedUpdate::Config(ConfigUpdate {
+                fee_bps: Some(20_000),
+                ..ConfigUpdate::default()
+            }),
+        },
+    );
+    assert_eq!(res_invalid, Err(ContractError::InvalidFee {}));
+    let res_not_owner = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::ProposeConfigChange {
+            update: TimelockedUpdate::Config(fee_update.clone()),
+        },
+    );
+    assert_eq!(
+        res_not_owner,
+        Err(ContractError::MissingRole { role: Role::Owner })
+    );
+
+    let res_propose = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::ProposeConfigChange {
+            update: TimelockedUpdate::Config(fee_update.clone()),
+        },
+    )
+    .unwrap();
+    let event = &res_propose.events[0];
+    assert_eq!("config_change_proposed", event.ty);
+    assert_eq!(Some("1"), event_attr(event, "change_id"));
+    let eta = mock_env().block.time.plus_seconds(86_400);
+    assert_eq!(
+        Some(eta.seconds().to_string().as_str()),
+        event_attr(event, "eta")
+    );
+
+    let changes: ConfigChangesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ConfigChanges {}).unwrap())
+            .unwrap();
+    assert_eq!(1, changes.changes.len());
+    assert_eq!(eta, changes.changes[0].eta);
+    assert_eq!(
+        TimelockedUpdate::Config(fee_update),
+        changes.changes[0].update
+    );
+
+    let res_early = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::ExecuteConfigChange { id: 1 },
+    );
+    assert_eq!(res_early, Err(ContractError::ChangeNotReady {}));
+
+    let mut env = mock_env();
+    env.block.time = eta;
+    let res_execute = execute(
+        deps.as_mut(),
+        env.clone(),
+        creator.clone(),
+        ExecuteMsg::ExecuteConfigChange { id: 1 },
+    )
+    .unwrap();
+    let event = &res_execute.events[0];
+    assert_eq!("config_updated", event.ty);
+    assert_eq!(Some("1"), event_attr(event, "change_id"));
+    assert_eq!(Some("500"), event_attr(event, "fee_bps"));
+    let config: State =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(500, config.fee_bps);
+    assert!(execute(
+        deps.as_mut(),
+        env.clone(),
+        creator.clone(),
+        ExecuteMsg::ExecuteConfigChange { id: 1 },
+    )
+    .is_err());
+
+    // a cancelled change is dropped from the queue
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::ProposeConfigChange {
+            update: TimelockedUpdate::Config(ConfigUpdate {
+                config_delay: Some(0),
+                ..ConfigUpdate::default()
+            }),
+        },
+    )
+    .unwrap();
+    let res_cancel = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::CancelConfigChange { id: 2 },
+    )
+    .unwrap();
+    assert_eq!("config_change_cancelled", res_cancel.events[0].ty);
+    let changes: ConfigChangesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ConfigChanges {}).unwrap())
+            .unwrap();
+    assert!(changes.changes.is_empty());
+
+    // roles, relayers and the blocklist wait for the delay too
+    let grant = |address: &str| TimelockedUpdate::GrantRole {
+        role: Role::Operator,
+        address: address.to_string(),
+        game_id: None,
+    };
+    let res_grant = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::GrantRole {
+            role: Role::Operator,
+            address: "operator".to_string(),
+            game_id: None,
+        },
+    );
+    assert_eq!(res_grant, Err(ContractError::Timelocked {}));
+    let res_scoped = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::ProposeConfigChange {
+            update: TimelockedUpdate::GrantRole {
+                role: Role::Operator,
+                address: "operator".to_string(),
+                game_id: Some("chess".to_string()),
+            },
+        },
+    );
+    assert_eq!(res_scoped, Err(ContractError::GameScopedRole {}));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::ProposeConfigChange {
+            update: grant("operator"),
+        },
+    )
+    .unwrap();
+    let res_execute = execute(
+        deps.as_mut(),
+        env.clone(),
+        creator,
+        ExecuteMsg::ExecuteConfigChange { id: 3 },
+    )
+    .unwrap();
+    assert_eq!("role_granted", res_execute.events[0].ty);
+    assert_eq!(
+        Some("operator"),
+        event_attr(&res_execute.events[0], "address")
+    );
+
+    // operators propose and execute relayer and blocklist updates
+    let block = ExecuteMsg::UpdateBlocklist {
+        add: vec!["cheater".to_string()],
+        remove: vec![],
+    };
+    let res_block = execute(deps.as_mut(), mock_env(), mock_info("operator", &[]), block);
+    assert_eq!(res_block, Err(ContractError::Timelocked {}));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::ProposeConfigChange {
+            update: TimelockedUpdate::UpdateBlocklist {
+                add: vec!["cheater".to_string()],
+                remove: vec![],
+            },
+        },
+    )
+    .unwrap();
+    let res_early = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::ExecuteConfigChange { id: 4 },
+    );
+    assert_eq!(res_early, Err(ContractError::ChangeNotReady {}));
+    let res_execute = execute(
+        deps.as_mut(),
+        env,
+        mock_info("operator", &[]),
+        ExecuteMsg::ExecuteConfigChange { id: 4 },
+    )
+    .unwrap();
+    assert_eq!("blocklist_updated", res_execute.events[0].ty);
+    assert!(BLOCKLIST.has(&deps.storage, &Addr::unchecked("cheater")));
+}
+
+#[test]
+fn test_stake_limits_and_self_exclusion() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check = instantiate(deps.as_mut(), mock_env(), creator, inst_msg).unwrap();
+
+    let create = ExecuteMsg::CreateWagerNative {
+        label: None,
+        stake: None,
+        stake_value: None,
+        series_length: None,
+    };
+    let limits = |daily: u128, weekly: u128| StakeLimits {
+        daily: GenericBalance {
+            native: coins(daily, "uluna"),
+            cw20: vec![],
+            cw721: vec![],
+        },
+        weekly: GenericBalance {
+            native: coins(weekly, "uluna"),
+            cw20: vec![],
+            cw721: vec![],
+        },
+        monthly: GenericBalance::new(),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::SetStakeLimits {
+            limits: limits(1000, 1500),
+        },
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(600, "uluna")),
+        create.clone(),
+    )
+    .unwrap();
+    let res_daily = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(500, "uluna")),
+        create.clone(),
+    );
+    assert_eq!(res_daily, Err(ContractError::StakeLimitExceeded {}));
+    // tokens without a cap are not limited
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(5000, "uusd")),
+        create.clone(),
+    )
+    .unwrap();
+
+    // a looser daily limit waits for the cool-down, a tighter weekly one applies at once
+    let res_loosen = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::SetStakeLimits {
+            limits: limits(2000, 1400),
+        },
+    )
+    .unwrap();
+    let event = &res_loosen.events[0];
+    assert_eq!("stake_limits_set", event.ty);
+    let effective = mock_env().block.time.plus_seconds(LIMIT_COOLDOWN);
+    assert_eq!(
+        Some(effective.seconds().to_string().as_str()),
+        event_attr(event, "effective")
+    );
+    let res_limits: PlayerLimitsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PlayerLimits {
+                player: "new_user".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(limits(1000, 1400), res_limits.limits.limits);
+    assert_eq!(
+        Some(PendingLimits {
+            limits: limits(2000, 1400),
+            effective,
+        }),
+        res_limits.limits.pending
+    );
+    assert_eq!(
+        GenericBalance {
+            native: vec![coin(600, "uluna"), coin(5000, "uusd")],
+            cw20: vec![],
+            cw721: vec![],
+        },
+        res_limits.daily_staked
+    );
+
+    // the next day the looser daily limit applies, the weekly one still counts yesterday
+    let mut env = mock_env();
+    env.block.time = effective;
+    let res_weekly = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("new_user", &coins(1000, "uluna")),
+        create.clone(),
+    );
+    assert_eq!(res_weekly, Err(ContractError::StakeLimitExceeded {}));
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("new_user", &coins(800, "uluna")),
+        create.clone(),
+    )
+    .unwrap();
+    let res_limits: PlayerLimitsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PlayerLimits {
+                player: "new_user".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(limits(2000, 1400), res_limits.limits.limits);
+    assert_eq!(None, res_limits.limits.pending);
+    assert_eq!(coins(800, "uluna"), res_limits.daily_staked.native);
+    assert_eq!(
+        vec![coin(1400, "uluna"), coin(5000, "uusd")],
+        res_limits.weekly_staked.native
+    );
+
+    // a self-exclusion lasts at most 5 years at once
+    for seconds in [MAX_SELF_EXCLUSION + 1, u64::MAX] {
+        let res_too_long = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("new_user3", &[]),
+            ExecuteMsg::SelfExclude { seconds },
+        );
+        assert_eq!(res_too_long, Err(ContractError::InvalidExclusion {}));
+    }
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("new_user3", &[]),
+        ExecuteMsg::SelfExclude {
+            seconds: MAX_SELF_EXCLUSION,
+        },
+    )
+    .unwrap();
+
+    // a self-exclusion refuses every stake and cannot be shortened
+    let res_exclude = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::SelfExclude { seconds: 3600 },
+    )
+    .unwrap();
+    let until = env.block.time.plus_seconds(3600).seconds().to_string();
+    assert_eq!("self_excluded", res_exclude.events[0].ty);
+    assert_eq!(
+        Some(until.as_str()),
+        event_attr(&res_exclude.events[0], "until")
+    );
+    let res_shorten = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::SelfExclude { seconds: 0 },
+    )
+    .unwrap();
+    assert_eq!(
+        Some(until.as_str()),
+        event_attr(&res_shorten.events[0], "until")
+    );
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("new_user2", &coins(10, "uusd")),
+        create.clone(),
+    )
+    .unwrap();
+    let wager_id = all_wager_ids(&deps.storage).unwrap().pop().unwrap();
+    let res_join = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("new_user", &coins(10, "uusd")),
+        ExecuteMsg::AddFundsNative { wager_id },
+    );
+    assert_eq!(res_join, Err(ContractError::SelfExcluded {}));
+
+    env.block.time = env.block.time.plus_seconds(3600);
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("new_user", &coins(10, "uusd")),
+        ExecuteMsg::AddFundsNative { wager_id },
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_zero_stake_caps() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check = instantiate(deps.as_mut(), mock_env(), creator, inst_msg).unwrap();
+
+    let daily = |amount: u128| StakeLimits {
+        daily: GenericBalance {
+            native: coins(amount, "uluna"),
+            cw20: vec![],
+            cw721: vec![],
+        },
+        weekly: GenericBalance::new(),
+        monthly: GenericBalance::new(),
+    };
+    let set_limits = |limits| ExecuteMsg::SetStakeLimits { limits };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        set_limits(daily(100)),
+    )
+    .unwrap();
+    // lowering a cap to zero tightens it at once
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        set_limits(daily(0)),
+    )
+    .unwrap();
+    let res_limits: PlayerLimitsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PlayerLimits {
+                player: "new_user".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(daily(0), res_limits.limits.limits);
+    assert_eq!(None, res_limits.limits.pending);
+
+    let create = ExecuteMsg::CreateWagerNative {
+        label: None,
+        stake: None,
+        stake_value: None,
+        series_length: None,
+    };
+    let res_create = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(1, "uluna")),
+        create.clone(),
+    );
+    assert_eq!(res_create, Err(ContractError::StakeLimitExceeded {}));
+
+    // side bets and queue entries count against the limits too
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &coins(10, "uluna")),
+        create,
+    )
+    .unwrap();
+    let wager_id = all_wager_ids(&deps.storage).unwrap().pop().unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user4", &coins(10, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id },
+    )
+    .unwrap();
+    let res_bet = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(1, "uluna")),
+        ExecuteMsg::PlaceSideBet {
+            wager_id,
+            side: Side::User1,
+        },
+    );
+    assert_eq!(res_bet, Err(ContractError::StakeLimitExceeded {}));
+    let res_queue = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(1, "uluna")),
+        ExecuteMsg::JoinQueue {
+            game_id: "chess".to_string(),
+            stake: coin(1, "uluna"),
+            max_rating_diff: None,
+        },
+    );
+    assert_eq!(res_queue, Err(ContractError::StakeLimitExceeded {}));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user3", &[]),
+        set_limits(daily(100)),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user3", &coins(80, "uluna")),
+        ExecuteMsg::PlaceSideBet {
+            wager_id,
+            side: Side::User1,
+        },
+    )
+    .unwrap();
+    let res_bet = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user3", &coins(30, "uluna")),
+        ExecuteMsg::PlaceSideBet {
+            wager_id,
+            side: Side::User1,
+        },
+    );
+    assert_eq!(res_bet, Err(ContractError::StakeLimitExceeded {}));
+}
+
+#[test]
+fn test_blocklist_and_force_cancel() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::GrantRole {
+            role: Role::Operator,
+            address: "operator".to_string(),
+            game_id: None,
+        },
+    )
+    .unwrap();
+
+    let create = ExecuteMsg::CreateWagerNative {
+        label: None,
+        stake: None,
+        stake_value: None,
+        series_length: None,
+    };
+    // an open wager and two matched ones of the cheater
+    execute_tracked(
+        &mut deps,
+        mock_info("cheater", &coins(1000, "uluna")),
+        create.clone(),
+    );
+    for wager_id in 2..=3 {
+        execute_tracked(
+            &mut deps,
+            mock_info("new_user", &coins(1000, "uluna")),
+            create.clone(),
+        );
+        execute_tracked(
+            &mut deps,
+            mock_info("cheater", &coins(1000, "uluna")),
+            ExecuteMsg::AddFundsNative { wager_id },
+        );
+    }
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        create.clone(),
+    );
+
+    let update = ExecuteMsg::UpdateBlocklist {
+        add: vec!["cheater".to_string()],
+        remove: vec![],
+    };
+    let res_not_operator = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        update.clone(),
+    );
+    assert_eq!(
+        res_not_operator,
+        Err(ContractError::MissingRole {
+            role: Role::Operator
+        })
+    );
+    let res_update = execute_tracked(&mut deps, mock_info("operator", &[]), update);
+    assert_eq!("blocklist_updated", res_update.events[0].ty);
+    assert_eq!(
+        Some("[\"cheater\"]"),
+        event_attr(&res_update.events[0], "added")
+    );
+    let blocked: BlockedResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Blocked {
+                address: "cheater".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(blocked.blocked);
+
+    // blocked players can neither create, join nor send tokens
+    let res_create = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("cheater", &coins(1000, "uluna")),
+        create,
+    );
+    assert_eq!(res_create, Err(ContractError::Blocked {}));
+    let res_join = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("cheater", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 4 },
+    );
+    assert_eq!(res_join, Err(ContractError::Blocked {}));
+    let res_receive = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "cheater".to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::AddFunds { wager_id: 4 }).unwrap(),
+        }),
+    );
+    assert_eq!(res_receive, Err(ContractError::Blocked {}));
+
+    let res_not_blocked = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("operator", &[]),
+        ExecuteMsg::ForceCancel { wager_id: 4 },
+    );
+    assert_eq!(res_not_blocked, Err(ContractError::NotBlocked {}));
+
+    // an open wager is refunded
+    let res_open = execute_tracked(
+        &mut deps,
+        mock_info("operator", &[]),
+        ExecuteMsg::ForceCancel { wager_id: 1 },
+    );
+    assert_eq!("wager_cancelled", res_open.events[0].ty);
+    assert_eq!(
+        res_open.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "cheater".to_string(),
+            amount: coins(1000, "uluna"),
+        })]
+    );
+
+    // a matched wager is refunded under the default policy
+    let res_refund = execute_tracked(
+        &mut deps,
+        mock_info("operator", &[]),
+        ExecuteMsg::ForceCancel { wager_id: 2 },
+    );
+    assert_eq!("wager_cancelled", res_refund.events[0].ty);
+    assert_eq!(2, res_refund.messages.len());
+
+    // and forfeited to the opponent under the forfeit policy
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator,
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            blocked_policy: Some(BlockedPolicy::Forfeit),
+            ..ConfigUpdate::default()
+        }),
+    )
+    .unwrap();
+    let res_forfeit = execute_tracked(
+        &mut deps,
+        mock_info("operator", &[]),
+        ExecuteMsg::ForceCancel { wager_id: 3 },
+    );
+    assert_eq!("wager_settled", res_forfeit.events[0].ty);
+    assert_eq!(
+        Some("new_user"),
+        event_attr(&res_forfeit.events[0], "winner")
+    );
+    assert_eq!(
+        res_forfeit.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: "new_user".to_string(),
+                amount: coins(1000, "uluna"),
+            });
+            2
+        ]
+    );
+    assert_eq!(vec![4], all_wager_ids(&deps.storage).unwrap());
+}
+
+#[test]
+fn test_offer_rematch() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    );
+
+    // the winner who asked for a rematch keeps the pot as claimable tokens
+    let request = ExecuteMsg::RequestRematch { wager_id: 1 };
+    let res_stranger = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("stranger", &[]),
+        request.clone(),
+    );
+    assert_eq!(res_stranger, Err(ContractError::Unauthorized {}));
+    let res_request = execute_tracked(&mut deps, mock_info("new_user", &[]), request);
+    assert_eq!("wager_rematch_requested", res_request.events[0].ty);
+    let res_settle = execute_tracked(
+        &mut deps,
+        creator,
+        ExecuteMsg::SendFunds {
+            wager_id: 1,
+            winner_address: Addr::unchecked("new_user"),
+        },
+    );
+    assert!(res_settle.messages.is_empty());
+    assert_eq!(
+        coins(2000, "uluna"),
+        CLAIMABLE
+            .load(&deps.storage, &Addr::unchecked("new_user"))
+            .unwrap()
+            .native
+    );
+
+    let rematch: Rematch = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Rematch {
+                settled_wager_id: 1,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(Addr::unchecked("new_user"), rematch.user2);
+    assert_eq!(coins(1000, "uluna"), rematch.stake.native);
+    assert_eq!(mock_env().block.height + REMATCH_PERIOD, rematch.expires);
+
+    let offer = |from_claimable| ExecuteMsg::OfferRematch {
+        settled_wager_id: 1,
+        from_claimable,
+    };
+    let res_stranger = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("stranger", &coins(1000, "uluna")),
+        offer(false),
+    );
+    assert_eq!(res_stranger, Err(ContractError::Unauthorized {}));
+
+    // the loser has no winnings to fund the rematch with
+    let res_no_claimable = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &[]),
+        offer(true),
+    );
+    assert_eq!(
+        res_no_claimable,
+        Err(ContractError::InsufficientClaimable {})
+    );
+    let res_offer = execute_tracked(&mut deps, mock_info("new_user", &[]), offer(true));
+    let event = &res_offer.events[0];
+    assert_eq!("wager_created", event.ty);
+    assert_eq!(Some("new_user"), event_attr(event, "user1"));
+    assert_eq!(Some("new_user2"), event_attr(event, "opponent"));
+    assert_eq!(Some("open"), event_attr(event, "status"));
+    assert_eq!(("rematch_of", "1"), attr_pair(&res_offer, "rematch_of"));
+    assert_eq!(
+        coins(1000, "uluna"),
+        CLAIMABLE
+            .load(&deps.storage, &Addr::unchecked("new_user"))
+            .unwrap()
+            .native
+    );
+    let res_again = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &coins(1000, "uluna")),
+        offer(false),
+    );
+    assert_eq!(res_again, Err(ContractError::NoRematch {}));
+
+    // only the opponent can accept
+    let res_stranger_join = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("stranger", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 2 },
+    );
+    assert_eq!(res_stranger_join, Err(ContractError::Unauthorized {}));
+    let res_accept = execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 2 },
+    );
+    assert_eq!(Some("matched"), event_attr(&res_accept.events[0], "status"));
+
+    // settled rematches can be rematched in turn, until the rematch expires
+    let res_settle = execute_tracked(
+        &mut deps,
+        mock_info("creator", &[]),
+        ExecuteMsg::SendFunds {
+            wager_id: 2,
+            winner_address: Addr::unchecked("new_user2"),
+        },
+    );
+    assert_eq!(2, res_settle.messages.len());
+    let revenge = ExecuteMsg::OfferRematch {
+        settled_wager_id: 2,
+        from_claimable: false,
+    };
+    let mut env = mock_env();
+    env.block.height += REMATCH_PERIOD + 1;
+    let res_expired = execute(
+        deps.as_mut(),
+        env,
+        mock_info("new_user", &coins(1000, "uluna")),
+        revenge.clone(),
+    );
+    assert_eq!(res_expired, Err(ContractError::RematchExpired {}));
+    let res_revenge = execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        revenge,
+    );
+    assert_eq!(
+        Some("new_user2"),
+        event_attr(&res_revenge.events[0], "opponent")
+    );
+
+    // a newer wager between the players replaces the rematch of the older one
+    let settle = |deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>, wager_id| {
+        execute_tracked(
+            deps,
+            mock_info("creator", &[]),
+            ExecuteMsg::SendFunds {
+                wager_id,
+                winner_address: Addr::unchecked("new_user"),
+            },
+        );
+    };
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 3 },
+    );
+    settle(&mut deps, 3);
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 4 },
+    );
+    settle(&mut deps, 4);
+    let rematch = |settled_wager_id| {
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Rematch { settled_wager_id },
+        )
+    };
+    assert!(rematch(3).is_err());
+    assert!(rematch(4).is_ok());
+}
+
+#[test]
+fn test_hill_wager() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            fee_bps: Some(100),
+            ..ConfigUpdate::default()
+        }),
+    )
+    .unwrap();
+
+    let res_create = execute_tracked(
+        &mut deps,
+        mock_info("champion", &coins(1000, "uluna")),
+        ExecuteMsg::CreateHillWager { label: None },
+    );
+    assert_eq!(Some("0"), event_attr(&res_create.events[0], "streak"));
+    execute_tracked(
+        &mut deps,
+        mock_info("challenger1", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("bettor", &coins(100, "uluna")),
+        ExecuteMsg::PlaceSideBet {
+            wager_id: 1,
+            side: Side::User1,
+        },
+    );
+    let res_cash_out = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("champion", &[]),
+        ExecuteMsg::CashOut { wager_id: 1 },
+    );
+    assert_eq!(res_cash_out, Err(ContractError::MatchInProgress {}));
+
+    // the champion keeps the pot less fees as their stake
+    let res_defend = execute_tracked(
+        &mut deps,
+        creator.clone(),
+        ExecuteMsg::SendFunds {
+            wager_id: 1,
+            winner_address: Addr::unchecked("champion"),
+        },
+    );
+    assert!(res_defend.messages.is_empty());
+    let event = &res_defend.events[0];
+    assert_eq!("wager_settled", event.ty);
+    assert_eq!(Some("settled"), event_attr(event, "status"));
+    assert_eq!(Some("1"), event_attr(event, "streak"));
+    // the next match is a new wager with its own side pool
+    assert_eq!(("next_id", "2"), attr_pair(&res_defend, "next_id"));
+    let event = &res_defend.events[1];
+    assert_eq!("wager_created", event.ty);
+    assert_eq!(Some("open"), event_attr(event, "status"));
+    assert_eq!("side_pool_closed", res_defend.events[2].ty);
+    let wager: Wager =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Wager { id: 2 }).unwrap()).unwrap();
+    assert_eq!(Addr::unchecked("champion"), wager.user1);
+    assert_eq!(coins(1980, "uluna"), wager.user1_balance.native);
+    assert_eq!(WagerStatus::Open, wager.status);
+
+    // challengers match the grown stake, and a winning challenger takes the hill
+    let res_short = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("challenger2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 2 },
+    );
+    assert_eq!(res_short, Err(ContractError::UnequalBalance {}));
+    execute_tracked(
+        &mut deps,
+        mock_info("challenger2", &coins(1980, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 2 },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("bettor", &coins(100, "uluna")),
+        ExecuteMsg::PlaceSideBet {
+            wager_id: 2,
+            side: Side::User2,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        creator.clone(),
+        ExecuteMsg::LockSideBets { wager_id: 2 },
+    );
+    let res_dethrone = execute_tracked(
+        &mut deps,
+        creator,
+        ExecuteMsg::SendFunds {
+            wager_id: 2,
+            winner_address: Addr::unchecked("challenger2"),
+        },
+    );
+    assert_eq!(Some("1"), event_attr(&res_dethrone.events[0], "streak"));
+    assert_eq!(("next_id", "3"), attr_pair(&res_dethrone, "next_id"));
+    // bets on both matches are claimable
+    for wager_id in 1..=2 {
+        let res_claim = execute_tracked(
+            &mut deps,
+            mock_info("bettor", &[]),
+            ExecuteMsg::ClaimSideBet { wager_id },
+        );
+        assert_eq!("side_bet_claimed", res_claim.events[0].ty);
+    }
+
+    let res_former = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("champion", &[]),
+        ExecuteMsg::CashOut { wager_id: 3 },
+    );
+    assert_eq!(res_former, Err(ContractError::Unauthorized {}));
+    let res_cash_out = execute_tracked(
+        &mut deps,
+        mock_info("challenger2", &[]),
+        ExecuteMsg::CashOut { wager_id: 3 },
+    );
+    assert_eq!("wager_cashed_out", res_cash_out.events[0].ty);
+    assert_eq!(
+        res_cash_out.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "challenger2".to_string(),
+            amount: coins(3922, "uluna"),
+        })]
+    );
+    assert!(all_wager_ids(&deps.storage).unwrap().is_empty());
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    let res_not_hill = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::CashOut { wager_id: 4 },
+    );
+    assert_eq!(res_not_hill, Err(ContractError::NotHill {}));
+}
+
+#[test]
+fn test_mutual_cancel_and_void() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    for wager_id in 1..=3 {
+        execute_tracked(
+            &mut deps,
+            mock_info("new_user", &coins(1000, "uluna")),
+            ExecuteMsg::CreateWagerNative {
+                label: None,
+                stake: None,
+                stake_value: None,
+                series_length: None,
+            },
+        );
+        if wager_id < 3 {
+            execute_tracked(
+                &mut deps,
+                mock_info("new_user2", &coins(1000, "uluna")),
+                ExecuteMsg::AddFundsNative { wager_id },
+            );
+        }
+    }
+
+    let res_stranger = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("stranger", &[]),
+        ExecuteMsg::ProposeCancel { wager_id: 1 },
+    );
+    assert_eq!(res_stranger, Err(ContractError::Unauthorized {}));
+    let res_unproposed = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &[]),
+        ExecuteMsg::AcceptCancel { wager_id: 1 },
+    );
+    assert_eq!(res_unproposed, Err(ContractError::NoCancelProposal {}));
+
+    let res_propose = execute_tracked(
+        &mut deps,
+        mock_info("new_user", &[]),
+        ExecuteMsg::ProposeCancel { wager_id: 1 },
+    );
+    assert_eq!("wager_cancel_proposed", res_propose.events[0].ty);
+    assert_eq!(
+        ("proposer", "new_user"),
+        attr_pair(&res_propose, "proposer")
+    );
+    let res_own = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::AcceptCancel { wager_id: 1 },
+    );
+    assert_eq!(res_own, Err(ContractError::NoCancelProposal {}));
+
+    let res_accept = execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &[]),
+        ExecuteMsg::AcceptCancel { wager_id: 1 },
+    );
+    assert_eq!("wager_cancelled", res_accept.events[0].ty);
+    assert_eq!(
+        res_accept.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: "new_user".to_string(),
+                amount: coins(1000, "uluna"),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "new_user2".to_string(),
+                amount: coins(1000, "uluna"),
+            }),
+        ]
+    );
+
+    // a proposal can be withdrawn by its proposer, and lapses once an arbiter votes
+    let retract = ExecuteMsg::RetractCancel { wager_id: 2 };
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &[]),
+        ExecuteMsg::ProposeCancel { wager_id: 2 },
+    );
+    let res_other = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        retract.clone(),
+    );
+    assert_eq!(res_other, Err(ContractError::NoCancelProposal {}));
+    let res_retract = execute_tracked(&mut deps, mock_info("new_user2", &[]), retract);
+    assert_eq!("wager_cancel_retracted", res_retract.events[0].ty);
+    let accept = ExecuteMsg::AcceptCancel { wager_id: 2 };
+    let res_retracted = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        accept.clone(),
+    );
+    assert_eq!(res_retracted, Err(ContractError::NoCancelProposal {}));
+
+    execute_tracked(
+        &mut deps,
+        creator.clone(),
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            required_approvals: Some(2),
+            ..ConfigUpdate::default()
+        }),
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &[]),
+        ExecuteMsg::ProposeCancel { wager_id: 2 },
+    );
+    execute_tracked(
+        &mut deps,
+        creator.clone(),
+        ExecuteMsg::VoteResult {
+            wager_id: 2,
+            winner: "new_user".to_string(),
+        },
+    );
+    let res_lapsed = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        accept,
+    );
+    assert_eq!(res_lapsed, Err(ContractError::NoCancelProposal {}));
+
+    // the arbiter voids matches that never started
+    let res_player_void = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::VoidWager { wager_id: 2 },
+    );
+    assert!(res_player_void.is_err());
+    let res_open_void = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        ExecuteMsg::VoidWager { wager_id: 3 },
+    );
+    assert_eq!(res_open_void, Err(ContractError::NotMatched {}));
+    let res_void = execute_tracked(&mut deps, creator, ExecuteMsg::VoidWager { wager_id: 2 });
+    assert_eq!("wager_voided", res_void.events[0].ty);
+    assert_eq!(Some("cancelled"), event_attr(&res_void.events[0], "status"));
+    assert_eq!(2, res_void.messages.len());
+    assert_eq!(vec![3], all_wager_ids(&deps.storage).unwrap());
+}
+
+#[test]
+fn test_forfeit() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator,
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            fee_bps: Some(1000),
+            ..ConfigUpdate::default()
+        }),
+    )
+    .unwrap();
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    let res_open = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &[]),
+        ExecuteMsg::Forfeit { wager_id: 1 },
+    );
+    assert_eq!(res_open, Err(ContractError::NotMatched {}));
+
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    );
+    let res_stranger = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("stranger", &[]),
+        ExecuteMsg::Forfeit { wager_id: 1 },
+    );
+    assert_eq!(res_stranger, Err(ContractError::Unauthorized {}));
+
+    // a session key cannot concede the stake of its player
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user2", &[]),
+        ExecuteMsg::GrantSession {
+            key: "session_key".to_string(),
+            expires: Expiration::Never {},
+            max_stake: Some(GenericBalance::from(cw20::Balance::from(coins(1, "uluna")))),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("session_key", &[]),
+        ExecuteMsg::AcceptSession {
+            player: "new_user2".to_string(),
+        },
+    )
+    .unwrap();
+    let res_session = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("session_key", &[]),
+        ExecuteMsg::Forfeit { wager_id: 1 },
+    );
+    assert_eq!(res_session, Err(ContractError::Unauthorized {}));
+
+    // the opponent is paid like a winner, fees included
+    let res_forfeit = execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &[]),
+        ExecuteMsg::Forfeit { wager_id: 1 },
+    );
+    assert_eq!(("action", "forfeit"), attr_pair(&res_forfeit, "action"));
+    let event = &res_forfeit.events[0];
+    assert_eq!("wager_settled", event.ty);
+    assert_eq!(Some("new_user"), event_attr(event, "winner"));
+    assert_eq!(Some("new_user2"), event_attr(event, "forfeited_by"));
+    assert_eq!(
+        res_forfeit.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: "new_user".to_string(),
+                amount: coins(900, "uluna"),
+            });
+            2
+        ]
+    );
+    assert!(all_wager_ids(&deps.storage).unwrap().is_empty());
+
+    // the forfeit stays on record once the wager is gone
+    let stats = |player: &str| -> PlayerStatsResponse {
+        from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PlayerStats {
+                    player: player.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        PlayerStatsResponse {
+            player: Addr::unchecked("new_user2"),
+            wins: 0,
+            losses: 1,
+            forfeits: 1,
+        },
+        stats("new_user2")
+    );
+    assert_eq!(
+        PlayerStatsResponse {
+            player: Addr::unchecked("new_user"),
+            wins: 1,
+            losses: 0,
+            forfeits: 0,
+        },
+        stats("new_user")
+    );
+}
+
+fn send_nft(sender: &str, token_id: &str, msg: Cw721HookMsg) -> ExecuteMsg {
+    ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: sender.to_string(),
+        token_id: token_id.to_string(),
+        msg: to_binary(&msg).unwrap(),
+    })
+}
+
+fn nft_transfer(recipient: &str, token_id: &str) -> SubMsg {
+    SubMsg::new(WasmMsg::Execute {
+        contract_addr: "items".to_string(),
+        msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+            recipient: recipient.to_string(),
+            token_id: token_id.to_string(),
+        })
+        .unwrap(),
+        funds: vec![],
+    })
+}
+
+#[test]
+fn test_nft_wager() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    let nft = |token_id: &str| {
+        GenericBalance::from_nft(Nft {
+            contract: Addr::unchecked("items"),
+            token_id: token_id.to_string(),
+        })
+    };
+    let tokens = GenericBalance::from(cw20::Balance::from(coins(1000, "uluna")));
+
+    // terms without an NFT are plain wagers
+    let res_no_nft = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("new_user", &coins(1000, "uluna")),
+        ExecuteMsg::CreateNftWager {
+            label: None,
+            stake: None,
+            counter_stake: tokens.clone(),
+        },
+    );
+    assert_eq!(res_no_nft, Err(ContractError::InvalidStake {}));
+
+    // a sword against tokens
+    let res_create = execute_tracked(
+        &mut deps,
+        mock_info("items", &[]),
+        send_nft(
+            "new_user",
+            "sword",
+            Cw721HookMsg::CreateNftWager {
+                label: None,
+                stake: None,
+                counter_stake: tokens.clone(),
+            },
+        ),
+    );
+    assert_eq!("wager_created", res_create.events[0].ty);
+    let wager = WAGERS.load(&deps.storage, 1.into()).unwrap();
+    assert_eq!(WagerStatus::Open, wager.status);
+    assert_eq!(nft("sword"), wager.user1_balance);
+    assert_eq!(Some(tokens.clone()), wager.counter_stake);
+
+    // the opponent matches the counter stake, not the sword
+    let res_wrong = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("items", &[]),
+        send_nft("new_user2", "shield", Cw721HookMsg::Deposit { wager_id: 1 }),
+    );
+    assert_eq!(res_wrong, Err(ContractError::StakeExceeded {}));
+    let res_join = execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(1000, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 1 },
+    );
+    assert_eq!("wager_joined", res_join.events[0].ty);
+
+    // the winner takes the sword and the tokens
+    let res_send_funds = execute_tracked(
+        &mut deps,
+        creator,
+        ExecuteMsg::SendFunds {
+            wager_id: 1,
+            winner_address: Addr::unchecked("new_user2"),
+        },
+    );
+    assert_eq!(
+        res_send_funds.messages,
+        vec![
+            nft_transfer("new_user2", "sword"),
+            SubMsg::new(BankMsg::Send {
+                to_address: "new_user2".to_string(),
+                amount: coins(1000, "uluna"),
+            }),
+        ]
+    );
+
+    // a sword against a shield, refunded once both players agree to cancel
+    execute_tracked(
+        &mut deps,
+        mock_info("items", &[]),
+        send_nft(
+            "new_user",
+            "sword",
+            Cw721HookMsg::CreateNftWager {
+                label: None,
+                stake: None,
+                counter_stake: nft("shield"),
+            },
+        ),
+    );
+    let res_deposit = execute_tracked(
+        &mut deps,
+        mock_info("items", &[]),
+        send_nft("new_user2", "shield", Cw721HookMsg::Deposit { wager_id: 2 }),
+    );
+    assert_eq!("wager_joined", res_deposit.events[0].ty);
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &[]),
+        ExecuteMsg::ProposeCancel { wager_id: 2 },
+    );
+    let res_cancel = execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &[]),
+        ExecuteMsg::AcceptCancel { wager_id: 2 },
+    );
+    assert_eq!(
+        res_cancel.messages,
+        vec![
+            nft_transfer("new_user", "sword"),
+            nft_transfer("new_user2", "shield"),
+        ]
+    );
+    assert!(TOTAL_ESCROWED.load(&deps.storage).unwrap().cw721.is_empty());
+}
+
+#[test]
+fn test_unaccepted_session() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check = instantiate(deps.as_mut(), mock_env(), creator, inst_msg).unwrap();
+
+    // an attacker grants a session on the victim's address
+    let grant = ExecuteMsg::GrantSession {
+        key: "victim".to_string(),
+        expires: Expiration::Never {},
+        max_stake: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("attacker", &[]), grant).unwrap();
+
+    // the victim still plays as themselves
+    execute_tracked(
+        &mut deps,
+        mock_info("victim", &coins(1000, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    let wager = WAGERS.load(&deps.storage, 1.into()).unwrap();
+    assert_eq!(Addr::unchecked("victim"), wager.user1);
+    let res_cancel = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("attacker", &[]),
+        ExecuteMsg::Cancel { wager_id: 1 },
+    );
+    assert!(res_cancel.is_err());
+
+    // and can drop the grant as the key
+    let res_revoke = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("victim", &[]),
+        ExecuteMsg::RevokeSession {
+            key: "victim".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!("session_revoked", res_revoke.events[0].ty);
+}
+
+#[test]
+fn test_game_arbiters_and_owner_transfer() {
+    let creator = mock_info("creator", &[]);
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let inst_msg = InstantiateMsg {
+        sender: creator.clone().sender,
+    };
+
+    let _initialization_check =
+        instantiate(deps.as_mut(), mock_env(), creator.clone(), inst_msg).unwrap();
+
+    let grant = |role, address: &str, game_id: Option<&str>| ExecuteMsg::GrantRole {
+        role,
+        address: address.to_string(),
+        game_id: game_id.map(String::from),
+    };
+    let res_scoped_guardian = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        grant(Role::Guardian, "guardian", Some("chess")),
+    );
+    assert_eq!(res_scoped_guardian, Err(ContractError::GameScopedRole {}));
+    let res_grant = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        grant(Role::Arbiter, "chess_arbiter", Some("chess")),
+    )
+    .unwrap();
+    assert_eq!(Some("chess"), event_attr(&res_grant.events[0], "game_id"));
+    let chess_arbiters: RolesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Roles {
+                role: Role::Arbiter,
+                game_id: Some("chess".to_string()),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        vec![Addr::unchecked("chess_arbiter")],
+        chess_arbiters.addresses
+    );
+
+    // wager 1 is a chess match from the queue, wager 2 has no game
+    for player in ["new_user", "new_user2"] {
+        execute_tracked(
+            &mut deps,
+            mock_info(player, &coins(10, "uluna")),
+            ExecuteMsg::JoinQueue {
+                game_id: String::from("chess"),
+                stake: coin(10, "uluna"),
+                max_rating_diff: None,
+            },
+        );
+    }
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user", &coins(10, "uluna")),
+        ExecuteMsg::CreateWagerNative {
+            label: None,
+            stake: None,
+            stake_value: None,
+            series_length: None,
+        },
+    );
+    execute_tracked(
+        &mut deps,
+        mock_info("new_user2", &coins(10, "uluna")),
+        ExecuteMsg::AddFundsNative { wager_id: 2 },
+    );
+    let send_funds = |wager_id| ExecuteMsg::SendFunds {
+        wager_id,
+        winner_address: Addr::unchecked("new_user"),
+    };
+
+    let res_other_game = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("chess_arbiter", &[]),
+        send_funds(2),
+    );
+    assert_eq!(
+        res_other_game,
+        Err(ContractError::MissingRole {
+            role: Role::Arbiter
+        })
+    );
+    execute_tracked(&mut deps, mock_info("chess_arbiter", &[]), send_funds(1));
+
+    // an owner who lost the role no longer settles wagers or receives fees
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        grant(Role::Owner, "owner2", None),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner2", &[]),
+        ExecuteMsg::RevokeRole {
+            role: Role::Owner,
+            address: "creator".to_string(),
+            game_id: None,
+        },
+    )
+    .unwrap();
+    let state: State =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(Addr::unchecked("owner2"), state.owner);
+    let res_old_owner = execute(deps.as_mut(), mock_env(), creator, send_funds(2));
+    assert_eq!(
+        res_old_owner,
+        Err(ContractError::MissingRole {
+            role: Role::Arbiter
+        })
+    );
+    execute_tracked(&mut deps, mock_info("owner2", &[]), send_funds(2));
+}